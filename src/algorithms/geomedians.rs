@@ -4,11 +4,15 @@ use std::fmt;
 use std::io::{self, BufWriter, Write};
 
 use traits::*;
+use stats::{Stats};
 use geo::spaces::*;
-use steinertree::{Node};
+use geo::points::{Point2d};
+use steinertree::{NodeMut};
+use algorithms::lp;
+use error::SteinerError;
 
 
-fn centroid<P: Point>(y: &Node<P>) -> P {
+fn centroid<P: Point>(y: &NodeMut<P>) -> P {
     let mut centroid = y.p().clone();
     centroid.mul(P::R::zero());
 
@@ -20,6 +24,17 @@ fn centroid<P: Point>(y: &Node<P>) -> P {
     centroid
 }
 
+/// Median of `cs`, the midpoint of the two middle values for an even count.
+fn median<R: Real>(mut cs: Vec<R>) -> R {
+    cs.sort_by(|a, b| a.cmp_nan_last(b));
+    let n = cs.len();
+    if n % 2 == 1 {
+        cs[n/2]
+    } else {
+        (cs[n/2 - 1] + cs[n/2]) * R::from(0.5)
+    }
+}
+
 
 
 #[derive(Debug, Clone)]
@@ -41,6 +56,13 @@ impl GeoMedianStepPrecisionErrorData {
 
 impl GeoMedianStepData for GeoMedianStepPrecisionErrorData { }
 
+impl Report for GeoMedianStepPrecisionErrorData {
+    fn report(&self) -> Stats {
+        Stats::new()
+            .count("precision_errors", self.precisionerrors)
+    }
+}
+
 impl fmt::Display for GeoMedianStepPrecisionErrorData {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         try!(writeln!(f, "Data for the geometric median step function:"));
@@ -68,6 +90,13 @@ impl GeoMedianStepFixedPointData {
 
 impl GeoMedianStepData for GeoMedianStepFixedPointData { }
 
+impl Report for GeoMedianStepFixedPointData {
+    fn report(&self) -> Stats {
+        Stats::new()
+            .count("fixed_points", self.fixedpoints)
+    }
+}
+
 impl fmt::Display for GeoMedianStepFixedPointData {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         try!(writeln!(f, "Data for the geometric median step function:"));
@@ -78,7 +107,7 @@ impl fmt::Display for GeoMedianStepFixedPointData {
 
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ChiaFrancoApprox<P: Point> {
     e: P::R,
     data: GeoMedianStepPrecisionErrorData
@@ -107,10 +136,10 @@ impl<P: Point> ChiaFrancoApprox<P> {
 impl<P: Point> GeoMedianStep<P, LpSpace<P::R>> for ChiaFrancoApprox<P> {
     type D = GeoMedianStepPrecisionErrorData;
 
-    fn init(&mut self, _: &mut Node<P>, _: &LpSpace<P::R>) {
+    fn init(&mut self, _: &mut NodeMut<P>, _: &LpSpace<P::R>) {
     }
 
-    fn step(&mut self, ynext: &mut P, y: &mut Node<P>, _: usize, geo: &LpSpace<P::R>) {
+    fn step(&mut self, ynext: &mut P, y: &mut NodeMut<P>, _: usize, geo: &LpSpace<P::R>) {
         debug_assert!(P::R::from(2.0) < geo.p());
 
         #[inline]
@@ -124,7 +153,7 @@ impl<P: Point> GeoMedianStep<P, LpSpace<P::R>> for ChiaFrancoApprox<P> {
         }
 
         #[inline]
-        fn yk<P: Point>(cf: &ChiaFrancoApprox<P>, y: &Node<P>, k: usize,
+        fn yk<P: Point>(cf: &ChiaFrancoApprox<P>, y: &NodeMut<P>, k: usize,
                         lambda: P::R, ptmp: &mut P, geo: &LpSpace<P::R>) -> P::R {
             let mut numerator = P::R::zero();
             let mut denominator = P::R::zero();
@@ -226,7 +255,7 @@ impl<P: Point> fmt::Display for ChiaFrancoApprox<P> {
 
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ChiaFranco<P: Point, F> {
     e: F,
     step: ChiaFrancoApprox<P>,
@@ -251,10 +280,10 @@ impl<P: Point, F: Fn(usize) -> P::R> ChiaFranco<P, F> {
 impl<P: Point, F: Fn(usize) -> P::R> GeoMedianStep<P, LpSpace<P::R>> for ChiaFranco<P, F> {
     type D = GeoMedianStepPrecisionErrorData;
 
-    fn init(&mut self, _: &mut Node<P>, _: &LpSpace<P::R>) {
+    fn init(&mut self, _: &mut NodeMut<P>, _: &LpSpace<P::R>) {
     }
 
-    fn step(&mut self, ynext: &mut P, y: &mut Node<P>, s: usize, geo: &LpSpace<P::R>) {
+    fn step(&mut self, ynext: &mut P, y: &mut NodeMut<P>, s: usize, geo: &LpSpace<P::R>) {
         self.step.set_epsilon((self.e)(s));
         self.step.step(ynext, y, s, geo);
     }
@@ -278,7 +307,7 @@ impl<P: Point, F: Fn(usize) -> P::R> fmt::Display for ChiaFranco<P, F> {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BrimbergLove {
     data: GeoMedianStepFixedPointData
 }
@@ -297,14 +326,14 @@ impl Default for BrimbergLove {
 impl<P: Point> GeoMedianStep<P, LpSpace<P::R>> for BrimbergLove {
     type D = GeoMedianStepFixedPointData;
 
-    fn init(&mut self, y: &mut Node<P>, geo: &LpSpace<P::R>) {
+    fn init(&mut self, y: &mut NodeMut<P>, geo: &LpSpace<P::R>) {
         if y.neighbours().any(|n| geo.dist(n.p(), y.p()) == P::R::zero()) {
             let cent = centroid(y);
             y.p_mut().clone_from(&cent);
         }
     }
 
-    fn step(&mut self, ynext: &mut P, y: &mut Node<P>, _: usize, geo: &LpSpace<P::R>) {
+    fn step(&mut self, ynext: &mut P, y: &mut NodeMut<P>, _: usize, geo: &LpSpace<P::R>) {
         debug_assert!(P::R::one() <= geo.p() && geo.p() <= P::R::from(2.0));
 
         ynext.mul(P::R::zero());
@@ -371,7 +400,7 @@ impl fmt::Display for BrimbergLove {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Weiszfeld {
     data: GeoMedianStepFixedPointData
 }
@@ -390,14 +419,14 @@ impl Default for Weiszfeld {
 impl<P: Point> GeoMedianStep<P, EuclideanSpace> for Weiszfeld {
     type D = GeoMedianStepFixedPointData;
 
-    fn init(&mut self, y: &mut Node<P>, geo: &EuclideanSpace) {
+    fn init(&mut self, y: &mut NodeMut<P>, geo: &EuclideanSpace) {
         if y.neighbours().any(|n| geo.dist(n.p(), y.p()) == P::R::zero()) {
             let cent = centroid(y);
             y.p_mut().clone_from(&cent);
         }
     }
 
-    fn step(&mut self, x: &mut P, node: &mut Node<P>, _: usize, geo: &EuclideanSpace) {
+    fn step(&mut self, x: &mut P, node: &mut NodeMut<P>, _: usize, geo: &EuclideanSpace) {
         let mut div_sum = P::R::zero();
         x.mul(P::R::zero());
 
@@ -434,7 +463,7 @@ impl fmt::Display for Weiszfeld {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Ostresh {
     data: GeoMedianStepPrecisionErrorData
 }
@@ -453,10 +482,10 @@ impl Default for Ostresh {
 impl<P: Point> GeoMedianStep<P, EuclideanSpace> for Ostresh {
     type D = GeoMedianStepPrecisionErrorData;
 
-    fn init(&mut self, _: &mut Node<P>, _: &EuclideanSpace) {
+    fn init(&mut self, _: &mut NodeMut<P>, _: &EuclideanSpace) {
     }
 
-    fn step(&mut self, x: &mut P, node: &mut Node<P>, _: usize, geo: &EuclideanSpace) {
+    fn step(&mut self, x: &mut P, node: &mut NodeMut<P>, _: usize, geo: &EuclideanSpace) {
         x.mul(P::R::zero());
         let mut s = P::R::zero();
         let (mut g, mut tmp) = (x.clone(), x.clone());
@@ -514,22 +543,34 @@ impl fmt::Display for Ostresh {
 
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct GeoMedianStepper<P: Point, I> {
     node_dist_cutoff: P::R,
+    max_steps: usize,
     step: I,
     data: GeoMedianStepperData
 }
 
 impl<P: Point, I> GeoMedianStepper<P, I> {
+    /// Steps a median with `step` until it moves less than `a`, or at most
+    /// 10000 steps; see `max_steps`.
     pub fn new(a: P::R, step: I) -> Self {
         GeoMedianStepper {
             node_dist_cutoff: a,
+            max_steps: 10000,
             step: step,
             data: GeoMedianStepperData::new()
         }
     }
 
+    /// Stops the iteration of a median after `n` steps even if it has not
+    /// converged, which is counted in `GeoMedianData::unconverged`.
+    /// Defaults to 10000; `usize::MAX` practically removes the limit.
+    pub fn max_steps(mut self, n: usize) -> Self {
+        self.max_steps = n;
+        self
+    }
+
     pub fn default_with_step(step: I) -> Self {
         Self::new(P::R::from(0.00001), step)
     }
@@ -550,12 +591,12 @@ impl<P, M, I> GeoMedian<P, M> for GeoMedianStepper<P, I>
 
     type D = GeoMedianStepperData;
 
-    fn init(&mut self, y: &mut Node<P>, geo: &M) {
+    fn init(&mut self, y: &mut NodeMut<P>, geo: &M) {
         self.data.inits += 1;
         self.step.init(y, geo);
     }
 
-    fn find(&mut self, node: &mut Node<P>, geo: &M) {
+    fn find(&mut self, node: &mut NodeMut<P>, geo: &M) {
         let start = Instant::now();
         let mut x = node.p().clone();
         let mut s: usize = 1;
@@ -567,8 +608,11 @@ impl<P, M, I> GeoMedian<P, M> for GeoMedianStepper<P, I>
 
             node.p_mut().clone_from(&x);
 
-            debug_assert!(change.is_number());
-            if change < self.node_dist_cutoff {
+            let converged = change < self.node_dist_cutoff;
+            if converged || !change.is_number() || s >= self.max_steps {
+                if !converged {
+                    self.data.unconverged += 1;
+                }
                 self.data.time += Instant::now() - start;
                 return;
             }
@@ -584,13 +628,18 @@ impl<P, M, I> GeoMedian<P, M> for GeoMedianStepper<P, I>
     fn print(&self, f: &mut fmt::Formatter, inde: u32) -> fmt::Result {
         try!(self.step.print(f, inde));
         write!(f, " stopping when the change in position \
-            (measured in the current space) got below {}", self.node_dist_cutoff)
+            (measured in the current space) got below {} or after {} steps",
+            self.node_dist_cutoff, self.max_steps)
     }
 
     fn print_data<W: Write>(&self, w: &mut W) -> io::Result<()> {
         try!(writeln!(w, "{}", self.data()));
         self.step.print_data(w)
     }
+
+    fn report(&self) -> Stats {
+        self.data.report().group("step", self.step.report())
+    }
 }
 
 impl<P, I> fmt::Display for GeoMedianStepper<P, I>
@@ -605,7 +654,8 @@ impl<P, I> fmt::Display for GeoMedianStepper<P, I>
 pub struct GeoMedianStepperData {
     time: Duration,
     total_steps: u64,
-    inits: u64
+    inits: u64,
+    unconverged: u64
 }
 
 impl GeoMedianStepperData {
@@ -613,7 +663,8 @@ impl GeoMedianStepperData {
         GeoMedianStepperData {
             time: Duration::new(0, 0),
             total_steps: 0,
-            inits: 0
+            inits: 0,
+            unconverged: 0
         }
     }
 
@@ -643,6 +694,20 @@ impl GeoMedianData for GeoMedianStepperData {
     fn time(&self) -> &Duration {
         &self.time
     }
+
+    fn unconverged(&self) -> u64 {
+        self.unconverged
+    }
+}
+
+impl Report for GeoMedianStepperData {
+    fn report(&self) -> Stats {
+        Stats::new()
+            .seconds("time", &self.time)
+            .count("steps", self.total_steps)
+            .count("inits", self.inits)
+            .count("unconverged", self.unconverged)
+    }
 }
 
 impl fmt::Display for GeoMedianStepperData {
@@ -652,10 +717,11 @@ impl fmt::Display for GeoMedianStepperData {
         }
 
         try!(writeln!(f, "Data for the geometric median iteration:"));
-        writeln!(f, "\tTotal time: {}", printdur(&self.time));
-        writeln!(f, "\tTotal steps: {}", self.total_steps);
-        writeln!(f, "\tAverage number of steps pr. geometric median: {}", self.average_steps());
-        writeln!(f, "\tAverage time pr. step: {}", self.average_time_step());
+        try!(writeln!(f, "\tTotal time: {}", printdur(&self.time)));
+        try!(writeln!(f, "\tTotal steps: {}", self.total_steps));
+        try!(writeln!(f, "\tGeometric medians stopped at the step limit: {}", self.unconverged));
+        try!(writeln!(f, "\tAverage number of steps pr. geometric median: {}", self.average_steps()));
+        try!(writeln!(f, "\tAverage time pr. step: {}", self.average_time_step()));
         writeln!(f, "\tAverage time pr. geometric median: {}", self.average_time_problem())
     }
 }
@@ -666,7 +732,7 @@ impl fmt::Display for GeoMedianStepperData {
 
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct GeoMedianEllipsoid<P, E>
     where P: Point, E: GeoMedian<P, EuclideanSpace> {
 
@@ -701,22 +767,35 @@ impl<P, S, E> GeoMedian<P, S> for GeoMedianEllipsoid<P, E>
 
     type D = GeoMedianEllipsoidData;
 
-    fn init(&mut self, y: &mut Node<P>, _: &S) {
+    fn init(&mut self, y: &mut NodeMut<P>, _: &S) {
         self.eucl_median.init(y, &EuclideanSpace);
     }
 
-    fn find(&mut self, node: &mut Node<P>, geo: &S) {
+    /// Panics if the Euclidean median does, see `try_find`.
+    fn find(&mut self, node: &mut NodeMut<P>, geo: &S) {
+        if let Err(e) = self.try_find(node, geo) {
+            panic!("{}", e);
+        }
+    }
+
+    /// Finds the median with the Euclidean median in the space where the
+    /// unit ball of `geo` is the unit sphere, and fails if it does. The
+    /// neighbours are restored either way.
+    fn try_find(&mut self, node: &mut NodeMut<P>, geo: &S) -> Result<(), SteinerError> {
         let start = Instant::now();
         for n in node.neighbours_mut() {
-            for (c, d) in n.p_mut().iter_mut().zip(geo.comps().iter()) {
+            for (c, d) in n.iter_mut().zip(geo.comps().iter()) {
                 *c = *c / *d;
             }
         }
+        for (c, d) in node.p_mut().iter_mut().zip(geo.comps().iter()) {
+            *c = *c / *d;
+        }
 
-        self.eucl_median.find(node, &EuclideanSpace);
+        let res = self.eucl_median.try_find(node, &EuclideanSpace);
 
         for n in node.neighbours_mut() {
-            for (c, d) in n.p_mut().iter_mut().zip(geo.comps().iter()) {
+            for (c, d) in n.iter_mut().zip(geo.comps().iter()) {
                 *c = *c * *d;
             }
         }
@@ -725,6 +804,8 @@ impl<P, S, E> GeoMedian<P, S> for GeoMedianEllipsoid<P, E>
         }
         self.data.time += Instant::now() - start;
         self.data.selftime = self.data.time - *self.eucl_median.data().time();
+        self.data.unconverged = self.eucl_median.data().unconverged();
+        res
     }
 
     fn data(&self) -> &Self::D {
@@ -747,6 +828,10 @@ impl<P, S, E> GeoMedian<P, S> for GeoMedianEllipsoid<P, E>
         try!(writeln!(w, "{}", self.data));
         self.eucl_median.print_data(w)
     }
+
+    fn report(&self) -> Stats {
+        self.data.report().group("median", self.eucl_median.report())
+    }
 }
 
 
@@ -763,14 +848,16 @@ impl<P, E> fmt::Display for GeoMedianEllipsoid<P, E>
 #[derive(Debug, Clone)]
 pub struct GeoMedianEllipsoidData {
     time: Duration,
-    selftime: Duration
+    selftime: Duration,
+    unconverged: u64
 }
 
 impl GeoMedianEllipsoidData {
     pub fn new() -> Self {
         GeoMedianEllipsoidData {
             time: Duration::new(0, 0),
-            selftime: Duration::new(0, 0)
+            selftime: Duration::new(0, 0),
+            unconverged: 0
         }
     }
 }
@@ -779,6 +866,19 @@ impl GeoMedianData for GeoMedianEllipsoidData {
     fn time(&self) -> &Duration {
         &self.time
     }
+
+    fn unconverged(&self) -> u64 {
+        self.unconverged
+    }
+}
+
+impl Report for GeoMedianEllipsoidData {
+    fn report(&self) -> Stats {
+        Stats::new()
+            .seconds("time", &self.time)
+            .seconds("transform_time", &self.selftime)
+            .count("unconverged", self.unconverged)
+    }
 }
 
 impl fmt::Display for GeoMedianEllipsoidData {
@@ -803,7 +903,7 @@ impl fmt::Display for GeoMedianEllipsoidData {
 
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Uteshev {
     data: UteshevData,
 }
@@ -814,31 +914,15 @@ impl Uteshev {
             data: UteshevData::new(),
         }
     }
-}
-
-impl Default for Uteshev {
-    fn default() -> Self {
-        Uteshev::new()
-    }
-}
-
-impl<P: Point> GeoMedian<P, EuclideanSpace> for Uteshev {
-    type D = UteshevData;
-
-    fn init(&mut self, y: &mut Node<P>, geo: &EuclideanSpace) {
-        if y.neighbours().any(|n| geo.dist(n.p(), y.p()) == P::R::zero()) {
-            let cent = centroid(y);
-            y.p_mut().clone_from(&cent);
-        }
-    }
-
-    fn find(&mut self, node: &mut Node<P>, geo: &EuclideanSpace) {
-        let start = Instant::now();
 
+    /// Uteshev's closed form for the point minimizing the sum of Euclidean
+    /// distances to `x`, `y` and `z`.
+    pub fn fermat_point<P: Point>(x: &P, y: &P, z: &P, geo: &EuclideanSpace) -> P {
+        /// Twice the area of the triangle, by Heron's formula
         #[inline]
         fn s<R: Real>(xy: R, xz: R, yz: R) -> R {
             R::from(0.5) * (
-                (xy + xz + yz) * (xy + xz) * (xy + yz) * (xz + yz)
+                (xy + xz + yz) * (xy + xz - yz) * (xy - xz + yz) * (xz + yz - xy)
             ).sqrt()
         }
 
@@ -849,47 +933,93 @@ impl<P: Point> GeoMedian<P, EuclideanSpace> for Uteshev {
                 + s
         }
 
-        let (mut p, mut i) = node.neighbours_data_mut();
-        let (x, y, z) = (i.next().unwrap(), i.next().unwrap(), i.next().unwrap());
         let (xy, xz, yz) = (geo.dist(x, y), geo.dist(x, z), geo.dist(y, z));
+
+        // Two coincident points outweigh the third
+        if xy == P::R::zero() || xz == P::R::zero() {
+            return x.clone();
+        } else if yz == P::R::zero() {
+            return y.clone();
+        }
+
         let (xy2, xz2, yz2) = (xy*xy, xz*xz, yz*yz);
         let cosx = (xy2 + xz2 - yz2) / (P::R::from(2.0) * xy * xz);
         let cosy = (xy2 + yz2 - xz2) / (P::R::from(2.0) * xy * yz);
         let cosz = (xz2 + yz2 - xy2) / (P::R::from(2.0) * xz * yz);
 
         if cosx <= P::R::from(-0.5) {
-            p.clone_from(x);
+            return x.clone();
         } else if cosy <= P::R::from(-0.5) {
-            p.clone_from(y);
+            return y.clone();
         } else if cosz <= P::R::from(-0.5) {
-            p.clone_from(z);
-        } else {
-            let s = s(xy, xz, yz);
-            let (cxr, cyr, czr) = (
-                ca(xy2, xz2, yz2, s).recip(),
-                ca(xy2, yz2, xz2, s).recip(),
-                ca(xz2, yz2, xy2, s).recip()
-            );
-
-            for (pk, (&xk, (&yk, &zk))) in p.iter_mut().zip(x.iter().zip(y.iter().zip(z.iter()))) {
-                let a = xk*cxr + yk*cyr + zk*czr;
-                if a.is_number() {
-                    *pk = a;
-                }
+            return z.clone();
+        }
+
+        let s = s(xy, xz, yz);
+        let (cxr, cyr, czr) = (
+            ca(xy2, xz2, yz2, s).recip(),
+            ca(xy2, yz2, xz2, s).recip(),
+            ca(xz2, yz2, xy2, s).recip()
+        );
+
+        let mut p = x.clone();
+        for (pk, (&xk, (&yk, &zk))) in p.iter_mut().zip(x.iter().zip(y.iter().zip(z.iter()))) {
+            let a = xk*cxr + yk*cyr + zk*czr;
+            if a.is_number() {
+                *pk = a;
             }
+        }
 
-            let mul = (cxr+cyr+czr).recip();
-            p.scale_mut(&mut |c| {
-                let a = mul*c;
-                if a.is_number() {
-                    a
-                } else {
-                    c
-                }
-            });
+        let mul = (cxr+cyr+czr).recip();
+        p.scale_mut(&mut |c| {
+            let a = mul*c;
+            if a.is_number() {
+                a
+            } else {
+                c
+            }
+        });
+        p
+    }
+}
+
+impl Default for Uteshev {
+    fn default() -> Self {
+        Uteshev::new()
+    }
+}
+
+impl<P: Point> GeoMedian<P, EuclideanSpace> for Uteshev {
+    type D = UteshevData;
+
+    fn init(&mut self, y: &mut NodeMut<P>, geo: &EuclideanSpace) {
+        if y.neighbours().any(|n| geo.dist(n.p(), y.p()) == P::R::zero()) {
+            let cent = centroid(y);
+            y.p_mut().clone_from(&cent);
+        }
+    }
+
+    /// Panics if the Steiner point does not have degree 3, see `try_find`.
+    fn find(&mut self, node: &mut NodeMut<P>, geo: &EuclideanSpace) {
+        if let Err(e) = self.try_find(node, geo) {
+            panic!("{}", e);
+        }
+    }
+
+    /// Moves `node` to the Fermat point of its three neighbours. Steiner
+    /// points of full topologies always have degree 3; for any other degree
+    /// the node is left untouched and `SteinerError::Degree` is returned.
+    fn try_find(&mut self, node: &mut NodeMut<P>, geo: &EuclideanSpace) -> Result<(), SteinerError> {
+        if node.degree() != 3 {
+            return Err(SteinerError::Degree { steiner_point: node.id(), degree: node.degree() });
         }
 
+        let start = Instant::now();
+        let (p, mut i) = node.neighbours_data_mut();
+        let (x, y, z) = (i.next().unwrap(), i.next().unwrap(), i.next().unwrap());
+        p.clone_from(&Uteshev::fermat_point(x, y, z, geo));
         self.data.time += Instant::now() - start;
+        Ok(())
     }
 
     fn data(&self) -> &Self::D {
@@ -932,6 +1062,13 @@ impl GeoMedianData for UteshevData {
     }
 }
 
+impl Report for UteshevData {
+    fn report(&self) -> Stats {
+        Stats::new()
+            .seconds("time", &self.time)
+    }
+}
+
 impl fmt::Display for UteshevData {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         fn printdur(dur: &Duration) -> f64 {
@@ -942,3 +1079,948 @@ impl fmt::Display for UteshevData {
         writeln!(f, "\tTotal time: {}", printdur(&self.time))
     }
 }
+
+
+
+#[derive(Debug, Clone)]
+pub struct CoordinateMedian {
+    data: CoordinateMedianData,
+}
+
+impl CoordinateMedian {
+    /// Exact geometric median in the rectilinear (L1) space, where the
+    /// problem separates into one median per coordinate.
+    pub fn new() -> Self {
+        CoordinateMedian {
+            data: CoordinateMedianData::new(),
+        }
+    }
+}
+
+impl Default for CoordinateMedian {
+    fn default() -> Self {
+        CoordinateMedian::new()
+    }
+}
+
+impl<P: Point> GeoMedian<P, LpSpace<L1>> for CoordinateMedian {
+    type D = CoordinateMedianData;
+
+    fn init(&mut self, _: &mut NodeMut<P>, _: &LpSpace<L1>) {
+    }
+
+    fn find(&mut self, node: &mut NodeMut<P>, _: &LpSpace<L1>) {
+        let start = Instant::now();
+
+        for k in 0..node.p().dim() {
+            let m = median(node.neighbours().map(|n| n.p().coords()[k]).collect());
+            node.p_mut().coords_mut()[k] = m;
+        }
+
+        self.data.time += Instant::now() - start;
+    }
+
+    fn data(&self) -> &Self::D {
+        &self.data
+    }
+
+    fn print(&self, f: &mut fmt::Formatter, _: u32) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+
+    fn print_data<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "{}", self.data)
+    }
+}
+
+impl fmt::Display for CoordinateMedian {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Coordinatewise median for the L1 space")
+    }
+}
+
+
+#[derive(Debug, Clone)]
+pub struct CoordinateMedianData {
+    time: Duration
+}
+
+impl CoordinateMedianData {
+    pub fn new() -> Self {
+        CoordinateMedianData {
+            time: Duration::new(0, 0)
+        }
+    }
+}
+
+impl GeoMedianData for CoordinateMedianData {
+    fn time(&self) -> &Duration {
+        &self.time
+    }
+}
+
+impl Report for CoordinateMedianData {
+    fn report(&self) -> Stats {
+        Stats::new()
+            .seconds("time", &self.time)
+    }
+}
+
+impl fmt::Display for CoordinateMedianData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn printdur(dur: &Duration) -> f64 {
+            (dur.as_secs() as f64) + (dur.subsec_nanos() as f64) / 1000000000.0
+        }
+
+        try!(writeln!(f, "Geometric median data for the coordinatewise median:"));
+        writeln!(f, "\tTotal time: {}", printdur(&self.time))
+    }
+}
+
+
+#[derive(Debug, Clone)]
+pub struct ChebyshevMedian {
+    iterations: usize,
+    data: ChebyshevMedianData,
+}
+
+impl ChebyshevMedian {
+    /// Geometric median in the L∞ space. In the plane L∞ is L1 rotated by
+    /// 45°, so the median is found exactly as the coordinatewise median of
+    /// the rotated neighbours. In other dimensions a subgradient method with
+    /// steps decreasing as 1/sqrt(k) is run for `iterations` steps, keeping
+    /// the best point seen.
+    pub fn new(iterations: usize) -> Self {
+        ChebyshevMedian {
+            iterations: iterations,
+            data: ChebyshevMedianData::new(),
+        }
+    }
+
+    fn find_planar<P: Point>(&mut self, node: &mut NodeMut<P>) {
+        let (us, vs): (Vec<P::R>, Vec<P::R>) = node.neighbours()
+            .map(|n| {
+                let c = n.p().coords();
+                (c[0] + c[1], c[0] - c[1])
+            })
+            .unzip();
+        let (u, v) = (median(us), median(vs));
+
+        let c = node.p_mut().coords_mut();
+        c[0] = (u + v) * P::R::from(0.5);
+        c[1] = (u - v) * P::R::from(0.5);
+    }
+
+    fn find_subgradient<P: Point>(&mut self, node: &mut NodeMut<P>, geo: &LpSpace<LInfinity>) {
+        let ns: Vec<P> = node.neighbours().map(|n| n.p().clone()).collect();
+        let cost = |y: &P| ns.iter().fold(P::R::zero(), |sum, n| sum + geo.dist(y, n));
+
+        let mut y = node.p().clone();
+        let mut best = y.clone();
+        let mut best_cost = cost(&y);
+        let step = best_cost / P::R::from(ns.len() as f64);
+
+        for k in 0..self.iterations {
+            let mut g = y.clone();
+            g.mul(P::R::zero());
+
+            for n in &ns {
+                let (mut i, mut m) = (0, P::R::zero());
+                for (j, (&yc, &nc)) in y.iter().zip(n.iter()).enumerate() {
+                    if (yc - nc).abs() > m {
+                        i = j;
+                        m = (yc - nc).abs();
+                    }
+                }
+
+                if m > P::R::zero() {
+                    let (yc, nc) = (y.coords()[i], n.coords()[i]);
+                    g.coords_mut()[i] += if yc > nc { P::R::one() } else { -P::R::one() };
+                }
+            }
+
+            self.data.subgradient_steps += 1;
+            let gnorm = g.dot(&g).sqrt();
+            if gnorm == P::R::zero() {
+                // Zero is a subgradient, so y is a median
+                best.clone_from(&y);
+                break;
+            }
+
+            g.mul(step / (gnorm * P::R::from((k + 1) as f64).sqrt()));
+            y.sub(&g);
+
+            let c = cost(&y);
+            if c < best_cost {
+                best.clone_from(&y);
+                best_cost = c;
+            }
+        }
+
+        node.p_mut().clone_from(&best);
+    }
+}
+
+impl Default for ChebyshevMedian {
+    fn default() -> Self {
+        ChebyshevMedian::new(200)
+    }
+}
+
+impl<P: Point> GeoMedian<P, LpSpace<LInfinity>> for ChebyshevMedian {
+    type D = ChebyshevMedianData;
+
+    fn init(&mut self, y: &mut NodeMut<P>, geo: &LpSpace<LInfinity>) {
+        if y.neighbours().any(|n| geo.dist(n.p(), y.p()) == P::R::zero()) {
+            let cent = centroid(y);
+            y.p_mut().clone_from(&cent);
+        }
+    }
+
+    fn find(&mut self, node: &mut NodeMut<P>, geo: &LpSpace<LInfinity>) {
+        let start = Instant::now();
+
+        if node.p().dim() == 2 {
+            self.find_planar(node);
+        } else {
+            self.find_subgradient(node, geo);
+        }
+
+        self.data.time += Instant::now() - start;
+    }
+
+    fn data(&self) -> &Self::D {
+        &self.data
+    }
+
+    fn print(&self, f: &mut fmt::Formatter, _: u32) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+
+    fn print_data<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "{}", self.data)
+    }
+}
+
+
+impl fmt::Display for ChebyshevMedian {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "L∞ median (exact in the plane, otherwise {} subgradient steps)", self.iterations)
+    }
+}
+
+
+#[derive(Debug, Clone)]
+pub struct ChebyshevMedianData {
+    time: Duration,
+    subgradient_steps: u64,
+}
+
+impl ChebyshevMedianData {
+    pub fn new() -> Self {
+        ChebyshevMedianData {
+            time: Duration::new(0, 0),
+            subgradient_steps: 0,
+        }
+    }
+
+    pub fn subgradient_steps(&self) -> u64 {
+        self.subgradient_steps
+    }
+}
+
+impl GeoMedianData for ChebyshevMedianData {
+    fn time(&self) -> &Duration {
+        &self.time
+    }
+}
+
+impl Report for ChebyshevMedianData {
+    fn report(&self) -> Stats {
+        Stats::new()
+            .seconds("time", &self.time)
+            .count("subgradient_steps", self.subgradient_steps)
+    }
+}
+
+impl fmt::Display for ChebyshevMedianData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn printdur(dur: &Duration) -> f64 {
+            (dur.as_secs() as f64) + (dur.subsec_nanos() as f64) / 1000000000.0
+        }
+
+        try!(writeln!(f, "Geometric median data for the L∞ median:"));
+        try!(writeln!(f, "\tTotal time: {}", printdur(&self.time)));
+        writeln!(f, "\tSubgradient steps: {}", self.subgradient_steps)
+    }
+}
+
+
+
+#[derive(Debug, Clone)]
+pub struct PolytopeMedian {
+    data: PolytopeMedianData,
+}
+
+impl PolytopeMedian {
+    /// Exact geometric median in a polytope space. With facet normals `a`
+    /// and neighbours `b_j` it solves the linear program
+    ///
+    /// min sum t_j  s.t.  <a, y - b_j> <= t_j  for all a and j
+    ///
+    /// where `y = y+ - y-` is written relative to the centroid of the
+    /// neighbours.
+    pub fn new() -> Self {
+        PolytopeMedian {
+            data: PolytopeMedianData::new(),
+        }
+    }
+}
+
+impl Default for PolytopeMedian {
+    fn default() -> Self {
+        PolytopeMedian::new()
+    }
+}
+
+impl<P: Point> GeoMedian<P, PolytopeSpace<P>> for PolytopeMedian {
+    type D = PolytopeMedianData;
+
+    fn init(&mut self, _: &mut NodeMut<P>, _: &PolytopeSpace<P>) {
+    }
+
+    fn find(&mut self, node: &mut NodeMut<P>, geo: &PolytopeSpace<P>) {
+        let start = Instant::now();
+
+        let cent = centroid(node);
+        let ns: Vec<Vec<f64>> = node.neighbours()
+            .map(|n| n.p().iter().zip(cent.iter()).map(|(&c, &o)| (c - o).into()).collect())
+            .collect();
+        let normals: Vec<Vec<f64>> = geo.normals().iter()
+            .map(|a| a.iter().map(|&c| c.into()).collect())
+            .collect();
+        let (d, k) = (cent.dim(), ns.len());
+
+        // Variables are y+ (d), y- (d) and t (k)
+        let mut a = Vec::with_capacity(normals.len() * k);
+        let mut b = Vec::with_capacity(normals.len() * k);
+        for (j, n) in ns.iter().enumerate() {
+            for normal in normals.iter() {
+                let mut row = vec![0.0; 2*d + k];
+                for (i, &c) in normal.iter().enumerate() {
+                    row[i] = c;
+                    row[d + i] = -c;
+                }
+                row[2*d + j] = -1.0;
+                a.push(row);
+                b.push(normal.iter().zip(n.iter()).fold(0.0, |s, (&c, &x)| s + c*x));
+            }
+        }
+        let mut c = vec![0.0; 2*d + k];
+        for cj in c[2*d..].iter_mut() {
+            *cj = -1.0;
+        }
+
+        self.data.lps += 1;
+        let mut y = cent.clone();
+        let solved = match lp::maximize(&a, &b, &c) {
+            lp::LpResult::Optimal(x, _) => {
+                for (i, yc) in y.iter_mut().enumerate() {
+                    *yc += P::R::from(x[i] - x[d + i]);
+                }
+                y.iter().all(|c| c.is_number())
+            },
+            _ => false
+        };
+        // Only possible with non-finite coordinates, the point then stays
+        if solved {
+            node.p_mut().clone_from(&y);
+        } else {
+            self.data.unsolved += 1;
+        }
+
+        self.data.time += Instant::now() - start;
+    }
+
+    fn data(&self) -> &Self::D {
+        &self.data
+    }
+
+    fn print(&self, f: &mut fmt::Formatter, _: u32) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+
+    fn print_data<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "{}", self.data)
+    }
+}
+
+impl<R: Real> GeoMedian<Point2d<R>, LambdaSpace<R>> for PolytopeMedian {
+    type D = PolytopeMedianData;
+
+    fn init(&mut self, y: &mut NodeMut<Point2d<R>>, geo: &LambdaSpace<R>) {
+        GeoMedian::init(self, y, geo.polytope())
+    }
+
+    fn find(&mut self, node: &mut NodeMut<Point2d<R>>, geo: &LambdaSpace<R>) {
+        GeoMedian::find(self, node, geo.polytope())
+    }
+
+    fn data(&self) -> &Self::D {
+        &self.data
+    }
+
+    fn print(&self, f: &mut fmt::Formatter, _: u32) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+
+    fn print_data<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "{}", self.data)
+    }
+}
+
+impl fmt::Display for PolytopeMedian {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Exact polytope space median by linear programming")
+    }
+}
+
+
+#[derive(Debug, Clone)]
+pub struct PolytopeMedianData {
+    time: Duration,
+    lps: u64,
+    unsolved: u64,
+}
+
+impl PolytopeMedianData {
+    pub fn new() -> Self {
+        PolytopeMedianData {
+            time: Duration::new(0, 0),
+            lps: 0,
+            unsolved: 0,
+        }
+    }
+
+    pub fn lps(&self) -> u64 {
+        self.lps
+    }
+
+    /// Number of linear programs without an optimal solution, which left
+    /// the Steiner point where it was.
+    pub fn unsolved(&self) -> u64 {
+        self.unsolved
+    }
+}
+
+impl GeoMedianData for PolytopeMedianData {
+    fn time(&self) -> &Duration {
+        &self.time
+    }
+
+    fn unconverged(&self) -> u64 {
+        self.unsolved
+    }
+}
+
+impl Report for PolytopeMedianData {
+    fn report(&self) -> Stats {
+        Stats::new()
+            .seconds("time", &self.time)
+            .count("lps", self.lps)
+            .count("unsolved", self.unsolved)
+    }
+}
+
+impl fmt::Display for PolytopeMedianData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn printdur(dur: &Duration) -> f64 {
+            (dur.as_secs() as f64) + (dur.subsec_nanos() as f64) / 1000000000.0
+        }
+
+        try!(writeln!(f, "Geometric median data for the polytope space median:"));
+        try!(writeln!(f, "\tTotal time: {}", printdur(&self.time)));
+        try!(writeln!(f, "\tLinear programs solved: {}", self.lps));
+        writeln!(f, "\tLinear programs without an optimal solution: {}", self.unsolved)
+    }
+}
+
+
+
+#[derive(Debug, Clone)]
+pub struct LpNewton {
+    max_iterations: usize,
+    tolerance: f64,
+    data: LpNewtonData,
+}
+
+impl LpNewton {
+    /// Geometric median in a planar Lp space without smoothing the norm.
+    ///
+    /// A neighbour is the median exactly when the sum of the gradients of
+    /// the distances to the other neighbours has dual norm at most its
+    /// multiplicity, which is checked first. Otherwise the median is not a
+    /// neighbour, the sum of distances is smooth around it and a damped
+    /// Newton method with backtracking converges to it. The iteration stops
+    /// when a step is shorter than `tolerance` relative to the spread of the
+    /// neighbours or after `max_iterations` steps. For p=1 the exact
+    /// coordinatewise median is used instead.
+    pub fn new(max_iterations: usize, tolerance: f64) -> Self {
+        LpNewton {
+            max_iterations: max_iterations,
+            tolerance: tolerance,
+            data: LpNewtonData::new(),
+        }
+    }
+
+    pub fn max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Gradient and Hessian of `||v||_p` at `v != 0`.
+    fn derivatives(v: [f64; 2], p: f64) -> ([f64; 2], [[f64; 2]; 2]) {
+        let n = (v[0].abs().powf(p) + v[1].abs().powf(p)).powf(p.recip());
+        let g = [
+            v[0].signum() * (v[0].abs() / n).powf(p - 1.0),
+            v[1].signum() * (v[1].abs() / n).powf(p - 1.0)
+        ];
+        let d = [
+            (p - 1.0) * v[0].abs().powf(p - 2.0) / n.powf(p - 1.0),
+            (p - 1.0) * v[1].abs().powf(p - 2.0) / n.powf(p - 1.0)
+        ];
+        let h = [
+            [d[0] - (p - 1.0) * g[0] * g[0] / n, -(p - 1.0) * g[0] * g[1] / n],
+            [-(p - 1.0) * g[1] * g[0] / n, d[1] - (p - 1.0) * g[1] * g[1] / n]
+        ];
+        (g, h)
+    }
+
+    fn solve(&mut self, ns: &[[f64; 2]], y: [f64; 2], p: f64) -> [f64; 2] {
+        let norm = |v: [f64; 2]| (v[0].abs().powf(p) + v[1].abs().powf(p)).powf(p.recip());
+        let cost = |y: [f64; 2]| ns.iter().fold(0.0, |s, a| s + norm([y[0] - a[0], y[1] - a[1]]));
+        let q = p / (p - 1.0);
+
+        for a in ns.iter() {
+            let mut weight = 0.0;
+            let mut r = [0.0, 0.0];
+            for b in ns.iter() {
+                let v = [a[0] - b[0], a[1] - b[1]];
+                if v == [0.0, 0.0] {
+                    weight += 1.0;
+                } else {
+                    let (g, _) = Self::derivatives(v, p);
+                    r = [r[0] + g[0], r[1] + g[1]];
+                }
+            }
+
+            if (r[0].abs().powf(q) + r[1].abs().powf(q)).powf(q.recip()) <= weight {
+                self.data.vertex_solutions += 1;
+                return *a;
+            }
+        }
+
+        let spread = ns.iter().fold(0.0, |m: f64, a| m.max(norm([a[0] - ns[0][0], a[1] - ns[0][1]])));
+        let mut y = if ns.iter().any(|a| *a == y) || !(y[0].is_finite() && y[1].is_finite()) {
+            let k = ns.len() as f64;
+            ns.iter().fold([0.0, 0.0], |c, a| [c[0] + a[0] / k, c[1] + a[1] / k])
+        } else {
+            y
+        };
+        let mut fy = cost(y);
+
+        for _ in 0..self.max_iterations {
+            self.data.iterations += 1;
+
+            let (mut g, mut h) = ([0.0, 0.0], [[0.0, 0.0], [0.0, 0.0]]);
+            for a in ns.iter() {
+                let v = [y[0] - a[0], y[1] - a[1]];
+                if v == [0.0, 0.0] {
+                    continue;
+                }
+                let (ga, ha) = Self::derivatives(v, p);
+                for i in 0..2 {
+                    g[i] += ga[i];
+                    for j in 0..2 {
+                        h[i][j] += ha[i][j];
+                    }
+                }
+            }
+
+            let gn = (g[0] * g[0] + g[1] * g[1]).sqrt();
+            if gn == 0.0 {
+                break;
+            }
+
+            // Newton direction, or the steepest descent direction where the
+            // Hessian is unusable (p < 2 on a coordinate axis)
+            let det = h[0][0] * h[1][1] - h[0][1] * h[1][0];
+            let mut dir = [
+                -(h[1][1] * g[0] - h[0][1] * g[1]) / det,
+                -(h[0][0] * g[1] - h[1][0] * g[0]) / det
+            ];
+            if !(det > 0.0 && h[0][0] > 0.0 && dir[0].is_finite() && dir[1].is_finite()) {
+                dir = [-g[0] / gn * spread, -g[1] / gn * spread];
+            }
+
+            let mut t = 1.0;
+            let slope = g[0] * dir[0] + g[1] * dir[1];
+            let (mut ynext, mut fnext);
+            loop {
+                ynext = [y[0] + t * dir[0], y[1] + t * dir[1]];
+                fnext = cost(ynext);
+                if fnext <= fy + 1e-4 * t * slope || t < 1e-20 {
+                    break;
+                }
+                t *= 0.5;
+            }
+
+            let step = norm([ynext[0] - y[0], ynext[1] - y[1]]);
+            if fnext < fy {
+                y = ynext;
+                fy = fnext;
+            }
+            if step <= self.tolerance * spread {
+                break;
+            }
+        }
+
+        y
+    }
+}
+
+impl Default for LpNewton {
+    fn default() -> Self {
+        LpNewton::new(100, 1e-9)
+    }
+}
+
+impl<R: Real> GeoMedian<Point2d<R>, LpSpace<R>> for LpNewton {
+    type D = LpNewtonData;
+
+    fn init(&mut self, _: &mut NodeMut<Point2d<R>>, _: &LpSpace<R>) {
+    }
+
+    fn find(&mut self, node: &mut NodeMut<Point2d<R>>, geo: &LpSpace<R>) {
+        let start = Instant::now();
+
+        let p: f64 = geo.p().into();
+        if p == 1.0 {
+            for k in 0..2 {
+                let m = median(node.neighbours().map(|n| n.p().coords()[k]).collect());
+                node.p_mut().coords_mut()[k] = m;
+            }
+        } else {
+            let ns: Vec<[f64; 2]> = node.neighbours()
+                .map(|n| [n.p().coords()[0].into(), n.p().coords()[1].into()])
+                .collect();
+            let y = [node.p().coords()[0].into(), node.p().coords()[1].into()];
+
+            let y = self.solve(&ns, y, p);
+            node.p_mut().coords_mut()[0] = R::from(y[0]);
+            node.p_mut().coords_mut()[1] = R::from(y[1]);
+        }
+
+        self.data.time += Instant::now() - start;
+    }
+
+    fn data(&self) -> &Self::D {
+        &self.data
+    }
+
+    fn print(&self, f: &mut fmt::Formatter, _: u32) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+
+    fn print_data<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "{}", self.data)
+    }
+}
+
+impl fmt::Display for LpNewton {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Planar Lp median by Newton's method (at most {} iterations, relative \
+            tolerance {})", self.max_iterations, self.tolerance)
+    }
+}
+
+
+#[derive(Debug, Clone)]
+pub struct LpNewtonData {
+    time: Duration,
+    iterations: u64,
+    vertex_solutions: u64,
+}
+
+impl LpNewtonData {
+    pub fn new() -> Self {
+        LpNewtonData {
+            time: Duration::new(0, 0),
+            iterations: 0,
+            vertex_solutions: 0,
+        }
+    }
+
+    pub fn iterations(&self) -> u64 {
+        self.iterations
+    }
+
+    pub fn vertex_solutions(&self) -> u64 {
+        self.vertex_solutions
+    }
+}
+
+impl GeoMedianData for LpNewtonData {
+    fn time(&self) -> &Duration {
+        &self.time
+    }
+}
+
+impl Report for LpNewtonData {
+    fn report(&self) -> Stats {
+        Stats::new()
+            .seconds("time", &self.time)
+            .count("iterations", self.iterations)
+            .count("vertex_solutions", self.vertex_solutions)
+    }
+}
+
+impl fmt::Display for LpNewtonData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn printdur(dur: &Duration) -> f64 {
+            (dur.as_secs() as f64) + (dur.subsec_nanos() as f64) / 1000000000.0
+        }
+
+        try!(writeln!(f, "Geometric median data for the planar Lp Newton median:"));
+        try!(writeln!(f, "\tTotal time: {}", printdur(&self.time)));
+        try!(writeln!(f, "\tNewton iterations: {}", self.iterations));
+        writeln!(f, "\tMedians at a neighbour: {}", self.vertex_solutions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use steinertree::SteinerTree;
+    use generators::Generator;
+    use geo::points::Point3d;
+
+    /// A Steiner point at the centroid of `ns`, joined to all of them.
+    fn star<P: Point>(ns: &[P]) -> SteinerTree<P> {
+        let mut st = SteinerTree::new(ns, &[], &[]);
+        let all: Vec<usize> = (0..ns.len()).collect();
+        st.push_node(ns[0].clone(), &all, false);
+        st
+    }
+
+    /// Runs `median` on the Steiner point of `st` until it stops moving.
+    fn solve<P, M, G>(median: &mut G, st: &mut SteinerTree<P>, geo: &M)
+        where P: Point, M: MinkowskiSpace<P>, G: GeoMedian<P, M> {
+
+        let s = st.node_count() - 1;
+        median.init(&mut st.steiner_i(s), geo);
+        for _ in 0..100 {
+            median.find(&mut st.steiner_i(s), geo);
+        }
+    }
+
+    /// Whether no small move of the Steiner point shortens the star, which
+    /// makes it a median as the length is convex.
+    fn is_median<P, M>(st: &SteinerTree<P>, geo: &M, tol: f64) -> bool
+        where P: Point + FromCoords, M: MinkowskiSpace<P> {
+
+        let s = st.node_count() - 1;
+        let len: f64 = st.len(geo).into();
+        let dim = st.i(s).p().dim();
+        let mut dirs = Generator::new(7, dim);
+        (0..200).all(|_| {
+            let mut moved = st.clone();
+            let d: Vec<P> = dirs.sphere(1, 1e-3);
+            moved.steiner_i(s).p_mut().add(&d[0]);
+            let moved_len: f64 = moved.len(geo).into();
+            moved_len >= len - tol
+        })
+    }
+
+    fn p2(x: f64, y: f64) -> Point2d<f64> {
+        Point2d::new([x, y])
+    }
+
+    #[test]
+    fn chebyshev_median_in_the_plane() {
+        let geo = LpSpace::new(LInfinity);
+        for seed in 0..10 {
+            let ns: Vec<Point2d<f64>> = Generator::new(seed, 2).uniform(3 + seed as usize % 3);
+            let mut st = star(&ns);
+            solve(&mut ChebyshevMedian::default(), &mut st, &geo);
+            assert!(is_median(&st, &geo, 1e-12));
+        }
+    }
+
+    #[test]
+    fn chebyshev_median_by_subgradients() {
+        let geo = LpSpace::new(LInfinity);
+        for seed in 0..5 {
+            let ns: Vec<Point3d<f64>> = Generator::new(seed, 3).uniform(3);
+            let mut st = star(&ns);
+            solve(&mut ChebyshevMedian::default(), &mut st, &geo);
+            assert!(is_median(&st, &geo, 1e-3));
+        }
+    }
+
+    #[test]
+    fn linfinity_square() {
+        // L∞ is L1 rotated by 45°, where the corners of the square form a
+        // diamond joined by a cross through its centre
+        let geo = LpSpace::new(LInfinity);
+        let t = vec![p2(0.0, 0.0), p2(1.0, 0.0), p2(1.0, 1.0), p2(0.0, 1.0)];
+        let st = ::algorithms::steinerbnb::SteinerBnB::default_linfinity().find(t, &geo);
+        assert!((st.len(&geo) - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn coordinate_median_in_l1() {
+        let geo = LpSpace::new(L1);
+        for seed in 0..5 {
+            let ns: Vec<Point3d<f64>> = Generator::new(seed, 3).uniform(3 + seed as usize % 3);
+            let mut st = star(&ns);
+            solve(&mut CoordinateMedian::default(), &mut st, &geo);
+            assert!(is_median(&st, &geo, 1e-12));
+        }
+    }
+
+    fn hexagonal() -> PolytopeSpace<Point2d<f64>> {
+        let h = 3f64.sqrt() / 2.0;
+        PolytopeSpace::from_vertices(vec![p2(1.0, 0.0), p2(0.5, h), p2(-0.5, h)])
+    }
+
+    #[test]
+    fn polytope_median_is_exact() {
+        let geo = hexagonal();
+        for seed in 0..10 {
+            let ns: Vec<Point2d<f64>> = Generator::new(seed, 2).uniform(3 + seed as usize % 4);
+            let mut st = star(&ns);
+            let mut median = PolytopeMedian::default();
+            solve(&mut median, &mut st, &geo);
+            assert!(is_median(&st, &geo, 1e-9));
+            assert_eq!(median.data.unconverged(), 0);
+        }
+    }
+
+    #[test]
+    fn unsolved_polytope_medians_are_counted() {
+        let geo = hexagonal();
+        let mut st = star(&[p2(0.0, 0.0), p2(1.0, 0.0), p2(::std::f64::INFINITY, 1.0)]);
+        *st.steiner_i(3).p_mut() = p2(0.5, 0.5);
+        let mut median = PolytopeMedian::default();
+        median.find(&mut st.steiner_i(3), &geo);
+        assert_eq!(median.data.unsolved(), 1);
+        assert_eq!(median.data.unconverged(), 1);
+        assert_eq!(*st.i(3).p(), p2(0.5, 0.5));
+    }
+
+    #[test]
+    fn lambda_median_is_exact() {
+        for lambda in 2..6 {
+            let geo = LambdaSpace::new(lambda);
+            let ns: Vec<Point2d<f64>> = Generator::new(lambda as u64, 2).uniform(4);
+            let mut st = star(&ns);
+            solve(&mut PolytopeMedian::default(), &mut st, &geo);
+            assert!(is_median(&st, &geo, 1e-9));
+        }
+    }
+
+    #[test]
+    fn uteshev_finds_the_fermat_point() {
+        for seed in 0..10 {
+            let ns: Vec<Point3d<f64>> = Generator::new(seed, 3).uniform(3);
+            let mut st = star(&ns);
+            solve(&mut Uteshev::default(), &mut st, &EuclideanSpace);
+            assert!(is_median(&st, &EuclideanSpace, 1e-12));
+        }
+
+        // An angle of at least 120° makes its vertex the median
+        let mut st = star(&[p2(0.0, 0.0), p2(1.0, 0.0), p2(-0.6, 0.1)]);
+        solve(&mut Uteshev::default(), &mut st, &EuclideanSpace);
+        assert_eq!(*st.i(3).p(), p2(0.0, 0.0));
+    }
+
+    #[test]
+    fn uteshev_rejects_other_degrees() {
+        let mut st = star(&[p2(0.0, 0.0), p2(1.0, 0.0), p2(1.0, 1.0), p2(0.0, 1.0)]);
+        let before = st.i(4).p().clone();
+        let err = Uteshev::default().try_find(&mut st.steiner_i(4), &EuclideanSpace);
+        assert_eq!(err, Err(SteinerError::Degree { steiner_point: 4, degree: 4 }));
+        assert_eq!(*st.i(4).p(), before);
+
+        let geo = EllipseSpace::new(2.0, 1.0);
+        let err = GeoMedianEllipsoid::default().try_find(&mut st.steiner_i(4), &geo);
+        assert_eq!(err, Err(SteinerError::Degree { steiner_point: 4, degree: 4 }));
+        assert_eq!(*st.i(0).p(), p2(0.0, 0.0));
+        assert_eq!(*st.i(2).p(), p2(1.0, 1.0));
+
+        let mut rmt = ::algorithms::rmt::GeoMedianIter::default();
+        assert_eq!(rmt.try_find(&mut st, &EuclideanSpace),
+            Err(SteinerError::Degree { steiner_point: 4, degree: 4 }));
+    }
+
+    #[test]
+    fn uteshev_in_an_ellipse_space() {
+        let geo = EllipseSpace::new(2.0, 0.5);
+        for seed in 0..10 {
+            let ns: Vec<Point2d<f64>> = Generator::new(seed, 2).uniform(3);
+            let mut st = star(&ns);
+            solve(&mut GeoMedianEllipsoid::default(), &mut st, &geo);
+            assert!(is_median(&st, &geo, 1e-12));
+        }
+    }
+
+    #[test]
+    fn lp_newton_finds_the_median() {
+        for &p in [1.0, 1.5, 3.0, 6.0].iter() {
+            let geo = LpSpace::new(p);
+            for seed in 0..10 {
+                let ns: Vec<Point2d<f64>> = Generator::new(seed, 2).uniform(3 + seed as usize % 3);
+                let mut st = star(&ns);
+                solve(&mut LpNewton::default(), &mut st, &geo);
+                assert!(is_median(&st, &geo, 1e-9), "p = {}, seed = {}", p, seed);
+            }
+        }
+    }
+
+    #[test]
+    fn lp_newton_vertex_solution() {
+        let geo = LpSpace::new(3.0);
+        let mut st = star(&[p2(0.0, 0.0), p2(1.0, 0.0), p2(-1.0, 0.01)]);
+        let mut median = LpNewton::default();
+        solve(&mut median, &mut st, &geo);
+        assert_eq!(*st.i(3).p(), p2(0.0, 0.0));
+        assert!(median.data.vertex_solutions > 0);
+    }
+
+    #[test]
+    fn median_sorts_nan_last() {
+        assert_eq!(median(vec![3.0, ::std::f64::NAN, 1.0, 2.0, 0.0]), 2.0);
+        assert!(median(vec![::std::f64::NAN, 1.0]).is_nan());
+    }
+
+    #[test]
+    fn coordinate_median_of_nan_neighbours() {
+        let geo = LpSpace::new(L1);
+        let mut st = star(&[p2(0.0, 0.0), p2(1.0, 0.0), p2(::std::f64::NAN, 1.0)]);
+        solve(&mut CoordinateMedian::new(), &mut st, &geo);
+        assert!(!st.len(&geo).is_number());
+    }
+
+    #[test]
+    fn stepper_data_displays_every_line() {
+        let text = GeoMedianStepperData::new().to_string();
+        assert_eq!(text.lines().count(), 7);
+        assert!(text.contains("Geometric medians stopped at the step limit: 0"));
+    }
+}
@@ -0,0 +1,228 @@
+//! A small dense simplex solver for the linear programs arising from
+//! geometric medians in polyhedral spaces. The programs are tiny (a few
+//! dozen rows) so a tableau with Bland's rule is fast enough and never
+//! cycles.
+
+const EPS: f64 = 1e-9;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LpResult {
+    /// An optimal solution and its objective value
+    Optimal(Vec<f64>, f64),
+    Infeasible,
+    Unbounded
+}
+
+/// Linear program in slack form, see Cormen et al., "Introduction to
+/// Algorithms", chapter 29.
+struct Tableau {
+    /// Indices of the nonbasic variables, one per column
+    n: Vec<usize>,
+    /// Indices of the basic variables, one per row
+    b: Vec<usize>,
+    a: Vec<Vec<f64>>,
+    rhs: Vec<f64>,
+    c: Vec<f64>,
+    v: f64
+}
+
+impl Tableau {
+    fn pivot(&mut self, l: usize, e: usize) {
+        let ale = self.a[l][e];
+        self.rhs[l] /= ale;
+        for j in 0..self.n.len() {
+            if j != e {
+                self.a[l][j] /= ale;
+            }
+        }
+        self.a[l][e] = ale.recip();
+
+        for i in 0..self.b.len() {
+            let aie = self.a[i][e];
+            if i == l || aie == 0.0 {
+                continue;
+            }
+            self.rhs[i] -= aie * self.rhs[l];
+            for j in 0..self.n.len() {
+                if j != e {
+                    self.a[i][j] -= aie * self.a[l][j];
+                }
+            }
+            self.a[i][e] = -aie * self.a[l][e];
+        }
+
+        let ce = self.c[e];
+        self.v += ce * self.rhs[l];
+        for j in 0..self.n.len() {
+            if j != e {
+                self.c[j] -= ce * self.a[l][j];
+            }
+        }
+        self.c[e] = -ce * self.a[l][e];
+
+        ::std::mem::swap(&mut self.n[e], &mut self.b[l]);
+    }
+
+    /// Runs the simplex method from the current feasible basis. Returns
+    /// false if the program is unbounded.
+    fn optimize(&mut self) -> bool {
+        loop {
+            // Bland's rule: lowest variable index among improving columns
+            let e = match (0..self.n.len())
+                .filter(|&j| self.c[j] > EPS)
+                .min_by_key(|&j| self.n[j]) {
+                Some(e) => e,
+                None => return true
+            };
+
+            let mut leave: Option<(f64, usize)> = None;
+            for i in 0..self.b.len() {
+                if self.a[i][e] > EPS {
+                    let ratio = self.rhs[i] / self.a[i][e];
+                    leave = match leave {
+                        Some((r, l)) if r < ratio - EPS
+                            || ((r - ratio).abs() <= EPS && self.b[l] < self.b[i]) => Some((r, l)),
+                        _ => Some((ratio, i))
+                    };
+                }
+            }
+
+            match leave {
+                Some((_, l)) => self.pivot(l, e),
+                None => return false
+            }
+        }
+    }
+}
+
+/// Maximizes `c·x` subject to `a x <= b` and `x >= 0`.
+pub fn maximize(a: &[Vec<f64>], b: &[f64], c: &[f64]) -> LpResult {
+    let (m, n) = (b.len(), c.len());
+
+    // Auxiliary variable x_n, only used if the origin is infeasible
+    let mut t = Tableau {
+        n: (0..n+1).collect(),
+        b: (n+1..n+m+1).collect(),
+        a: a.iter().map(|row| {
+            let mut row = row.clone();
+            row.push(-1.0);
+            row
+        }).collect(),
+        rhs: b.to_vec(),
+        c: vec![0.0; n+1],
+        v: 0.0
+    };
+    t.c[n] = -1.0;
+
+    let min = (0..m).fold(None, |min: Option<usize>, i|
+        match min {
+            Some(k) if b[k] <= b[i] => Some(k),
+            _ => Some(i)
+        });
+
+    if let Some(k) = min {
+        if b[k] < 0.0 {
+            t.pivot(k, n);
+            t.optimize();
+            if t.v < -EPS {
+                return LpResult::Infeasible;
+            }
+
+            // Move the auxiliary variable out of the basis if needed
+            if let Some(l) = t.b.iter().position(|&x| x == n) {
+                match (0..t.n.len()).find(|&j| t.a[l][j].abs() > EPS) {
+                    Some(e) => t.pivot(l, e),
+                    // The row is redundant
+                    None => {
+                        t.b.remove(l);
+                        t.a.remove(l);
+                        t.rhs.remove(l);
+                    }
+                }
+            }
+        }
+    }
+
+    // Drop the auxiliary column and restore the original objective
+    if let Some(e) = t.n.iter().position(|&x| x == n) {
+        t.n.remove(e);
+        for row in t.a.iter_mut() {
+            row.remove(e);
+        }
+    }
+    t.c = vec![0.0; t.n.len()];
+    t.v = 0.0;
+    for (j, &cj) in c.iter().enumerate() {
+        if let Some(l) = t.b.iter().position(|&x| x == j) {
+            t.v += cj * t.rhs[l];
+            for k in 0..t.n.len() {
+                t.c[k] -= cj * t.a[l][k];
+            }
+        } else {
+            let k = t.n.iter().position(|&x| x == j).unwrap();
+            t.c[k] += cj;
+        }
+    }
+
+    if !t.optimize() {
+        return LpResult::Unbounded;
+    }
+
+    let mut x = vec![0.0; n];
+    for (i, &j) in t.b.iter().enumerate() {
+        if j < n {
+            x[j] = t.rhs[i];
+        }
+    }
+    LpResult::Optimal(x, t.v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn optimum(a: &[Vec<f64>], b: &[f64], c: &[f64]) -> (Vec<f64>, f64) {
+        match maximize(a, b, c) {
+            LpResult::Optimal(x, v) => (x, v),
+            r => panic!("no optimum: {:?}", r)
+        }
+    }
+
+    #[test]
+    fn feasible_origin() {
+        // Cormen et al., section 29.3
+        let a = vec![vec![1.0, 1.0, 3.0], vec![2.0, 2.0, 5.0], vec![4.0, 1.0, 2.0]];
+        let (x, v) = optimum(&a, &[30.0, 24.0, 36.0], &[3.0, 1.0, 2.0]);
+        assert!((v - 28.0).abs() < 1e-9);
+        let value = x[0] * 3.0 + x[1] + x[2] * 2.0;
+        assert!((value - 28.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn infeasible_origin() {
+        // max -x1 - x2 s.t. x1 + x2 >= 2, x1 <= 1
+        let a = vec![vec![-1.0, -1.0], vec![1.0, 0.0]];
+        let (x, v) = optimum(&a, &[-2.0, 1.0], &[-1.0, -1.0]);
+        assert!((v + 2.0).abs() < 1e-9);
+        assert!((x[0] + x[1] - 2.0).abs() < 1e-9);
+        assert!(x[0] <= 1.0 + 1e-9);
+    }
+
+    #[test]
+    fn infeasible_and_unbounded() {
+        // x1 <= -1
+        assert_eq!(maximize(&[vec![1.0]], &[-1.0], &[1.0]), LpResult::Infeasible);
+        // max x1 s.t. -x1 <= 0
+        assert_eq!(maximize(&[vec![-1.0]], &[0.0], &[1.0]), LpResult::Unbounded);
+    }
+
+    #[test]
+    fn degenerate_does_not_cycle() {
+        // Beale's example, which cycles with the largest coefficient rule
+        let a = vec![vec![0.25, -8.0, -1.0, 9.0],
+                     vec![0.5, -12.0, -0.5, 3.0],
+                     vec![0.0, 0.0, 1.0, 0.0]];
+        let (_, v) = optimum(&a, &[0.0, 0.0, 1.0], &[0.75, -20.0, 0.5, -6.0]);
+        assert!((v - 1.25).abs() < 1e-9);
+    }
+}
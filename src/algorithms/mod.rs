@@ -2,3 +2,5 @@ pub mod rmt;
 pub mod steinerbnb;
 pub mod mst;
 pub mod geomedians;
+pub mod rectilinear;
+pub mod lp;
@@ -1,10 +1,15 @@
 use traits::*;
 use steinertree::{SteinerTree};
+use geo::delaunay::{Delaunay};
+use geo::points::{Point2d};
+use geo::spaces::{EuclideanSpace};
 
 extern crate disjoint_set;
 use self::disjoint_set::DisjointSet;
 
-#[derive(Debug)]
+/// Kruskal's algorithm on the complete graph of the terminals. Needs
+/// O(n^2 log n) time and O(n^2) memory for the sorted edges.
+#[derive(Debug, Clone, Copy)]
 pub struct Kruskal { }
 
 #[derive(Eq, PartialEq, PartialOrd)]
@@ -21,17 +26,42 @@ impl Kruskal {
     }
 }
 
-impl<P: Point, M: MinkowskiSpace<P>> MST<P, M> for Kruskal {
-    fn find(&mut self, terminals: &[P], geo: &M) -> SteinerTree<P> {
-        let mut forest = DisjointSet::new();
-        for i in 0..terminals.len() {
-            forest.make_set(i);
+/// Adds the shortest of `edges` that join the components of `st` until it
+/// is a spanning tree of its terminals.
+fn kruskal<P: Point>(mut st: SteinerTree<P>, mut edges: Vec<Edge<P::R>>) -> SteinerTree<P> {
+    let n = st.terminals().len();
+    if n < 2 {
+        return st;
+    }
+
+    let mut forest = DisjointSet::new();
+    for i in 0..n {
+        forest.make_set(i);
+    }
+
+    edges.sort_by(|a, b| a.len.cmp_nan_last(&b.len)
+        .then((a.from, a.to).cmp(&(b.from, b.to))));
+
+    let mut c = 0;
+    for e in edges.iter() {
+        if forest.find(e.from) != forest.find(e.to) {
+            st.add_edge((e.from, e.to));
+            forest.union(e.from, e.to).unwrap();
+            c += 1;
+            if c == n - 1 {
+                break;
+            }
         }
+    }
 
-        let mut edges = Vec::with_capacity(terminals.len()*terminals.len());
-        let mut st = SteinerTree::new(terminals, &[], &[]);
+    debug_assert_eq!(c, n - 1, "the edges must connect all terminals");
+    st
+}
 
-        for i in 0..terminals.len() - 1 {
+impl<P: Point, M: MinkowskiSpace<P>> MST<P, M> for Kruskal {
+    fn find(&mut self, terminals: &[P], geo: &M) -> SteinerTree<P> {
+        let mut edges = Vec::with_capacity(terminals.len()*terminals.len());
+        for i in 0..terminals.len().saturating_sub(1) {
             for n in i+1..terminals.len() {
                 edges.push(Edge::<P::R> {
                     len: geo.dist(&terminals[i], &terminals[n]),
@@ -41,20 +71,201 @@ impl<P: Point, M: MinkowskiSpace<P>> MST<P, M> for Kruskal {
             }
         }
 
-        edges.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        kruskal(SteinerTree::new(terminals, &[], &[]), edges)
+    }
+}
+
+/// Prim's algorithm on the complete graph of the terminals, computing the
+/// distances as needed. Needs O(n^2) time and O(n) memory, which suits
+/// any Minkowski space.
+#[derive(Debug, Clone, Copy)]
+pub struct Prim { }
+
+impl Prim {
+    pub fn new() -> Self {
+        Prim {}
+    }
+}
+
+impl<P: Point, M: MinkowskiSpace<P>> MST<P, M> for Prim {
+    fn find(&mut self, terminals: &[P], geo: &M) -> SteinerTree<P> {
+        let mut st = SteinerTree::new(terminals, &[], &[]);
+        let n = terminals.len();
+        if n < 2 {
+            return st;
+        }
+
+        // Distance of every terminal to the tree, and its closest terminal
+        // in the tree
+        let mut dist: Vec<P::R> = terminals.iter().map(|t| geo.dist(&terminals[0], t)).collect();
+        let mut closest = vec![0; n];
+        let mut in_tree = vec![false; n];
+        in_tree[0] = true;
 
-        let mut c = 0;
-        for e in edges.iter() {
-            if forest.find(e.from) != forest.find(e.to) {
-                st.add_edge((e.from, e.to));
-                forest.union(e.from, e.to).unwrap();
-                c += 1;
-                if c == terminals.len() - 1 {
-                    return st;
+        for _ in 1..n {
+            let next = (0..n).filter(|&i| !in_tree[i])
+                .fold(None, |min: Option<usize>, i| match min {
+                    Some(m) if dist[m] <= dist[i] => min,
+                    _ => Some(i)
+                }).unwrap();
+            in_tree[next] = true;
+            st.add_edge((closest[next], next));
+
+            for i in 0..n {
+                if !in_tree[i] {
+                    let d = geo.dist(&terminals[next], &terminals[i]);
+                    if d < dist[i] {
+                        dist[i] = d;
+                        closest[i] = next;
+                    }
                 }
             }
-        };
+        }
+
+        st
+    }
+}
 
-        unreachable!("!")
+/// Kruskal's algorithm on the edges of the Delaunay triangulation of the
+/// terminals, which contains every minimum spanning tree in the Euclidean
+/// plane. Needs O(n) memory and expected O(n log n) time: the
+/// triangulation inserts the terminals in a randomized order, which is fast
+/// also for terminals in convex position, e.g. on a parabola.
+#[derive(Debug, Clone, Copy)]
+pub struct DelaunayMST { }
+
+impl DelaunayMST {
+    pub fn new() -> Self {
+        DelaunayMST {}
+    }
+}
+
+impl<R: Real> MST<Point2d<R>, EuclideanSpace> for DelaunayMST {
+    fn find(&mut self, terminals: &[Point2d<R>], geo: &EuclideanSpace) -> SteinerTree<Point2d<R>> {
+        let del = Delaunay::new(terminals);
+        let edges = del.edges().into_iter()
+            .chain(del.duplicates().iter().cloned())
+            .map(|(from, to)| Edge::<R> {
+                len: geo.dist(&terminals[from], &terminals[to]),
+                from: from,
+                to: to
+            }).collect();
+
+        kruskal(SteinerTree::new(terminals, &[], &[]), edges)
+    }
+}
+
+/// Chooses one of the MST algorithms for any space at runtime, for the
+/// users of MSTs that are generic over the space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MSTAlgorithm {
+    Kruskal,
+    Prim
+}
+
+impl Default for MSTAlgorithm {
+    fn default() -> Self {
+        MSTAlgorithm::Kruskal
+    }
+}
+
+impl<P: Point, M: MinkowskiSpace<P>> MST<P, M> for MSTAlgorithm {
+    fn find(&mut self, terminals: &[P], geo: &M) -> SteinerTree<P> {
+        match *self {
+            MSTAlgorithm::Kruskal => Kruskal::new().find(terminals, geo),
+            MSTAlgorithm::Prim => Prim::new().find(terminals, geo)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use generators::Generator;
+    use geo::spaces::{LpSpace, L1};
+    use geo::points::Point3d;
+    use upperbounds::{MSTBound, MSTBoundWith};
+
+    fn assert_spanning<P: Point>(st: &SteinerTree<P>, n: usize) {
+        assert_eq!(st.node_count(), n);
+        assert_eq!(st.edges().count(), n.saturating_sub(1));
+        if n > 1 {
+            assert!(st.nodes().all(|node| node.degree() > 0));
+        }
+    }
+
+    #[test]
+    fn all_algorithms_find_the_same_length() {
+        let geo = EuclideanSpace::new();
+        for seed in 0..5 {
+            let t: Vec<Point2d<f64>> = Generator::new(seed, 2).uniform(40);
+            let kruskal = Kruskal::new().find(&t, &geo);
+            assert_spanning(&kruskal, 40);
+            for st in [Prim::new().find(&t, &geo), DelaunayMST::new().find(&t, &geo)].iter() {
+                assert_spanning(st, 40);
+                assert!((st.len(&geo) - kruskal.len(&geo)).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn prim_in_other_spaces() {
+        let geo = LpSpace::new(L1);
+        let t: Vec<Point3d<f64>> = Generator::new(1, 3).uniform(25);
+        let kruskal = Kruskal::new().find(&t, &geo).len(&geo);
+        let prim = Prim::new().find(&t, &geo);
+        assert_spanning(&prim, 25);
+        assert!((prim.len(&geo) - kruskal).abs() < 1e-12);
+    }
+
+    #[test]
+    fn degenerate_terminals() {
+        let geo = EuclideanSpace::new();
+        // A grid with a duplicate, and collinear terminals
+        let mut grid: Vec<Point2d<f64>> = (0..16).map(|i| Point2d::new([(i % 4) as f64, (i / 4) as f64])).collect();
+        grid.push(Point2d::new([1.0, 1.0]));
+        let line: Vec<Point2d<f64>> = (0..6).map(|i| Point2d::new([i as f64, 2.0 * i as f64])).rev().collect();
+        for &(ref t, len) in [(grid, 15.0), (line, 5.0 * 5f64.sqrt())].iter() {
+            for st in [Kruskal::new().find(t, &geo), Prim::new().find(t, &geo),
+                       DelaunayMST::new().find(t, &geo)].iter() {
+                assert_spanning(st, t.len());
+                assert!((st.len(&geo) - len).abs() < 1e-12);
+            }
+        }
+
+        for n in 0..3 {
+            let t: Vec<Point2d<f64>> = (0..n).map(|i| Point2d::new([i as f64, 0.0])).collect();
+            assert_spanning(&DelaunayMST::new().find(&t, &geo), n);
+            assert_spanning(&Prim::new().find(&t, &geo), n);
+            assert_spanning(&Kruskal::new().find(&t, &geo), n);
+        }
+    }
+
+    #[test]
+    fn delaunay_on_a_parabola() {
+        // The slow case of a triangulation in lexicographic order. The
+        // minimum spanning tree is the path through the terminals.
+        let geo = EuclideanSpace::new();
+        let t: Vec<Point2d<f64>> = (-10000..10000i64)
+            .map(|x| Point2d::new([x as f64, (x * x) as f64])).collect();
+        let st = DelaunayMST::new().find(&t, &geo);
+        assert_spanning(&st, t.len());
+        let len: f64 = t.windows(2).map(|w| geo.dist(&w[0], &w[1])).sum();
+        assert!((st.len(&geo) - len).abs() < 1e-6 * len);
+    }
+
+    #[test]
+    fn kruskal_is_the_default() {
+        let geo = EuclideanSpace::new();
+        let t: Vec<Point2d<f64>> = Generator::new(2, 2).uniform(20);
+        assert_eq!(MSTAlgorithm::default(), MSTAlgorithm::Kruskal);
+
+        let len = MSTBound.bound(t.clone(), &geo).len(&geo);
+        for &mst in [MSTAlgorithm::Kruskal, MSTAlgorithm::Prim].iter() {
+            let st = MSTBoundWith::new(mst).bound(t.clone(), &geo);
+            assert!((st.len(&geo) - len).abs() < 1e-12);
+        }
+        let st = MSTBoundWith::new(DelaunayMST::new()).bound(t, &geo);
+        assert!((st.len(&geo) - len).abs() < 1e-12);
     }
 }
@@ -0,0 +1,292 @@
+use std::time::{Duration, Instant};
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::{self, Write};
+
+use traits::*;
+use stats::{Stats};
+use geo::spaces::{LpSpace, L1};
+use steinertree::{SteinerTree};
+
+#[derive(Debug, Clone)]
+pub struct HananRMTData {
+    nodes: usize,
+    time: Duration,
+    candidates: u64
+}
+
+impl HananRMTData {
+    pub fn new() -> Self {
+        HananRMTData {
+            nodes: 0,
+            time: Duration::new(0, 0),
+            candidates: 0
+        }
+    }
+
+    /// Total number of Hanan grid coordinates considered over all axes and
+    /// topologies.
+    pub fn candidates(&self) -> u64 {
+        self.candidates
+    }
+}
+
+impl RmtData for HananRMTData {
+    fn time(&self) -> &Duration {
+        &self.time
+    }
+
+    fn nodes(&self) -> usize  {
+        self.nodes
+    }
+}
+
+impl Report for HananRMTData {
+    fn report(&self) -> Stats {
+        Stats::new()
+            .count("nodes", self.nodes as u64)
+            .seconds("time", &self.time)
+            .count("candidates", self.candidates)
+    }
+}
+
+impl fmt::Display for HananRMTData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn printdur(dur: &Duration) -> f64 {
+            (dur.as_secs() as f64) + (dur.subsec_nanos() as f64) / 1000000000.0
+        }
+
+        try!(writeln!(f, "Data for the Hanan grid RMT algorithm:"));
+        try!(writeln!(f, "\tNumber of topologies optimized: {}", self.nodes));
+        try!(writeln!(f, "\tHanan grid coordinates considered: {}", self.candidates));
+        try!(writeln!(f, "\tTotal time: {}", printdur(&self.time)));
+        let average = if self.nodes == 0 { Duration::new(0, 0) } else { self.time / (self.nodes as u32) };
+        writeln!(f, "\tAvarage time pr. topology: {}", printdur(&average))
+    }
+}
+
+/// Exact relatively minimal trees in the rectilinear (L1) space.
+///
+/// The L1 length of a tree is a sum of independent one dimensional problems,
+/// one for each axis, and by Hanan's theorem an optimal tree has all Steiner
+/// points on the Hanan grid of the terminals. Every axis is solved by dynamic
+/// programming over the tree rooted at a terminal, where each Steiner point
+/// only takes the terminal coordinates of that axis. A Steiner point with
+/// fixed neighbours ends up at their coordinatewise median.
+///
+/// It is therefore only defined for `LpSpace<L1>`.
+#[derive(Debug, Clone)]
+pub struct HananRMT {
+    data: HananRMTData
+}
+
+impl HananRMT {
+    pub fn new() -> Self {
+        HananRMT {
+            data: HananRMTData::new()
+        }
+    }
+
+    fn axis<P: Point>(&mut self, stree: &mut SteinerTree<P>, order: &[(usize, usize)], k: usize) {
+        let mut cs: Vec<P::R> = stree.terminals().map(|t| t.p().coords()[k]).collect();
+        // NaN coordinates compare equal here, so that a terminal with one
+        // still finds its position and the NaN ends up in the length
+        let same = |a: &P::R, b: &P::R| a.cmp_nan_last(b) == Ordering::Equal;
+        cs.sort_by(|a, b| a.cmp_nan_last(b));
+        cs.dedup_by(|a, b| same(a, b));
+        self.data.candidates += cs.len() as u64;
+
+        let n = stree.node_count();
+        let inf = P::R::from(::std::f64::INFINITY);
+
+        // cost[v][c]: length of the subtree below v along this axis when v
+        // is placed at cs[c]
+        let mut cost = vec![vec![P::R::zero(); cs.len()]; n];
+        // choice[v][c]: position of v when its parent is placed at cs[c]
+        let mut choice = vec![vec![0; cs.len()]; n];
+
+        for &(v, parent) in order.iter().rev() {
+            let node = stree.i(v);
+            if node.is_terminal() {
+                let at = cs.iter().position(|c| same(c, &node.p().coords()[k])).unwrap();
+                for (c, x) in cost[v].iter_mut().enumerate() {
+                    if c != at {
+                        *x = inf;
+                    }
+                }
+            }
+
+            if parent == v {
+                continue;
+            }
+
+            // Cheapest placement of v for every placement of its parent,
+            // found by a sweep from each side.
+            let mut best: Vec<(P::R, usize)> = Vec::with_capacity(cs.len());
+            for c in 0..cs.len() {
+                let own = (cost[v][c], c);
+                if c == 0 {
+                    best.push(own);
+                } else {
+                    let (l, i) = best[c-1];
+                    let l = l + (cs[c] - cs[c-1]);
+                    best.push(if l < own.0 { (l, i) } else { own });
+                }
+            }
+            for c in (0..cs.len().saturating_sub(1)).rev() {
+                let (r, i) = best[c+1];
+                let r = r + (cs[c+1] - cs[c]);
+                if r < best[c].0 {
+                    best[c] = (r, i);
+                }
+            }
+
+            for (c, &(x, i)) in best.iter().enumerate() {
+                cost[parent][c] += x;
+                choice[v][c] = i;
+            }
+        }
+
+        let mut pos = vec![0; n];
+        for &(v, parent) in order.iter() {
+            if parent == v {
+                let root = stree.i(v).p().coords()[k];
+                pos[v] = cs.iter().position(|c| same(c, &root)).unwrap();
+            } else {
+                pos[v] = choice[v][pos[parent]];
+                if !stree.i(v).is_terminal() {
+                    stree.steiner_i(v).p_mut().coords_mut()[k] = cs[pos[v]];
+                }
+            }
+        }
+    }
+}
+
+impl Default for HananRMT {
+    fn default() -> Self {
+        HananRMT::new()
+    }
+}
+
+impl<P: Point> RMT<P, LpSpace<L1>> for HananRMT {
+    type D = HananRMTData;
+
+    fn find(&mut self, stree: &mut SteinerTree<P>, geo: &LpSpace<L1>) -> P::R {
+        self.data.nodes += 1;
+        let start = Instant::now();
+
+        let root = match stree.terminals().next() {
+            Some(t) => t.id(),
+            None => return P::R::zero()
+        };
+
+        // Nodes in breadth first order from a terminal, paired with their parent
+        let mut order = vec![(root, root)];
+        let mut seen = vec![false; stree.node_count()];
+        seen[root] = true;
+        let mut i = 0;
+        while i < order.len() {
+            let v = order[i].0;
+            for n in stree.i(v).neighbours() {
+                if !seen[n.id()] {
+                    seen[n.id()] = true;
+                    order.push((n.id(), v));
+                }
+            }
+            i += 1;
+        }
+
+        let dim = stree.i(root).p().dim();
+        for k in 0..dim {
+            self.axis(stree, &order, k);
+        }
+
+        self.data.time += Instant::now() - start;
+        stree.len(geo)
+    }
+
+    fn data(&self) -> &Self::D {
+        &self.data
+    }
+
+    fn print(&self, f: &mut fmt::Formatter, _: u32) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+
+    fn print_data<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "{}", self.data)
+    }
+}
+
+impl fmt::Display for HananRMT {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Exact rectilinear RMT by dynamic programming on the Hanan grid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::points::Point2d;
+    use generators::Generator;
+    use algorithms::steinerbnb::SteinerBnB;
+    use algorithms::rmt::GeoMedianIter;
+    use algorithms::geomedians::CoordinateMedian;
+    use error::SteinerError;
+
+    #[test]
+    fn square() {
+        let geo = LpSpace::new(L1);
+        let t = vec![Point2d::new([0.0, 0.0]), Point2d::new([1.0, 0.0]),
+                     Point2d::new([1.0, 1.0]), Point2d::new([0.0, 1.0])];
+        let st = SteinerBnB::default_rectilinear().find(t, &geo);
+        assert_eq!(st.len(&geo), 3.0);
+    }
+
+    #[test]
+    fn steiner_points_lie_on_the_hanan_grid() {
+        let geo = LpSpace::new(L1);
+        for seed in 0..5 {
+            let t: Vec<Point2d<f64>> = Generator::new(seed, 2).uniform(7);
+            let st = SteinerBnB::default_rectilinear().find(t.clone(), &geo);
+            for s in st.steiner_points() {
+                let c = s.p().coords();
+                assert!(t.iter().any(|t| t.coords()[0] == c[0]));
+                assert!(t.iter().any(|t| t.coords()[1] == c[1]));
+            }
+        }
+    }
+
+    #[test]
+    fn no_longer_than_coordinatewise_medians() {
+        let geo = LpSpace::new(L1);
+        let t: Vec<Point2d<f64>> = Generator::new(3, 2).uniform(6);
+        // A full topology over the terminals with its Steiner points at the
+        // first terminal
+        let ss = vec![t[0].clone(); 4];
+        let edges = [(0, 6), (1, 6), (6, 7), (2, 7), (7, 8), (3, 8), (8, 9), (4, 9), (5, 9)];
+        let mut hanan = SteinerTree::new(&t, &ss, &edges);
+        let mut medians = hanan.clone();
+
+        let len = HananRMT::new().find(&mut hanan, &geo);
+        let iterated = GeoMedianIter::new(1e-9, CoordinateMedian::new()).find(&mut medians, &geo);
+        assert_eq!(len, hanan.len(&geo));
+        assert!(len <= iterated + 1e-12);
+    }
+
+    #[test]
+    fn empty_data_displays() {
+        let data = HananRMT::new().data;
+        assert!(data.to_string().contains("pr. topology: 0"));
+    }
+
+    #[test]
+    fn nan_terminals_are_reported() {
+        let geo = LpSpace::new(L1);
+        let t = vec![Point2d::new([0.0, 0.0]), Point2d::new([1.0, 0.0]), Point2d::new([::std::f64::NAN, 1.0])];
+        let mut st = SteinerTree::new(&t, &[t[0].clone()], &[(0, 3), (1, 3), (2, 3)]);
+        assert!(!HananRMT::new().find(&mut st.clone(), &geo).is_number());
+        assert_eq!(HananRMT::new().try_find(&mut st, &geo),
+            Err(SteinerError::NonFinite { terminal: 2 }));
+    }
+}
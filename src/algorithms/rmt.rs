@@ -4,8 +4,11 @@ use std::fmt;
 use std::io::{self, BufWriter, Write};
 
 use traits::*;
+use stats::{Stats};
 use geo::spaces::{EuclideanSpace};
 use steinertree::{SteinerTree};
+use error::{self, SteinerError};
+use observer::{Observer, NoObserver};
 use algorithms::geomedians::*;
 
 #[derive(Debug, Clone)]
@@ -13,6 +16,7 @@ pub struct GeoMedianIterData {
     nodes: usize,
     time: Duration,
     iterations: u64,
+    unconverged: u64,
     selftime: Duration
 }
 
@@ -21,6 +25,11 @@ impl GeoMedianIterData {
         self.iterations
     }
 
+    /// Number of topologies whose iteration was stopped at the limit.
+    pub fn unconverged(&self) -> u64 {
+        self.unconverged
+    }
+
     pub fn selftime(&self) -> &Duration {
         &self.selftime
     }
@@ -36,6 +45,17 @@ impl RmtData for GeoMedianIterData {
     }
 }
 
+impl Report for GeoMedianIterData {
+    fn report(&self) -> Stats {
+        Stats::new()
+            .count("nodes", self.nodes as u64)
+            .count("iterations", self.iterations)
+            .count("unconverged", self.unconverged)
+            .seconds("time", &self.time)
+            .seconds("self_time", &self.selftime)
+    }
+}
+
 impl fmt::Display for GeoMedianIterData {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         fn printdur(dur: &Duration) -> f64 {
@@ -48,32 +68,40 @@ impl fmt::Display for GeoMedianIterData {
             all Steiner points of a topology)", self.iterations));
         try!(writeln!(f, "\tAvarage number of iterations pr. topology: {}",
             (self.iterations as f64) / (self.nodes as f64)));
+        try!(writeln!(f, "\tTopologies stopped at the iteration limit: {}", self.unconverged));
         try!(writeln!(f, "\tTotal time: {}", printdur(&self.time)));
         writeln!(f, "\tAvarage time pr. topology: {}", printdur(&(self.time / (self.nodes as u32))))
     }
 }
 
 #[derive(Debug)]
-pub struct GeoMedianIter<P: Point, M: MinkowskiSpace<P>, G: GeoMedian<P, M>> {
+pub struct GeoMedianIter<P: Point, M: MinkowskiSpace<P>, G: GeoMedian<P, M>, O = NoObserver> {
     tree_len_cutoff: P::R,
+    max_iterations: u64,
     median: G,
     data: GeoMedianIterData,
+    observer: O,
     _m: PhantomData<M>
 }
 
 impl<P, M, G> GeoMedianIter<P, M, G>
     where P: Point, M: MinkowskiSpace<P>, G: GeoMedian<P, M> {
 
+    /// Iterates the medians of a topology until its length changes by less
+    /// than `a`, or at most 10000 times; see `max_iterations`.
     pub fn new(a: P::R, median: G) -> GeoMedianIter<P, M, G> {
         GeoMedianIter {
             tree_len_cutoff: a,
+            max_iterations: 10000,
             median: median,
             data: GeoMedianIterData {
                 nodes: 0,
                 time: Duration::new(0, 0),
                 selftime: Duration::new(0, 0),
-                iterations: 0
+                iterations: 0,
+                unconverged: 0
             },
+            observer: NoObserver,
             _m: PhantomData
         }
     }
@@ -81,15 +109,95 @@ impl<P, M, G> GeoMedianIter<P, M, G>
     pub fn default_with_geomedian(median: G) -> GeoMedianIter<P, M, G> {
         Self::new(P::R::from(0.00001), median)
     }
+}
+
+impl<P, M, G, O> GeoMedianIter<P, M, G, O>
+    where P: Point, M: MinkowskiSpace<P>, G: GeoMedian<P, M>, O: Observer<P> {
+
+    /// Reports every relatively minimal tree found to `observer`.
+    pub fn observer<Q: Observer<P>>(self, observer: Q) -> GeoMedianIter<P, M, G, Q> {
+        GeoMedianIter {
+            tree_len_cutoff: self.tree_len_cutoff,
+            max_iterations: self.max_iterations,
+            median: self.median,
+            data: self.data,
+            observer: observer,
+            _m: PhantomData
+        }
+    }
 
     pub fn tree_len_cutoff(mut self, a: P::R) -> Self {
         self.tree_len_cutoff = a;
         self
     }
 
+    /// Stops the iteration of a topology after `n` iterations even if the
+    /// tree length still changes by more than the cutoff. Defaults to
+    /// 10000; `try_find` reports the stop as `SteinerError::NoConvergence`.
+    pub fn max_iterations(mut self, n: u64) -> Self {
+        self.max_iterations = n;
+        self
+    }
+
     pub fn geo_median_alg(&mut self) -> &mut G {
         &mut self.median
     }
+
+    /// Optimizes the Steiner points of `stree`, returning its length and
+    /// whether the iteration converged before the limit, or the first error
+    /// of the median algorithm.
+    fn iterate(&mut self, stree: &mut SteinerTree<P>, geo: &M) -> Result<(P::R, bool), SteinerError> {
+        self.data.nodes += 1;
+        let start = Instant::now();
+
+        let steiner_points: Vec<usize> = stree.steiner_points().map(|s| s.id()).collect();
+        for &s in steiner_points.iter() {
+            self.median.init(&mut stree.steiner_i(s), geo);
+        }
+        // Measured after init, which may move Steiner points away from
+        // their (degenerate) positions.
+        let mut last_len = stree.len(geo);
+        let mut iterations = 0;
+        loop {
+            self.data.iterations += 1;
+            iterations += 1;
+            for &s in steiner_points.iter() {
+                if let Err(e) = self.median.try_find(&mut stree.steiner_i(s), geo) {
+                    self.data.time += Instant::now() - start;
+                    return Err(e);
+                }
+            }
+
+            let len = stree.len(geo);
+            let converged = last_len - len < self.tree_len_cutoff;
+            if converged || !len.is_number() || iterations >= self.max_iterations {
+                if !converged {
+                    self.data.unconverged += 1;
+                }
+                self.data.time += Instant::now() - start;
+                self.data.selftime = self.data.time - *self.median.data().time();
+                self.observer.rmt_finished(stree, len, iterations);
+                return Ok((len, converged));
+            }
+
+            last_len = len;
+        }
+    }
+}
+
+impl<P, M, G, O> Clone for GeoMedianIter<P, M, G, O>
+    where P: Point, M: MinkowskiSpace<P>, G: GeoMedian<P, M> + Clone, O: Clone {
+
+    fn clone(&self) -> Self {
+        GeoMedianIter {
+            tree_len_cutoff: self.tree_len_cutoff,
+            max_iterations: self.max_iterations,
+            median: self.median.clone(),
+            data: self.data.clone(),
+            observer: self.observer.clone(),
+            _m: PhantomData
+        }
+    }
 }
 
 impl<P> Default for GeoMedianIter<P, EuclideanSpace, Uteshev>
@@ -100,33 +208,33 @@ impl<P> Default for GeoMedianIter<P, EuclideanSpace, Uteshev>
     }
 }
 
-impl<P, M, G> RMT<P, M> for GeoMedianIter<P, M, G>
-    where P: Point, M: MinkowskiSpace<P>, G: GeoMedian<P, M> {
+impl<P, M, G, O> RMT<P, M> for GeoMedianIter<P, M, G, O>
+    where P: Point, M: MinkowskiSpace<P>, G: GeoMedian<P, M>, O: Observer<P> {
 
     type D = GeoMedianIterData;
 
+    /// Panics if the median algorithm fails, see `try_find`.
     fn find(&mut self, stree: &mut SteinerTree<P>, geo: &M) -> P::R {
-        self.data.nodes += 1;
-        let start = Instant::now();
-
-        let mut last_len = stree.len(geo);
-        for s in stree.steiner_points() {
-            self.median.init(s, geo);
+        match self.iterate(stree, geo) {
+            Ok((len, _)) => len,
+            Err(e) => panic!("{}", e)
         }
-        loop {
-            self.data.iterations += 1;
-            for s in stree.steiner_points() {
-                self.median.find(s, geo);
-            }
+    }
 
-            let len = stree.len(geo);
-            if last_len - len < self.tree_len_cutoff {
-                self.data.time += Instant::now() - start;
-                self.data.selftime = self.data.time - *self.median.data().time();
-                return len;
-            }
+    fn try_find(&mut self, stree: &mut SteinerTree<P>, geo: &M) -> Result<P::R, SteinerError> {
+        let ts: Vec<P> = stree.terminals().map(|n| n.p().clone()).collect();
+        try!(error::check_terminals(&ts));
 
-            last_len = len;
+        let unconverged = self.median.data().unconverged();
+        let (len, converged) = try!(self.iterate(stree, geo));
+        if !len.is_number() {
+            Err(SteinerError::NonFiniteLength)
+        } else if !converged {
+            Err(SteinerError::NoConvergence { algorithm: "relatively minimal tree iteration" })
+        } else if self.median.data().unconverged() > unconverged {
+            Err(SteinerError::NoConvergence { algorithm: "geometric median iteration" })
+        } else {
+            Ok(len)
         }
     }
 
@@ -143,8 +251,8 @@ impl<P, M, G> RMT<P, M> for GeoMedianIter<P, M, G>
         }
 
         try!(write!(f, "Geometric median iterator that continuously finds \
-            geometric medians until the change in tree length is less than {}.",
-            self.tree_len_cutoff));
+            geometric medians until the change in tree length is less than {} \
+            or {} iterations were made.", self.tree_len_cutoff, self.max_iterations));
         try!(write!(f, " Geometric medians were found using "));
         self.median.print(f, inde)
     }
@@ -153,10 +261,14 @@ impl<P, M, G> RMT<P, M> for GeoMedianIter<P, M, G>
         try!(writeln!(w, "{}", self.data()));
         self.median.print_data(w)
     }
+
+    fn report(&self) -> Stats {
+        self.data.report().group("median", self.median.report())
+    }
 }
 
-impl<P, M, G> fmt::Display for GeoMedianIter<P, M, G>
-    where P: Point, M: MinkowskiSpace<P>, G: GeoMedian<P, M> {
+impl<P, M, G, O> fmt::Display for GeoMedianIter<P, M, G, O>
+    where P: Point, M: MinkowskiSpace<P>, G: GeoMedian<P, M>, O: Observer<P> {
 
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         self.print(f, 0)
@@ -1,33 +1,89 @@
 use traits::*;
-use geo::spaces::{EuclideanSpace};
+use stats::{Stats};
+use geo::spaces::{EuclideanSpace, LpSpace, L1, LInfinity, PolytopeSpace, LambdaSpace};
+use geo::points::{Point2d};
 use algorithms::rmt::{GeoMedianIter};
+use algorithms::rectilinear::{HananRMT};
 use upperbounds::{LineTree};
+use prunetests::{UpperBoundPruning};
 use enumerator::*;
 use steinertree::{SteinerTree};
+use error::{self, SteinerError};
+use observer::{Observer, NoObserver, PruneReason};
 use algorithms::geomedians::*;
 
 use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
 use std::time::{Duration, Instant};
 use std::fmt;
 use std::io::{self, BufWriter, Write};
 
 #[derive(Debug)]
-pub struct SteinerBnB<P, M, R, E, U> {
+pub struct SteinerBnB<P, M, R, E, U, T = UpperBoundPruning, O = NoObserver> {
     rmt: R,
     enumerator: E,
     upperbound: U,
+    prunetest: T,
+    degeneracy_eps: f64,
+    limits: SearchLimits,
     data: SteinerBnBData,
+    observer: O,
     _m: PhantomData<M>,
     _p: PhantomData<P>
 }
 
 impl<P, M, K, E, U> SteinerBnB<P, M, K, E, U> {
+    /// Prunes the partial topologies only by the upper bound, see
+    /// `prunetest` for others.
     pub fn new(rmt: K, enumerator: E, u: U) -> Self {
         SteinerBnB {
             rmt: rmt,
             enumerator: enumerator,
             upperbound: u,
+            prunetest: UpperBoundPruning,
+            degeneracy_eps: 0.00001,
+            limits: SearchLimits::new(),
             data: SteinerBnBData::new(),
+            observer: NoObserver,
+            _m: PhantomData,
+            _p: PhantomData
+        }
+    }
+}
+
+impl<P, M, K, E, U, T, O> SteinerBnB<P, M, K, E, U, T, O> {
+    /// Prunes the relatively minimal partial topologies that fail
+    /// `prunetest` besides those longer than the best tree.
+    pub fn prunetest<Q>(self, prunetest: Q) -> SteinerBnB<P, M, K, E, U, Q, O> {
+        SteinerBnB {
+            rmt: self.rmt,
+            enumerator: self.enumerator,
+            upperbound: self.upperbound,
+            prunetest: prunetest,
+            degeneracy_eps: self.degeneracy_eps,
+            limits: self.limits,
+            data: self.data,
+            observer: self.observer,
+            _m: PhantomData,
+            _p: PhantomData
+        }
+    }
+
+    /// Reports the relatively minimal trees pruned by the prune test and
+    /// the improvements of the best tree to `observer`. The enumerator and
+    /// the RMT algorithm take their own observers.
+    pub fn observer<Q>(self, observer: Q) -> SteinerBnB<P, M, K, E, U, T, Q> {
+        SteinerBnB {
+            rmt: self.rmt,
+            enumerator: self.enumerator,
+            upperbound: self.upperbound,
+            prunetest: self.prunetest,
+            degeneracy_eps: self.degeneracy_eps,
+            limits: self.limits,
+            data: self.data,
+            observer: observer,
             _m: PhantomData,
             _p: PhantomData
         }
@@ -41,36 +97,164 @@ impl<P, M, K, E, U> SteinerBnB<P, M, K, E, U> {
         &mut self.enumerator
     }
 
+    pub fn prune_test(&mut self) -> &mut T {
+        &mut self.prunetest
+    }
+
+    /// Edges of the resulting tree shorter than `eps` times its length are
+    /// contracted when the tree is made non-degenerate.
+    pub fn degeneracy_eps(mut self, eps: f64) -> Self {
+        self.degeneracy_eps = eps;
+        self
+    }
+
+    /// Stops the search after `limit` and returns the best tree found so
+    /// far. The status of the data tells whether the search was stopped.
+    pub fn time_limit(mut self, limit: Duration) -> Self {
+        self.limits.time = Some(limit);
+        self
+    }
+
+    /// Stops the search after `limit` topologies have been enumerated.
+    pub fn node_limit(mut self, limit: u64) -> Self {
+        self.limits.nodes = Some(limit);
+        self
+    }
+
+    /// Stops the search when the topologies kept by the enumerator and the
+    /// best tree take more than about `bytes` bytes, see
+    /// `Enumerator::stored` and `SteinerTree::memory`.
+    pub fn memory_limit(mut self, bytes: usize) -> Self {
+        self.limits.memory = Some(bytes);
+        self
+    }
+
+    /// Stops the search as soon as `flag` is set, e.g. from another thread.
+    pub fn cancel_flag(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.limits.cancel = Some(flag);
+        self
+    }
+}
+
+/// The limits of a search, shared by `SteinerBnB` and `ParallelSteinerBnB`.
+#[derive(Debug, Clone)]
+struct SearchLimits {
+    time: Option<Duration>,
+    nodes: Option<u64>,
+    memory: Option<usize>,
+    cancel: Option<Arc<AtomicBool>>
+}
+
+impl SearchLimits {
+    fn new() -> Self {
+        SearchLimits {
+            time: None,
+            nodes: None,
+            memory: None,
+            cancel: None
+        }
+    }
+
+    /// Why a search started at `start` must stop after `nodes` topologies,
+    /// if it must. `memory` is only called with a memory limit.
+    fn stop<F: FnOnce() -> usize>(&self, start: Instant, nodes: u64, memory: F) -> Option<SearchStatus> {
+        if self.cancel.as_ref().map_or(false, |c| c.load(Ordering::Relaxed)) {
+            Some(SearchStatus::Cancelled)
+        } else if self.nodes.map_or(false, |l| nodes >= l) ||
+                  self.time.map_or(false, |l| Instant::now() - start >= l) ||
+                  self.memory.map_or(false, |l| memory() > l) {
+            Some(SearchStatus::LimitReached)
+        } else {
+            None
+        }
+    }
+}
 
+/// The lower bound of a search that ended with `status`: the length of the
+/// best tree if it finished, else the length of the relatively minimal tree
+/// of the first three terminals if it was computed.
+fn lower_bound<R: Real>(status: SearchStatus, root_len: Option<R>, best_len: R) -> Option<f64> {
+    match status {
+        SearchStatus::Optimal => Some(best_len.into()),
+        _ => root_len.map(|l| if l < best_len { l.into() } else { best_len.into() })
+    }
 }
 
-impl<P, M, K, E, U> SMT<P, M> for SteinerBnB<P, M, K, E, U>
+impl<P, M, K, E, U, T, O> SteinerBnB<P, M, K, E, U, T, O>
     where P: Point, M: MinkowskiSpace<P>, K: RMT<P, M>, E: Enumerator<P>,
-          U: UpperBound<P, M> {
+          U: UpperBound<P, M>, T: PruneTest, O: Observer<P> {
 
-    type D = SteinerBnBData;
+    /// The branch and bound search, with `rmt` computing the relatively
+    /// minimal trees.
+    fn search<F>(&mut self, t: Vec<P>, geo: &M, mut rmt: F) -> Result<SteinerTree<P>, SteinerError>
+        where F: FnMut(&mut K, &mut SteinerTree<P>, &M) -> Result<P::R, SteinerError> {
 
-    fn find(&mut self, t: Vec<P>, geo: &M) -> SteinerTree<P> {
         let start = Instant::now();
         let mut best = self.upperbound.bound(t.clone(), geo);
         let mut best_len = best.len(geo);
         self.enumerator.init(t, geo);
 
-        while self.enumerator.next(geo) {
+        // Every full topology extends the topology of the first three
+        // terminals, so its relatively minimal tree is a lower bound, up to
+        // the accuracy of the RMT algorithm.
+        let mut root_len = None;
+        let mut nodes = 0;
+        let mut status = SearchStatus::Optimal;
+        loop {
+            let memory = || self.enumerator.stored() * self.enumerator.tree().memory() + best.memory();
+            if let Some(s) = self.limits.stop(start, nodes, memory) {
+                status = s;
+                break;
+            }
+            if !self.enumerator.next(geo) {
+                break;
+            }
+            nodes += 1;
+
+            let len = try!(rmt(&mut self.rmt, self.enumerator.tree_mut(), geo));
+            if root_len.is_none() && self.enumerator.tree().terminals().len() == 3 {
+                root_len = Some(len);
+            }
             if self.enumerator.tree().terminals().len() == best.terminals().len() {
-                self.rmt.find(self.enumerator.tree_mut(), geo);
-                let len = self.enumerator.tree().len(geo);
                 if len < best_len {
                     self.data.best_updates += 1;
                     best = self.enumerator.tree().clone();
                     best_len = len;
+                    self.observer.new_incumbent(&best, len);
                 }
+            } else if self.prunetest.prunetest(self.enumerator.tree(), len, best_len) {
+                // The relatively minimal tree of a partial topology is a
+                // lower bound for all full topologies below it.
+                self.data.pruned += 1;
+                self.observer.pruned(self.enumerator.tree().terminals().len() - 3, PruneReason::Bound);
+                self.enumerator.prune();
             }
         }
 
-        //best.non_degenerate();
+        best.non_degenerate(geo, P::R::from(self.degeneracy_eps));
         self.data.time = Instant::now() - start;
-        best
+        self.data.status = status;
+        self.data.length = best_len.into();
+        self.data.lower_bound = lower_bound(status, root_len, best_len);
+        Ok(best)
+    }
+}
+
+impl<P, M, K, E, U, T, O> SMT<P, M> for SteinerBnB<P, M, K, E, U, T, O>
+    where P: Point, M: MinkowskiSpace<P>, K: RMT<P, M>, E: Enumerator<P>,
+          U: UpperBound<P, M>, T: PruneTest, O: Observer<P> {
+
+    type D = SteinerBnBData;
+
+    fn find(&mut self, t: Vec<P>, geo: &M) -> SteinerTree<P> {
+        // Without checks the search itself can't fail
+        self.search(t, geo, |rmt, tree, geo| Ok(rmt.find(tree, geo))).unwrap()
+    }
+
+    fn try_find(&mut self, t: Vec<P>, geo: &M) -> Result<SteinerTree<P>, SteinerError> {
+        try!(error::check_terminals(&t));
+        let best = try!(self.search(t, geo, |rmt, tree, geo| rmt.try_find(tree, geo)));
+        if best.len(geo).is_number() { Ok(best) } else { Err(SteinerError::NonFiniteLength) }
     }
 
     fn data(&self) -> &Self::D {
@@ -98,11 +282,17 @@ impl<P, M, K, E, U> SMT<P, M> for SteinerBnB<P, M, K, E, U>
         try!(self.enumerator.print_data(w));
         self.rmt.print_data(w)
     }
+
+    fn report(&self) -> Stats {
+        self.data.report()
+            .group("enumerator", self.enumerator.report())
+            .group("rmt", self.rmt.report())
+    }
 }
 
-impl<P, M, K, E, U> fmt::Display for SteinerBnB<P, M, K, E, U>
+impl<P, M, K, E, U, T, O> fmt::Display for SteinerBnB<P, M, K, E, U, T, O>
     where P: Point, M: MinkowskiSpace<P>, K: RMT<P, M>, E: Enumerator<P>,
-          U: UpperBound<P, M> {
+          U: UpperBound<P, M>, T: PruneTest, O: Observer<P> {
 
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         self.print(f, 0)
@@ -111,7 +301,7 @@ impl<P, M, K, E, U> fmt::Display for SteinerBnB<P, M, K, E, U>
 
 impl<P: Point> Default
     for SteinerBnB<P, EuclideanSpace, GeoMedianIter<P, EuclideanSpace, Uteshev>,
-                   GPEnumeration<P, FurthestSiteOrdering>, LineTree> {
+                   GPEnumeration<P, FurthestSiteOrdering>, LineTree, UpperBoundPruning> {
 
     fn default() -> Self {
         SteinerBnB::new(
@@ -122,7 +312,7 @@ impl<P: Point> Default
 }
 
 impl<P, H> SteinerBnB<P, H, GeoMedianIter<P, H, GeoMedianEllipsoid<P, Uteshev>>,
-                      GPEnumeration<P, FurthestSiteOrdering>, LineTree>
+                      GPEnumeration<P, FurthestSiteOrdering>, LineTree, UpperBoundPruning>
     where P: Point, H: HyperEllipsoidSpace<P> {
 
     pub fn default_hyperellipsoid() -> Self {
@@ -134,7 +324,68 @@ impl<P, H> SteinerBnB<P, H, GeoMedianIter<P, H, GeoMedianEllipsoid<P, Uteshev>>,
     }
 }
 
-impl<P, M, R> SteinerBnB<P, M, R, GPEnumeration<P, FurthestSiteOrdering>, LineTree>
+impl<P: Point> SteinerBnB<P, LpSpace<LInfinity>, GeoMedianIter<P, LpSpace<LInfinity>, ChebyshevMedian>,
+                           GPEnumeration<P, FurthestSiteOrdering>, LineTree, UpperBoundPruning> {
+
+    pub fn default_linfinity() -> Self {
+        SteinerBnB::new(
+            GeoMedianIter::default_with_geomedian(
+                ChebyshevMedian::default()),
+            GPEnumeration::default(),
+            LineTree::default())
+    }
+}
+
+impl<P: Point> SteinerBnB<P, PolytopeSpace<P>, GeoMedianIter<P, PolytopeSpace<P>, PolytopeMedian>,
+                           GPEnumeration<P, FurthestSiteOrdering>, LineTree, UpperBoundPruning> {
+
+    pub fn default_polytope() -> Self {
+        SteinerBnB::new(
+            GeoMedianIter::default_with_geomedian(
+                PolytopeMedian::default()),
+            GPEnumeration::default(),
+            LineTree::default())
+    }
+}
+
+impl<R: Real> SteinerBnB<Point2d<R>, LambdaSpace<R>, GeoMedianIter<Point2d<R>, LambdaSpace<R>, PolytopeMedian>,
+                          GPEnumeration<Point2d<R>, FurthestSiteOrdering>, LineTree, UpperBoundPruning> {
+
+    pub fn default_lambda() -> Self {
+        SteinerBnB::new(
+            GeoMedianIter::default_with_geomedian(
+                PolytopeMedian::default()),
+            GPEnumeration::default(),
+            LineTree::default())
+    }
+}
+
+impl<R: Real> SteinerBnB<Point2d<R>, LpSpace<R>, GeoMedianIter<Point2d<R>, LpSpace<R>, LpNewton>,
+                          GPEnumeration<Point2d<R>, FurthestSiteOrdering>, LineTree, UpperBoundPruning> {
+
+    pub fn default_lp() -> Self {
+        SteinerBnB::new(
+            GeoMedianIter::default_with_geomedian(
+                LpNewton::default()),
+            GPEnumeration::default(),
+            LineTree::default())
+    }
+}
+
+impl<P: Point> SteinerBnB<P, LpSpace<L1>, HananRMT,
+                           GPEnumeration<P, FurthestSiteOrdering>, LineTree, UpperBoundPruning> {
+
+    /// Rectilinear SMT solver for `LpSpace::new(L1)`. Every topology is
+    /// optimized exactly on the Hanan grid.
+    pub fn default_rectilinear() -> Self {
+        SteinerBnB::new(
+            HananRMT::default(),
+            GPEnumeration::default(),
+            LineTree::default())
+    }
+}
+
+impl<P, M, R> SteinerBnB<P, M, R, GPEnumeration<P, FurthestSiteOrdering>, LineTree, UpperBoundPruning>
     where P: Point,  {
 
     pub fn default_rmt(rmt: R) -> Self {
@@ -145,18 +396,568 @@ impl<P, M, R> SteinerBnB<P, M, R, GPEnumeration<P, FurthestSiteOrdering>, LineTr
     }
 }
 
+/// Steiner branch and bound that solves the subproblems below a fixed depth
+/// of the Gilbert-Pollak enumeration in parallel.
+///
+/// The enumeration is split into one subproblem for each topology at depth
+/// `split_depth` that is not pruned. The topologies above that depth are
+/// optimized and pruned while splitting, as in `SteinerBnB`. The subproblems
+/// are solved by a pool of threads, each with a fresh copy of the RMT
+/// algorithm, which share the length of the best tree found so far for
+/// pruning.
+#[derive(Debug)]
+pub struct ParallelSteinerBnB<P: Point, M, R, S: TerminalSorter, U, T = UpperBoundPruning> {
+    rmt: R,
+    enumerator: GPEnumeration<P, S>,
+    upperbound: U,
+    prunetest: T,
+    threads: usize,
+    split_depth: usize,
+    degeneracy_eps: f64,
+    limits: SearchLimits,
+    splitter: Option<R>,
+    workers: Vec<R>,
+    data: SteinerBnBData,
+    enumeration_data: GPEnumerationData,
+    _m: PhantomData<M>
+}
+
+/// Upper bound shared between the threads, stored as the bits of an `f64`.
+struct SharedBound(AtomicU64);
+
+impl SharedBound {
+    fn new(len: f64) -> Self {
+        SharedBound(AtomicU64::new(len.to_bits()))
+    }
+
+    fn get(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::SeqCst))
+    }
+
+    /// Lowers the bound to `len`. Returns false if the bound already was at
+    /// most `len`.
+    fn lower(&self, len: f64) -> bool {
+        let mut cur = self.0.load(Ordering::SeqCst);
+        loop {
+            if f64::from_bits(cur) <= len {
+                return false;
+            }
+            match self.0.compare_exchange_weak(cur, len.to_bits(),
+                                               Ordering::SeqCst, Ordering::SeqCst) {
+                Ok(_) => return true,
+                Err(c) => cur = c
+            }
+        }
+    }
+}
+
+/// The state of a parallel search shared between the threads.
+struct Shared {
+    bound: SharedBound,
+    limits: SearchLimits,
+    threads: usize,
+    start: Instant,
+    nodes: AtomicU64,
+    stopped: AtomicBool,
+    status: Mutex<SearchStatus>,
+    error: Mutex<Option<SteinerError>>
+}
+
+impl Shared {
+    /// Counts a topology that an enumerator returned against the node
+    /// limit. Returns false, and stops the search, if the limit was reached
+    /// before.
+    fn take_node(&self) -> bool {
+        let mut cur = self.nodes.load(Ordering::SeqCst);
+        loop {
+            if self.limits.nodes.map_or(false, |l| cur >= l) {
+                self.stop(SearchStatus::LimitReached);
+                return false;
+            }
+            match self.nodes.compare_exchange_weak(cur, cur + 1, Ordering::SeqCst, Ordering::SeqCst) {
+                Ok(_) => return true,
+                Err(c) => cur = c
+            }
+        }
+    }
+
+    /// Checks the other limits, with `memory` the memory taken by the
+    /// calling thread. Returns true if the search is stopped.
+    fn check<F: FnOnce() -> usize>(&self, memory: F) -> bool {
+        if self.stopped.load(Ordering::SeqCst) {
+            return true;
+        }
+        // The node limit is checked by take_node
+        match self.limits.stop(self.start, 0, memory) {
+            Some(s) => {
+                self.stop(s);
+                true
+            },
+            None => false
+        }
+    }
+
+    /// Stops the search, keeping the status of the first stop.
+    fn stop(&self, status: SearchStatus) {
+        let mut cur = self.status.lock().unwrap();
+        if !self.stopped.swap(true, Ordering::SeqCst) {
+            *cur = status;
+        }
+    }
+
+    /// Stops the search because of `e`, keeping the first error.
+    fn fail(&self, e: SteinerError) {
+        let mut cur = self.error.lock().unwrap();
+        if cur.is_none() {
+            *cur = Some(e);
+        }
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+}
+
+struct Worker<P: Point, R> {
+    rmt: R,
+    best: Option<(SteinerTree<P>, P::R)>,
+    data: SteinerBnBData,
+    enumeration_data: GPEnumerationData
+}
+
+impl<P, M, K, S, U> ParallelSteinerBnB<P, M, K, S, U>
+    where P: Point, S: TerminalSorter {
+
+    /// Prunes the partial topologies only by the upper bound, see
+    /// `prunetest` for others.
+    pub fn new(rmt: K, enumerator: GPEnumeration<P, S>, u: U) -> Self {
+        ParallelSteinerBnB {
+            rmt: rmt,
+            enumerator: enumerator,
+            upperbound: u,
+            prunetest: UpperBoundPruning,
+            threads: thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+            split_depth: 3,
+            degeneracy_eps: 0.00001,
+            limits: SearchLimits::new(),
+            splitter: None,
+            workers: Vec::new(),
+            data: SteinerBnBData::new(),
+            enumeration_data: GPEnumerationData::new(0),
+            _m: PhantomData
+        }
+    }
+}
+
+impl<P, M, K, S, U, T> ParallelSteinerBnB<P, M, K, S, U, T>
+    where P: Point, S: TerminalSorter {
+
+    /// Prunes the relatively minimal partial topologies that fail
+    /// `prunetest` besides those longer than the best tree.
+    pub fn prunetest<Q>(self, prunetest: Q) -> ParallelSteinerBnB<P, M, K, S, U, Q> {
+        ParallelSteinerBnB {
+            rmt: self.rmt,
+            enumerator: self.enumerator,
+            upperbound: self.upperbound,
+            prunetest: prunetest,
+            threads: self.threads,
+            split_depth: self.split_depth,
+            degeneracy_eps: self.degeneracy_eps,
+            limits: self.limits,
+            splitter: self.splitter,
+            workers: self.workers,
+            data: self.data,
+            enumeration_data: self.enumeration_data,
+            _m: PhantomData
+        }
+    }
+
+    pub fn rmt_alg(&mut self) -> &mut K {
+        &mut self.rmt
+    }
+
+    pub fn enumerator(&mut self) -> &mut GPEnumeration<P, S> {
+        &mut self.enumerator
+    }
+
+    pub fn prune_test(&mut self) -> &mut T {
+        &mut self.prunetest
+    }
+
+    /// The RMT algorithm used for the topologies above the split depth in
+    /// the last run.
+    pub fn splitter_rmt_alg(&self) -> Option<&K> {
+        self.splitter.as_ref()
+    }
+
+    /// The RMT algorithms used by the threads in the last run.
+    pub fn worker_rmt_algs(&self) -> &[K] {
+        &self.workers
+    }
+
+    /// The enumeration data of the last run, combined over all subproblems.
+    pub fn enumeration_data(&self) -> &GPEnumerationData {
+        &self.enumeration_data
+    }
+
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Depth of the enumeration (number of terminals added to the initial
+    /// three) at which the subproblems are split off.
+    pub fn split_depth(mut self, depth: usize) -> Self {
+        self.split_depth = depth;
+        self
+    }
+
+    /// Edges of the resulting tree shorter than `eps` times its length are
+    /// contracted when the tree is made non-degenerate.
+    pub fn degeneracy_eps(mut self, eps: f64) -> Self {
+        self.degeneracy_eps = eps;
+        self
+    }
+
+    /// Stops all threads after `limit` and returns the best tree found so
+    /// far, see `SteinerBnB::time_limit`.
+    pub fn time_limit(mut self, limit: Duration) -> Self {
+        self.limits.time = Some(limit);
+        self
+    }
+
+    /// Stops all threads after `limit` topologies have been enumerated in
+    /// total.
+    pub fn node_limit(mut self, limit: u64) -> Self {
+        self.limits.nodes = Some(limit);
+        self
+    }
+
+    /// Stops all threads when the topologies waiting to be solved, or those
+    /// kept by the enumerator of one thread, take more than about `bytes`
+    /// divided by the number of threads.
+    pub fn memory_limit(mut self, bytes: usize) -> Self {
+        self.limits.memory = Some(bytes);
+        self
+    }
+
+    /// Stops all threads as soon as `flag` is set.
+    pub fn cancel_flag(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.limits.cancel = Some(flag);
+        self
+    }
+}
+
+impl<P, M, K, S, U, T> ParallelSteinerBnB<P, M, K, S, U, T>
+    where P: Point, M: MinkowskiSpace<P>, K: RMT<P, M>, S: TerminalSorter,
+          T: PruneTest {
+
+    /// Solves subproblems from `queue` until it is empty, with `find`
+    /// computing the relatively minimal trees.
+    fn worker<F>(rmt: K, queue: &Mutex<Vec<GPEnumeration<P, S>>>, shared: &Shared,
+                 prunetest: &T, full: usize, geo: &M, find: &F) -> Worker<P, K>
+        where F: Fn(&mut K, &mut SteinerTree<P>, &M) -> Result<P::R, SteinerError> {
+        let mut res = Worker {
+            rmt: rmt,
+            best: None,
+            data: SteinerBnBData::new(),
+            enumeration_data: GPEnumerationData::new(0)
+        };
+
+        loop {
+            let job = queue.lock().unwrap().pop();
+            let mut enumerator = match job {
+                Some(e) => e,
+                None => return res
+            };
+
+            loop {
+                // Every thread may take its share of the memory
+                let memory = || enumerator.stored() * enumerator.tree().memory() * shared.threads;
+                if shared.check(memory) {
+                    res.enumeration_data.merge(enumerator.data());
+                    return res;
+                }
+                if !enumerator.next(geo) {
+                    break;
+                }
+                if !shared.take_node() {
+                    res.enumeration_data.merge(enumerator.data());
+                    return res;
+                }
+
+                let len = match find(&mut res.rmt, enumerator.tree_mut(), geo) {
+                    Ok(len) => len,
+                    Err(e) => {
+                        shared.fail(e);
+                        res.enumeration_data.merge(enumerator.data());
+                        return res;
+                    }
+                };
+                let best_len = P::R::from(shared.bound.get());
+                if enumerator.tree().terminals().len() == full {
+                    if len < best_len && shared.bound.lower(len.into()) {
+                        res.data.best_updates += 1;
+                        res.best = Some((enumerator.tree().clone(), len));
+                    }
+                } else if prunetest.prunetest(enumerator.tree(), len, best_len) {
+                    res.data.pruned += 1;
+                    enumerator.prune();
+                }
+            }
+
+            res.enumeration_data.merge(enumerator.data());
+        }
+    }
+}
+
+impl<P, M, K, S, U, T> ParallelSteinerBnB<P, M, K, S, U, T>
+    where P: Point + Send + Sync, P::R: Send + Sync, M: MinkowskiSpace<P> + Sync,
+          K: RMT<P, M> + Clone + Send, S: TerminalSorter + Clone + Send,
+          U: UpperBound<P, M>, T: PruneTest + Sync {
+
+    /// The parallel branch and bound search, with `find` computing the
+    /// relatively minimal trees in all threads. The first error stops all
+    /// threads.
+    fn search<F>(&mut self, t: Vec<P>, geo: &M, find: F) -> Result<SteinerTree<P>, SteinerError>
+        where F: Fn(&mut K, &mut SteinerTree<P>, &M) -> Result<P::R, SteinerError> + Sync {
+
+        let start = Instant::now();
+        let full = t.len();
+        let mut best = self.upperbound.bound(t.clone(), geo);
+        let mut best_len = best.len(geo);
+        let shared = Shared {
+            bound: SharedBound::new(best_len.into()),
+            limits: self.limits.clone(),
+            threads: self.threads,
+            start: start,
+            nodes: AtomicU64::new(0),
+            stopped: AtomicBool::new(false),
+            status: Mutex::new(SearchStatus::Optimal),
+            error: Mutex::new(None)
+        };
+        self.enumerator.init(t, geo);
+        self.data = SteinerBnBData::new();
+
+        // The RMT algorithm in self.rmt is never run, so that every thread
+        // starts from a fresh copy
+        let mut splitter = self.rmt.clone();
+        let mut jobs = Vec::new();
+        let mut root_len = None;
+        loop {
+            let memory = || (jobs.len() + self.enumerator.stored()) * self.enumerator.tree().memory();
+            if shared.check(memory) || !self.enumerator.next(geo) {
+                break;
+            }
+
+            let terms = self.enumerator.tree().terminals().len();
+            if terms >= 3 + self.split_depth || terms == full {
+                // The subproblem optimizes, and counts, its first topology
+                // itself
+                jobs.push(self.enumerator.subproblem());
+                self.enumerator.prune();
+            } else if !shared.take_node() {
+                break;
+            } else {
+                let len = try!(find(&mut splitter, self.enumerator.tree_mut(), geo));
+                if terms == 3 {
+                    root_len = Some(len);
+                }
+                if self.prunetest.prunetest(self.enumerator.tree(), len, best_len) {
+                    self.data.pruned += 1;
+                    self.enumerator.prune();
+                }
+            }
+        }
+        self.splitter = Some(splitter);
+        // The jobs are taken from the back
+        jobs.reverse();
+
+        let queue = Mutex::new(jobs);
+        let (prunetest, rmt, find) = (&self.prunetest, &self.rmt, &find);
+        let workers: Vec<Worker<P, K>> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..self.threads).map(|_| {
+                let (queue, shared) = (&queue, &shared);
+                let rmt = rmt.clone();
+                scope.spawn(move || Self::worker(rmt, queue, shared, prunetest, full, geo, find))
+            }).collect();
+
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        self.enumeration_data = self.enumerator.data().clone();
+        self.workers.clear();
+        for w in workers {
+            self.data.merge(&w.data);
+            self.enumeration_data.merge(&w.enumeration_data);
+            if let Some((tree, len)) = w.best {
+                if len < best_len {
+                    best = tree;
+                    best_len = len;
+                }
+            }
+            self.workers.push(w.rmt);
+        }
+        if let Some(e) = shared.error.lock().unwrap().take() {
+            return Err(e);
+        }
+
+        let status = *shared.status.lock().unwrap();
+        best.non_degenerate(geo, P::R::from(self.degeneracy_eps));
+        self.data.time = Instant::now() - start;
+        self.data.status = status;
+        self.data.length = best_len.into();
+        self.data.lower_bound = lower_bound(status, root_len, best_len);
+        Ok(best)
+    }
+}
+
+impl<P, M, K, S, U, T> SMT<P, M> for ParallelSteinerBnB<P, M, K, S, U, T>
+    where P: Point + Send + Sync, P::R: Send + Sync, M: MinkowskiSpace<P> + Sync,
+          K: RMT<P, M> + Clone + Send, S: TerminalSorter + Clone + Send,
+          U: UpperBound<P, M>, T: PruneTest + Sync {
+
+    type D = SteinerBnBData;
+
+    fn find(&mut self, t: Vec<P>, geo: &M) -> SteinerTree<P> {
+        // Without checks the search itself can't fail
+        self.search(t, geo, |rmt, tree, geo| Ok(rmt.find(tree, geo))).unwrap()
+    }
+
+    fn try_find(&mut self, t: Vec<P>, geo: &M) -> Result<SteinerTree<P>, SteinerError> {
+        try!(error::check_terminals(&t));
+        let best = try!(self.search(t, geo, |rmt, tree, geo| rmt.try_find(tree, geo)));
+        if best.len(geo).is_number() { Ok(best) } else { Err(SteinerError::NonFiniteLength) }
+    }
+
+    fn data(&self) -> &Self::D {
+        &self.data
+    }
+
+    fn print(&self, f: &mut fmt::Formatter, inde: u32) -> fmt::Result {
+        fn indent(f: &mut fmt::Formatter, indent: u32) -> fmt::Result {
+            for _ in 0..indent {
+                try!(write!(f, " "));
+            }
+            Ok(())
+        }
+
+        try!(write!(f, "Parallel Steiner branch and bound algorithm with {} threads \
+            splitting at depth {} of the ", self.threads, self.split_depth));
+        try!(self.enumerator.print(f, inde+4));
+        try!({indent(f, inde);
+            write!(f, ".\nRelatively minimal trees were found using the ");
+            self.rmt.print(f, inde+4) });
+        Ok(())
+    }
+
+    fn print_data<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        try!(writeln!(w, "{}", self.data()));
+        try!(writeln!(w, "{}", self.enumeration_data));
+        if let Some(ref rmt) = self.splitter {
+            try!(writeln!(w, "Splitting:"));
+            try!(rmt.print_data(w));
+        }
+        for (i, rmt) in self.workers.iter().enumerate() {
+            try!(writeln!(w, "Thread {}:", i));
+            try!(rmt.print_data(w));
+        }
+        Ok(())
+    }
+
+    fn report(&self) -> Stats {
+        let threads = self.workers.iter().enumerate()
+            .fold(Stats::new(), |t, (i, rmt)| t.group(&i.to_string(), rmt.report()));
+        let stats = self.data.report()
+            .group("enumerator", self.enumeration_data.report());
+        match self.splitter {
+            Some(ref rmt) => stats.group("split", rmt.report()).group("threads", threads),
+            None => stats.group("threads", threads)
+        }
+    }
+}
+
+impl<P, M, K, S, U, T> fmt::Display for ParallelSteinerBnB<P, M, K, S, U, T>
+    where P: Point + Send + Sync, P::R: Send + Sync, M: MinkowskiSpace<P> + Sync,
+          K: RMT<P, M> + Clone + Send, S: TerminalSorter + Clone + Send,
+          U: UpperBound<P, M>, T: PruneTest + Sync {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.print(f, 0)
+    }
+}
+
+impl<P: Point> Default
+    for ParallelSteinerBnB<P, EuclideanSpace, GeoMedianIter<P, EuclideanSpace, Uteshev>,
+                           FurthestSiteOrdering, LineTree, UpperBoundPruning> {
+
+    fn default() -> Self {
+        ParallelSteinerBnB::new(
+            GeoMedianIter::default(),
+            GPEnumeration::default(),
+            LineTree::default())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SteinerBnBData {
     time: Duration,
-    best_updates: u64
+    best_updates: u64,
+    pruned: u64,
+    status: SearchStatus,
+    length: f64,
+    lower_bound: Option<f64>
 }
 
 impl SteinerBnBData {
-    fn new() -> Self {
+    pub fn new() -> Self {
         SteinerBnBData {
             time: Duration::new(0, 0),
-            best_updates: 0
+            best_updates: 0,
+            pruned: 0,
+            status: SearchStatus::Optimal,
+            length: ::std::f64::INFINITY,
+            lower_bound: None
+        }
+    }
+
+    pub fn pruned(&self) -> u64 {
+        self.pruned
+    }
+
+    /// Whether the last search finished or was stopped early.
+    pub fn status(&self) -> SearchStatus {
+        self.status
+    }
+
+    /// Length of the tree returned by the last search.
+    pub fn length(&self) -> f64 {
+        self.length
+    }
+
+    /// A lower bound on the length of the Steiner minimal tree, which equals
+    /// `length` if the search finished. A stopped search gives the length
+    /// of the relatively minimal tree of the first three terminals. Both
+    /// are only as accurate as the RMT algorithm, so the bound is a
+    /// heuristic for iterative medians, which stop short of the minimum.
+    /// `None` if the search was stopped before any bound was known.
+    pub fn lower_bound(&self) -> Option<f64> {
+        self.lower_bound
+    }
+
+    /// Adds the statistics of `other` to these, both from parts of one
+    /// search: a stopped part stops the whole search, the length is the
+    /// shorter one and the lower bound the smaller one, unknown if one of
+    /// them is.
+    pub fn merge(&mut self, other: &Self) {
+        self.time += other.time;
+        self.best_updates += other.best_updates;
+        self.pruned += other.pruned;
+        if self.status == SearchStatus::Optimal {
+            self.status = other.status;
         }
+        self.length = self.length.min(other.length);
+        self.lower_bound = match (self.lower_bound, other.lower_bound) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            _ => None
+        };
     }
 }
 
@@ -170,6 +971,21 @@ impl SmtData for SteinerBnBData {
     }
 }
 
+impl Report for SteinerBnBData {
+    fn report(&self) -> Stats {
+        let stats = Stats::new()
+            .seconds("time", &self.time)
+            .count("best_updates", self.best_updates)
+            .count("pruned", self.pruned)
+            .text("status", &self.status.to_string())
+            .value("length", self.length);
+        match self.lower_bound {
+            Some(b) => stats.value("lower_bound", b),
+            None => stats
+        }
+    }
+}
+
 impl fmt::Display for SteinerBnBData {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         fn printdur(dur: &Duration) -> f64 {
@@ -178,6 +994,279 @@ impl fmt::Display for SteinerBnBData {
 
         try!(writeln!(f, "Data for the Steiner branch and bound algorithm:"));
         try!(writeln!(f, "\tTotal time: {}", printdur(&self.time)));
-        writeln!(f, "\tNumber of best updates: {}", self.best_updates)
+        try!(writeln!(f, "\tNumber of best updates: {}", self.best_updates));
+        try!(writeln!(f, "\tNumber of topologies pruned by the prune test: {}", self.pruned));
+        try!(writeln!(f, "\tStatus: {}", self.status));
+        try!(writeln!(f, "\tLength of the best tree: {}", self.length));
+        match self.lower_bound {
+            Some(b) => writeln!(f, "\tLower bound: {}", b),
+            None => writeln!(f, "\tLower bound: unknown")
+        }
+    }
+}
+
+/// How a search ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchStatus {
+    /// The search finished and the tree is optimal
+    Optimal,
+    /// A time or node limit stopped the search
+    LimitReached,
+    /// The cancel flag stopped the search
+    Cancelled
+}
+
+impl fmt::Display for SearchStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SearchStatus::Optimal => write!(f, "optimal"),
+            SearchStatus::LimitReached => write!(f, "limit reached"),
+            SearchStatus::Cancelled => write!(f, "cancelled")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use generators::Generator;
+
+    /// Never prunes, so every full topology is enumerated.
+    struct NoPruning;
+    impl PruneTest for NoPruning {
+        fn prunetest<P: Point>(&self, _: &SteinerTree<P>, _: P::R, _: P::R) -> bool {
+            false
+        }
+    }
+
+    fn points(seed: u64, n: usize) -> Vec<Point2d<f64>> {
+        Generator::new(seed, 2).uniform(n)
+    }
+
+    #[test]
+    fn square_is_solved() {
+        let t = vec![Point2d::new([0.0, 0.0]), Point2d::new([1.0, 0.0]),
+                     Point2d::new([1.0, 1.0]), Point2d::new([0.0, 1.0])];
+        let geo = EuclideanSpace::new();
+        let mut smt = SteinerBnB::default();
+        let st = smt.find(t, &geo);
+        assert!((st.len(&geo) - (1.0 + 3f64.sqrt())).abs() < 1e-2);
+        assert_eq!(st.steiner_points().len(), 2);
+    }
+
+    #[test]
+    fn pruning_keeps_the_optimum() {
+        let geo = EuclideanSpace::new();
+        for seed in 0..4 {
+            let t = points(seed, 8);
+            let mut pruned = SteinerBnB::default();
+            let mut full = SteinerBnB::new(GeoMedianIter::default(), GPEnumeration::default(),
+                                           LineTree).prunetest(NoPruning);
+            let a = pruned.find(t.clone(), &geo).len(&geo);
+            let b = full.find(t, &geo).len(&geo);
+            assert!((a - b).abs() < 1e-6, "{} != {}", a, b);
+            assert!(pruned.data().pruned() > 0);
+            assert_eq!(full.data().pruned(), 0);
+        }
+    }
+
+    /// Number of enumerated topologies, relatively minimal trees and pruned
+    /// topologies of a parallel run.
+    fn parallel_counts<T: PruneTest + Sync>(t: Vec<Point2d<f64>>, threads: usize, depth: usize,
+                                            prunetest: T) -> (f64, usize, usize, u64) {
+        let geo = EuclideanSpace::new();
+        let mut smt = ParallelSteinerBnB::new(GeoMedianIter::default(), GPEnumeration::default(),
+                                              LineTree)
+            .prunetest(prunetest)
+            .threads(threads)
+            .split_depth(depth);
+        let len = smt.find(t, &geo).len(&geo);
+        let rmts = smt.worker_rmt_algs().iter().map(|r| r.data().nodes()).sum::<usize>()
+            + smt.splitter_rmt_alg().unwrap().data().nodes();
+        (len, smt.enumeration_data().nodes(), rmts, smt.data().pruned())
+    }
+
+    fn sequential_counts<T: PruneTest>(t: Vec<Point2d<f64>>, prunetest: T) -> (f64, usize, usize, u64) {
+        let geo = EuclideanSpace::new();
+        let mut smt = SteinerBnB::new(GeoMedianIter::default(), GPEnumeration::default(),
+                                      LineTree).prunetest(prunetest);
+        let len = smt.find(t, &geo).len(&geo);
+        (len, smt.enumerator().data().nodes(), smt.rmt_alg().data().nodes(), smt.data().pruned())
+    }
+
+    #[test]
+    fn parallel_counts_every_topology_once() {
+        let t = points(5, 7);
+        let (len, nodes, rmts, pruned) = sequential_counts(t.clone(), NoPruning);
+        for &depth in [0, 1, 2, 10].iter() {
+            let (plen, pnodes, prmts, ppruned) = parallel_counts(t.clone(), 3, depth, NoPruning);
+            // The relatively minimal trees start from the Steiner points as
+            // left by the previous topology, which depends on the threads
+            assert!((len - plen).abs() < 1e-3 * len);
+            assert_eq!((nodes, rmts, pruned), (pnodes, prmts, ppruned));
+        }
+    }
+
+    #[test]
+    fn parallel_matches_sequential_search() {
+        let t = points(6, 8);
+        let (len, nodes, rmts, pruned) = sequential_counts(t.clone(), UpperBoundPruning);
+        let (plen, pnodes, prmts, ppruned) = parallel_counts(t.clone(), 1, 0, UpperBoundPruning);
+        assert_eq!((len, nodes, rmts, pruned), (plen, pnodes, prmts, ppruned));
+
+        for &threads in [1, 4].iter() {
+            let (plen, _, _, _) = parallel_counts(t.clone(), threads, 2, UpperBoundPruning);
+            assert!((len - plen).abs() < 1e-3 * len);
+        }
+    }
+
+    #[test]
+    fn lambda_square() {
+        // Within the Euclidean SMT and the Euclidean SMT stretched by the
+        // largest ratio of the λ-norm and the Euclidean norm
+        let t = vec![Point2d::new([0.0, 0.0]), Point2d::new([1.0, 0.0]),
+                     Point2d::new([1.0, 1.0]), Point2d::new([0.0, 1.0])];
+        let esmt = 1.0 + 3f64.sqrt();
+        for lambda in 3..7 {
+            let geo = LambdaSpace::new(lambda);
+            let len = SteinerBnB::default_lambda().find(t.clone(), &geo).len(&geo);
+            let stretch = 1.0 / (::std::f64::consts::PI / (2.0 * lambda as f64)).cos();
+            assert!(len >= esmt - 1e-9 && len <= esmt * stretch + 1e-9, "λ={}: {}", lambda, len);
+        }
+    }
+
+    #[test]
+    fn limits_stop_the_search() {
+        let geo = EuclideanSpace::new();
+        let t = points(7, 9);
+        let upper = LineTree.bound(t.clone(), &geo).len(&geo);
+
+        let mut smt = SteinerBnB::default().node_limit(20);
+        let len = smt.find(t.clone(), &geo).len(&geo);
+        assert_eq!(smt.data().status(), SearchStatus::LimitReached);
+        assert_eq!(smt.rmt_alg().data().nodes(), 20);
+        assert!(len <= upper + 1e-9);
+        let lower = smt.data().lower_bound().unwrap();
+        assert!(lower <= smt.data().length());
+
+        let mut smt = SteinerBnB::default().memory_limit(1);
+        smt.find(t.clone(), &geo);
+        assert_eq!(smt.data().status(), SearchStatus::LimitReached);
+        assert_eq!(smt.data().lower_bound(), None);
+
+        let mut smt = SteinerBnB::default().time_limit(Duration::new(0, 0));
+        smt.find(t.clone(), &geo);
+        assert_eq!(smt.data().status(), SearchStatus::LimitReached);
+
+        let mut smt = SteinerBnB::default().cancel_flag(Arc::new(AtomicBool::new(true)));
+        let len = smt.find(t.clone(), &geo).len(&geo);
+        assert_eq!(smt.data().status(), SearchStatus::Cancelled);
+        assert_eq!(smt.rmt_alg().data().nodes(), 0);
+        assert!((len - upper).abs() < 1e-9);
+
+        let mut smt = SteinerBnB::default().node_limit(1 << 40).memory_limit(1 << 30);
+        smt.find(t, &geo);
+        assert_eq!(smt.data().status(), SearchStatus::Optimal);
+        assert_eq!(smt.data().lower_bound(), Some(smt.data().length()));
+    }
+
+    #[test]
+    fn limits_stop_the_parallel_search() {
+        let geo = EuclideanSpace::new();
+        let t = points(7, 9);
+        let parallel = || ParallelSteinerBnB::new(GeoMedianIter::default(), GPEnumeration::default(),
+                                                  LineTree).threads(3).split_depth(2);
+
+        for &limit in [0, 1, 5, 50].iter() {
+            let mut smt = parallel().node_limit(limit);
+            smt.find(t.clone(), &geo);
+            assert_eq!(smt.data().status(), SearchStatus::LimitReached);
+            let rmts = smt.worker_rmt_algs().iter().map(|r| r.data().nodes()).sum::<usize>()
+                + smt.splitter_rmt_alg().unwrap().data().nodes();
+            assert_eq!(rmts, limit as usize);
+            assert!(smt.data().lower_bound().map_or(true, |l| l <= smt.data().length()));
+        }
+
+        let mut smt = parallel().memory_limit(1);
+        smt.find(t.clone(), &geo);
+        assert_eq!(smt.data().status(), SearchStatus::LimitReached);
+
+        let mut smt = parallel().cancel_flag(Arc::new(AtomicBool::new(true)));
+        smt.find(t.clone(), &geo);
+        assert_eq!(smt.data().status(), SearchStatus::Cancelled);
+        assert!(smt.worker_rmt_algs().iter().all(|r| r.data().nodes() == 0));
+
+        let mut smt = parallel().time_limit(Duration::from_secs(3600));
+        smt.find(t, &geo);
+        assert_eq!(smt.data().status(), SearchStatus::Optimal);
+        assert_eq!(smt.data().lower_bound(), Some(smt.data().length()));
+    }
+
+    #[test]
+    fn exact_parallel_node_limit_finishes_the_search() {
+        let geo = EuclideanSpace::new();
+        let t = points(5, 7);
+        let parallel = || ParallelSteinerBnB::new(GeoMedianIter::default(), GPEnumeration::default(),
+                                                  LineTree).prunetest(NoPruning).threads(4).split_depth(1);
+        let rmts = |smt: &ParallelSteinerBnB<_, _, GeoMedianIter<_, _, _>, _, _, _>|
+            smt.worker_rmt_algs().iter().map(|r| r.data().nodes()).sum::<usize>()
+                + smt.splitter_rmt_alg().unwrap().data().nodes();
+
+        let mut smt = parallel();
+        smt.find(t.clone(), &geo);
+        let nodes = rmts(&smt);
+        // Without pruning every run enumerates the same topologies, which
+        // must not be stopped by a limit of exactly their number
+        for _ in 0..20 {
+            let mut smt = parallel().node_limit(nodes as u64);
+            smt.find(t.clone(), &geo);
+            assert_eq!(smt.data().status(), SearchStatus::Optimal);
+            assert_eq!(rmts(&smt), nodes);
+
+            let mut smt = parallel().node_limit(nodes as u64 - 1);
+            smt.find(t.clone(), &geo);
+            assert_eq!(smt.data().status(), SearchStatus::LimitReached);
+            assert_eq!(rmts(&smt), nodes - 1);
+        }
+    }
+
+    #[test]
+    fn parallel_search_reports_rmt_errors() {
+        let geo = EuclideanSpace::new();
+        let t = points(3, 7);
+        let rmt = || GeoMedianIter::default().max_iterations(1);
+        let expected = Err(SteinerError::NoConvergence { algorithm: "relatively minimal tree iteration" });
+        assert_eq!(SteinerBnB::new(rmt(), GPEnumeration::default(), LineTree)
+                   .try_find(t.clone(), &geo).map(|_| ()), expected);
+        // In the threads and while splitting
+        for &depth in [0, 10].iter() {
+            let mut smt = ParallelSteinerBnB::new(rmt(), GPEnumeration::default(), LineTree)
+                .threads(3).split_depth(depth);
+            assert_eq!(smt.try_find(t.clone(), &geo).map(|_| ()), expected);
+            assert_eq!(smt.find(t.clone(), &geo).terminals().len(), 7);
+        }
+
+        let mut nan = t.clone();
+        nan[4] = Point2d::new([::std::f64::NAN, 0.0]);
+        let mut smt = ParallelSteinerBnB::default();
+        assert_eq!(smt.try_find(nan, &geo).map(|_| ()), Err(SteinerError::NonFinite { terminal: 4 }));
+        assert!(smt.try_find(t, &geo).is_ok());
+    }
+
+    #[test]
+    fn merging_keeps_the_search_results() {
+        let mut a = SteinerBnBData::new();
+        a.length = 3.0;
+        a.lower_bound = Some(2.0);
+        let mut b = a.clone();
+        b.length = 2.5;
+        b.lower_bound = Some(1.5);
+        b.status = SearchStatus::Cancelled;
+
+        let mut m = a.clone();
+        m.merge(&b);
+        assert_eq!((m.status(), m.length(), m.lower_bound()), (SearchStatus::Cancelled, 2.5, Some(1.5)));
+        m.merge(&SteinerBnBData::new());
+        assert_eq!((m.status(), m.length(), m.lower_bound()), (SearchStatus::Cancelled, 2.5, None));
     }
 }
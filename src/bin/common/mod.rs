@@ -0,0 +1,501 @@
+//! Solver configuration shared by the command line tools. A `SolverConfig`
+//! is parsed from options and builds the parts of a `SteinerBnB` for the
+//! chosen space and algorithms, which are handed to a `Solve` visitor since
+//! their types are only known at runtime.
+
+use minkowski_steiner::traits::*;
+use minkowski_steiner::geo::points::{PointNd, Point2d};
+use minkowski_steiner::geo::spaces::{EuclideanSpace, LpSpace, L1, LInfinity, HyperEllipsoidSpaceNd,
+    LambdaSpace, PolytopeSpace};
+use minkowski_steiner::algorithms::rmt::{GeoMedianIter};
+use minkowski_steiner::algorithms::geomedians::*;
+use minkowski_steiner::algorithms::rectilinear::{HananRMT};
+use minkowski_steiner::enumerator::{GPEnumeration, GPOrder, FurthestSiteOrdering, NoOrdering};
+use minkowski_steiner::algorithms::mst::{MSTAlgorithm};
+use minkowski_steiner::upperbounds::{LineTree, MSTBoundWith};
+
+pub type Pt = PointNd<f64>;
+
+pub const SOLVER_OPTIONS: &'static str = "  -s, --space <SPACE>        euclidean (default), lp:<p>, l1, linf,
+                             ellipsoid:<a,b,...>, lambda:<k> (planar points
+                             only) or polytope:<x,y,...;x,y,...>, whose unit
+                             ball is spanned by the given vertices and their
+                             negations
+  -a, --algorithm <ALG>      Geometric median algorithm of the relatively
+                             minimal trees: uteshev, weiszfeld, ostresh
+                             (euclidean and ellipsoid spaces), brimberglove
+                             (lp spaces with p <= 2), chiafranco (lp spaces
+                             with p > 2), coordinate (l1), chebyshev (linf)
+                             or polytope (lambda and polytope spaces); or
+                             hanan, the exact relatively minimal trees of l1.
+                             Defaults to uteshev in euclidean and ellipsoid
+                             spaces, to the applicable one of brimberglove
+                             and chiafranco in lp spaces, to hanan in l1, to
+                             chebyshev in linf and to polytope in lambda and
+                             polytope spaces
+      --epsilon <E>          Smoothing of chiafranco (default 1e-8)
+      --cutoff <A>           Change in tree length below which the
+                             relatively minimal tree iteration stops
+                             (default 1e-5)
+      --no-bsd               Disable bottleneck Steiner distance pruning
+      --no-ss                Disable smallest spheres pruning
+      --order <ORDER>        depth-first (default) or best-first:<frontier>
+      --sorter <SORTER>      Terminal ordering: furthest-site (default) or none
+  -u, --upper-bound <UB>     line (default) or mst
+      --mst <MST>            MST algorithm of the mst bound and the
+                             bottleneck Steiner distances: kruskal (default)
+                             or prim, which needs less memory
+      --degeneracy <EPS>     Contract edges shorter than EPS times the tree
+                             length (default 1e-5)
+      --preprocess           Merge duplicate points and scale the points into
+                             the unit box before solving";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Space {
+    Euclidean,
+    Lp(f64),
+    L1,
+    LInfinity,
+    Ellipsoid(Vec<f64>),
+    Lambda(usize),
+    /// The vertices spanning the unit ball, with their negations
+    Polytope(Vec<Vec<f64>>)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Algorithm {
+    Uteshev,
+    Weiszfeld,
+    Ostresh,
+    BrimbergLove,
+    ChiaFranco,
+    Coordinate,
+    Chebyshev,
+    Polytope,
+    Hanan
+}
+
+impl Algorithm {
+    /// The spaces the algorithm works in, for error messages.
+    fn spaces(&self) -> &'static str {
+        match *self {
+            Algorithm::Uteshev | Algorithm::Weiszfeld | Algorithm::Ostresh =>
+                "euclidean and ellipsoid spaces",
+            Algorithm::BrimbergLove | Algorithm::ChiaFranco => "lp spaces",
+            Algorithm::Coordinate | Algorithm::Hanan => "the l1 space",
+            Algorithm::Chebyshev => "the linf space",
+            Algorithm::Polytope => "lambda and polytope spaces"
+        }
+    }
+
+    fn unsupported<T>(&self) -> Result<T, String> {
+        Err(format!("{:?} works in {} only", self, self.spaces()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sorter {
+    FurthestSite,
+    None
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bound {
+    Line,
+    Mst
+}
+
+/// Receives the parts of the configured solver. The terminals are `Pt`,
+/// except in spaces that are only defined for other point types, such as
+/// `Point2d` in λ-geometries.
+pub trait Solve {
+    type Output;
+
+    fn solve<P, M, K, S, U>(self, rmt: K, enumerator: GPEnumeration<P, S>,
+                            upperbound: U, terminals: Vec<P>, geo: &M) -> Self::Output
+        where P: Point + Send + Sync, P::R: Send + Sync,
+              M: MinkowskiSpace<P> + Send + Sync,
+              K: RMT<P, M> + Clone + Send,
+              S: TerminalSorter + Clone + Send,
+              U: UpperBound<P, M>;
+}
+
+#[derive(Debug, Clone)]
+pub struct SolverConfig {
+    pub space: Space,
+    pub algorithm: Option<Algorithm>,
+    pub epsilon: f64,
+    pub cutoff: f64,
+    pub bsd: bool,
+    pub ss: bool,
+    pub order: GPOrder,
+    pub sorter: Sorter,
+    pub bound: Bound,
+    pub mst: MSTAlgorithm,
+    pub degeneracy: f64,
+    pub preprocess: bool
+}
+
+pub fn parse_number(option: &str, value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(v) if v.is_finite() => Ok(v),
+        _ => Err(format!("{} expects a number, found '{}'", option, value))
+    }
+}
+
+fn parse_space(value: &str) -> Result<Space, String> {
+    let mut parts = value.splitn(2, ':');
+    match (parts.next().unwrap().to_lowercase().as_ref(), parts.next()) {
+        ("euclidean", None) => Ok(Space::Euclidean),
+        ("l1", None) => Ok(Space::L1),
+        ("linf", None) => Ok(Space::LInfinity),
+        ("lp", Some(p)) => {
+            let p = try!(parse_number("lp:<p>", p));
+            if p < 1.0 {
+                return Err(format!("lp spaces need p >= 1, found {}", p));
+            }
+            Ok(Space::Lp(p))
+        }
+        ("ellipsoid", Some(cs)) => {
+            let cs: Vec<f64> = try!(cs.split(',').map(|c| parse_number("ellipsoid:<a,b,...>", c.trim())).collect());
+            if cs.iter().any(|&c| c <= 0.0) {
+                return Err("the axes of an ellipsoid must be positive".to_string());
+            }
+            Ok(Space::Ellipsoid(cs))
+        }
+        ("lambda", Some(k)) => match k.parse::<usize>() {
+            Ok(k) if k >= 2 => Ok(Space::Lambda(k)),
+            _ => Err(format!("lambda:<k> needs an integer k >= 2, found '{}'", k))
+        },
+        ("polytope", Some(vs)) => {
+            let mut vertices = vec![];
+            for v in vs.split(';') {
+                let v: Vec<f64> = try!(v.split(',')
+                    .map(|c| parse_number("polytope:<x,y,...;x,y,...>", c.trim())).collect());
+                if vertices.first().map_or(false, |w: &Vec<f64>| w.len() != v.len()) {
+                    return Err("the vertices of a polytope must have the same dimension".to_string());
+                }
+                vertices.push(v);
+            }
+            Ok(Space::Polytope(vertices))
+        }
+        _ => Err(format!("unknown space '{}'", value))
+    }
+}
+
+impl SolverConfig {
+    pub fn new() -> Self {
+        SolverConfig {
+            space: Space::Euclidean,
+            algorithm: None,
+            epsilon: 1e-8,
+            cutoff: 0.00001,
+            bsd: true,
+            ss: true,
+            order: GPOrder::DepthFirst,
+            sorter: Sorter::FurthestSite,
+            bound: Bound::Line,
+            mst: MSTAlgorithm::Kruskal,
+            degeneracy: 0.00001,
+            preprocess: false
+        }
+    }
+
+    /// Parses the solver option `arg`, taking its value from `args`. Returns
+    /// false if `arg` is not a solver option.
+    pub fn parse_option<I: Iterator<Item=String>>(&mut self, arg: &str, args: &mut I) -> Result<bool, String> {
+        match arg {
+            "--no-bsd" => {
+                self.bsd = false;
+                return Ok(true);
+            }
+            "--no-ss" => {
+                self.ss = false;
+                return Ok(true);
+            }
+            "--preprocess" => {
+                self.preprocess = true;
+                return Ok(true);
+            }
+            "-s" | "--space" | "-a" | "--algorithm" | "--epsilon" | "--cutoff" | "--order" |
+            "--sorter" | "-u" | "--upper-bound" | "--mst" | "--degeneracy" => { }
+            _ => return Ok(false)
+        }
+
+        let value = try!(args.next().ok_or_else(|| format!("{} expects a value", arg)));
+        match arg {
+            "-s" | "--space" => self.space = try!(parse_space(&value)),
+            "-a" | "--algorithm" => self.algorithm = Some(match value.to_lowercase().as_ref() {
+                "uteshev" => Algorithm::Uteshev,
+                "weiszfeld" => Algorithm::Weiszfeld,
+                "ostresh" => Algorithm::Ostresh,
+                "brimberglove" => Algorithm::BrimbergLove,
+                "chiafranco" => Algorithm::ChiaFranco,
+                "coordinate" => Algorithm::Coordinate,
+                "chebyshev" => Algorithm::Chebyshev,
+                "polytope" => Algorithm::Polytope,
+                "hanan" => Algorithm::Hanan,
+                _ => return Err(format!("unknown algorithm '{}'", value))
+            }),
+            "--epsilon" => self.epsilon = try!(parse_number(arg, &value)),
+            "--cutoff" => self.cutoff = try!(parse_number(arg, &value)),
+            "--order" => self.order = match value.to_lowercase().as_ref() {
+                "depth-first" => GPOrder::DepthFirst,
+                v if v.starts_with("best-first:") => match v["best-first:".len()..].parse() {
+                    Ok(n) => GPOrder::BestFirst(n),
+                    Err(_) => return Err(format!("invalid frontier size in '{}'", value))
+                },
+                _ => return Err(format!("unknown order '{}'", value))
+            },
+            "--sorter" => self.sorter = match value.to_lowercase().as_ref() {
+                "furthest-site" => Sorter::FurthestSite,
+                "none" => Sorter::None,
+                _ => return Err(format!("unknown terminal ordering '{}'", value))
+            },
+            "-u" | "--upper-bound" => self.bound = match value.to_lowercase().as_ref() {
+                "line" => Bound::Line,
+                "mst" => Bound::Mst,
+                _ => return Err(format!("unknown upper bound '{}'", value))
+            },
+            "--mst" => self.mst = match value.to_lowercase().as_ref() {
+                "prim" => MSTAlgorithm::Prim,
+                "kruskal" => MSTAlgorithm::Kruskal,
+                _ => return Err(format!("unknown MST algorithm '{}'", value))
+            },
+            _ => self.degeneracy = try!(parse_number(arg, &value))
+        }
+        Ok(true)
+    }
+
+    /// Builds the solver for `terminals` and hands it to `v`. Fails if the
+    /// algorithm does not work in the space or the space does not fit the
+    /// terminals.
+    pub fn solve<V: Solve>(&self, v: V, terminals: Vec<Pt>) -> Result<V::Output, String> {
+        let dim = terminals.first().map(|t| t.dim()).unwrap_or(0);
+
+        match self.space {
+            Space::Euclidean => {
+                let geo = EuclideanSpace;
+                match self.algorithm.unwrap_or(Algorithm::Uteshev) {
+                    Algorithm::Uteshev =>
+                        Ok(self.with_median(v, Uteshev::default(), terminals, &geo)),
+                    Algorithm::Weiszfeld =>
+                        Ok(self.with_median(v, GeoMedianStepper::default_with_step(Weiszfeld::default()),
+                            terminals, &geo)),
+                    Algorithm::Ostresh =>
+                        Ok(self.with_median(v, GeoMedianStepper::default_with_step(Ostresh::default()),
+                            terminals, &geo)),
+                    a => a.unsupported()
+                }
+            }
+            Space::Lp(p) => {
+                let geo = LpSpace::new(p);
+                let default = if p <= 2.0 { Algorithm::BrimbergLove } else { Algorithm::ChiaFranco };
+                match self.algorithm.unwrap_or(default) {
+                    Algorithm::BrimbergLove if p > 2.0 =>
+                        Err(format!("BrimbergLove needs 1 <= p <= 2, found {}", p)),
+                    Algorithm::ChiaFranco if p <= 2.0 =>
+                        Err(format!("ChiaFranco needs p > 2, found {}", p)),
+                    Algorithm::BrimbergLove =>
+                        Ok(self.with_median(v, GeoMedianStepper::default_with_step(BrimbergLove::default()),
+                            terminals, &geo)),
+                    Algorithm::ChiaFranco =>
+                        Ok(self.with_median(v, GeoMedianStepper::default_with_step(ChiaFrancoApprox::new(self.epsilon)),
+                            terminals, &geo)),
+                    a => a.unsupported()
+                }
+            }
+            Space::L1 => {
+                let geo = LpSpace::new(L1);
+                match self.algorithm.unwrap_or(Algorithm::Hanan) {
+                    Algorithm::Hanan => Ok(self.with_rmt(v, HananRMT::default(), terminals, &geo)),
+                    Algorithm::Coordinate =>
+                        Ok(self.with_median(v, CoordinateMedian::default(), terminals, &geo)),
+                    a => a.unsupported()
+                }
+            }
+            Space::LInfinity => {
+                let geo = LpSpace::new(LInfinity);
+                match self.algorithm.unwrap_or(Algorithm::Chebyshev) {
+                    Algorithm::Chebyshev =>
+                        Ok(self.with_median(v, ChebyshevMedian::default(), terminals, &geo)),
+                    a => a.unsupported()
+                }
+            }
+            Space::Ellipsoid(ref axes) => {
+                if axes.len() != dim {
+                    return Err(format!(
+                        "The ellipsoid has {} axes but the points have {} coordinates", axes.len(), dim));
+                }
+                let geo = HyperEllipsoidSpaceNd::new(axes.clone());
+                match self.algorithm.unwrap_or(Algorithm::Uteshev) {
+                    Algorithm::Uteshev =>
+                        Ok(self.with_median(v, GeoMedianEllipsoid::new(Uteshev::default()), terminals, &geo)),
+                    Algorithm::Weiszfeld =>
+                        Ok(self.with_median(v, GeoMedianEllipsoid::new(
+                            GeoMedianStepper::default_with_step(Weiszfeld::default())), terminals, &geo)),
+                    Algorithm::Ostresh =>
+                        Ok(self.with_median(v, GeoMedianEllipsoid::new(
+                            GeoMedianStepper::default_with_step(Ostresh::default())), terminals, &geo)),
+                    a => a.unsupported()
+                }
+            }
+            Space::Lambda(k) => {
+                if dim != 2 {
+                    return Err(format!("λ-geometries are planar but the points have {} coordinates", dim));
+                }
+                let geo = LambdaSpace::new(k);
+                let terminals: Vec<Point2d<f64>> = terminals.iter()
+                    .map(|t| Point2d::from_coords(t.coords()).unwrap())
+                    .collect();
+                match self.algorithm.unwrap_or(Algorithm::Polytope) {
+                    Algorithm::Polytope => Ok(self.with_median(v, PolytopeMedian::default(), terminals, &geo)),
+                    a => a.unsupported()
+                }
+            }
+            Space::Polytope(ref vertices) => {
+                if vertices[0].len() != dim {
+                    return Err(format!("The polytope has {} coordinates but the points have {}",
+                        vertices[0].len(), dim));
+                }
+                let geo = match PolytopeSpace::try_from_vertices(vertices.iter()
+                    .map(|v| PointNd::from_coords(v).unwrap())
+                    .collect()) {
+                    Some(geo) => geo,
+                    None => return Err(format!("The vertices of the polytope do not span {} dimensions", dim))
+                };
+                match self.algorithm.unwrap_or(Algorithm::Polytope) {
+                    Algorithm::Polytope => Ok(self.with_median(v, PolytopeMedian::default(), terminals, &geo)),
+                    a => a.unsupported()
+                }
+            }
+        }
+    }
+
+    fn with_median<V, P, M, G>(&self, v: V, median: G, terminals: Vec<P>, geo: &M) -> V::Output
+        where V: Solve, P: Point + Send + Sync, P::R: Send + Sync, M: MinkowskiSpace<P> + Send + Sync,
+              G: GeoMedian<P, M> + Clone + Send {
+
+        self.with_rmt(v, GeoMedianIter::new(P::R::from(self.cutoff), median), terminals, geo)
+    }
+
+    fn with_rmt<V, P, M, K>(&self, v: V, rmt: K, terminals: Vec<P>, geo: &M) -> V::Output
+        where V: Solve, P: Point + Send + Sync, P::R: Send + Sync, M: MinkowskiSpace<P> + Send + Sync,
+              K: RMT<P, M> + Clone + Send {
+
+        match self.sorter {
+            Sorter::FurthestSite => self.with_bound(v, rmt,
+                GPEnumeration::default_with_sorter(FurthestSiteOrdering)
+                    .order(self.order).bsd_pruning(self.bsd).ss_pruning(self.ss).mst(self.mst),
+                terminals, geo),
+            Sorter::None => self.with_bound(v, rmt,
+                GPEnumeration::default_with_sorter(NoOrdering)
+                    .order(self.order).bsd_pruning(self.bsd).ss_pruning(self.ss).mst(self.mst),
+                terminals, geo)
+        }
+    }
+
+    fn with_bound<V, P, M, K, S>(&self, v: V, rmt: K, enumerator: GPEnumeration<P, S>,
+                                 terminals: Vec<P>, geo: &M) -> V::Output
+        where V: Solve, P: Point + Send + Sync, P::R: Send + Sync, M: MinkowskiSpace<P> + Send + Sync,
+              K: RMT<P, M> + Clone + Send, S: TerminalSorter + Clone + Send {
+
+        match self.bound {
+            Bound::Line => v.solve(rmt, enumerator, LineTree::default(), terminals, geo),
+            Bound::Mst => v.solve(rmt, enumerator, MSTBoundWith::new(self.mst), terminals, geo)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minkowski_steiner::algorithms::steinerbnb::{SteinerBnB};
+
+    /// Solves with a plain `SteinerBnB` and returns the tree length.
+    struct Length;
+
+    impl Solve for Length {
+        type Output = f64;
+
+        fn solve<P, M, K, S, U>(self, rmt: K, enumerator: GPEnumeration<P, S>,
+                                upperbound: U, terminals: Vec<P>, geo: &M) -> Self::Output
+            where P: Point + Send + Sync, P::R: Send + Sync,
+                  M: MinkowskiSpace<P> + Send + Sync,
+                  K: RMT<P, M> + Clone + Send,
+                  S: TerminalSorter + Clone + Send,
+                  U: UpperBound<P, M> {
+
+            SteinerBnB::new(rmt, enumerator, upperbound)
+                .find(terminals, geo).len(geo).into()
+        }
+    }
+
+    fn config(args: &str) -> Result<SolverConfig, String> {
+        let mut config = SolverConfig::new();
+        let mut args = args.split_whitespace().map(|s| s.to_string());
+        while let Some(arg) = args.next() {
+            assert!(try!(config.parse_option(&arg, &mut args)), "{} is not a solver option", arg);
+        }
+        Ok(config)
+    }
+
+    fn square() -> Vec<Pt> {
+        [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]].iter()
+            .map(|c| PointNd::from_coords(c).unwrap()).collect()
+    }
+
+    fn length(args: &str) -> Result<f64, String> {
+        config(args).unwrap().solve(Length, square())
+    }
+
+    #[test]
+    fn options_are_aligned() {
+        for line in SOLVER_OPTIONS.lines() {
+            assert!(line.starts_with("  "), "'{}' is not indented", line);
+            assert!(line.len() > 29 && line[..29].ends_with(' ') && !line[29..].starts_with(' '),
+                "'{}' is not aligned", line);
+        }
+    }
+
+    #[test]
+    fn parses_spaces() {
+        assert_eq!(config("-s l1").unwrap().space, Space::L1);
+        assert_eq!(config("--space LINF").unwrap().space, Space::LInfinity);
+        assert_eq!(config("-s lambda:4").unwrap().space, Space::Lambda(4));
+        assert_eq!(config("-s polytope:1,0;0.5,2").unwrap().space,
+            Space::Polytope(vec![vec![1.0, 0.0], vec![0.5, 2.0]]));
+        assert!(config("-s lambda:1").is_err());
+        assert!(config("-s lambda:x").is_err());
+        assert!(config("-s polytope:1,0;1").is_err());
+        assert!(config("-s l2").is_err());
+        assert_eq!(config("-a hanan").unwrap().algorithm, Some(Algorithm::Hanan));
+        assert!(config("-a simplex").is_err());
+    }
+
+    #[test]
+    fn solves_in_every_space() {
+        let esmt = 1.0 + 3f64.sqrt();
+        assert!((length("").unwrap() - esmt).abs() < 1e-4);
+        assert_eq!(length("-s l1").unwrap(), 3.0);
+        assert_eq!(length("-s l1 -a coordinate").unwrap(), 3.0);
+        assert!((length("-s linf").unwrap() - 2.0).abs() < 1e-9);
+        assert!((length("-s lambda:2").unwrap() - 3.0).abs() < 1e-9);
+        // The diamond spanned by the unit vectors is the L1 ball
+        assert!((length("-s polytope:1,0;0,1").unwrap() - 3.0).abs() < 1e-9);
+        let hexagonal = length("-s lambda:3").unwrap();
+        assert!(hexagonal > esmt && hexagonal < 3.0);
+    }
+
+    #[test]
+    fn rejects_algorithms_outside_their_spaces() {
+        assert!(length("-s l1 -a uteshev").unwrap_err().contains("euclidean"));
+        assert!(length("-a hanan").unwrap_err().contains("l1"));
+        assert!(length("-s linf -a polytope").is_err());
+        assert!(length("-s lambda:3 -a chebyshev").is_err());
+        assert!(length("-s polytope:1,0,0").is_err());
+        assert!(length("-s polytope:1,1;-2,-2").unwrap_err().contains("span"));
+        assert!(config("-s lambda:3").unwrap().solve(Length, vec![PointNd::from_coords(&[0.0, 0.0, 0.0]).unwrap()])
+            .is_err());
+    }
+}
@@ -0,0 +1,331 @@
+//! Benchmark harness: runs a set of solver configurations on a set of
+//! instances and writes one CSV row per run with the statistics of the
+//! branch and bound, the enumeration and the RMT algorithm.
+
+extern crate minkowski_steiner;
+
+mod common;
+
+use std::env;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::process;
+use std::time::{Instant};
+
+use minkowski_steiner::traits::*;
+use minkowski_steiner::algorithms::steinerbnb::{SteinerBnB};
+use minkowski_steiner::stats::{Stat, Stats};
+use minkowski_steiner::enumerator::{GPEnumeration};
+use minkowski_steiner::preprocess::{Preprocess};
+use minkowski_steiner::generators::{Generator};
+use minkowski_steiner::io::{points};
+
+use common::{Pt, Solve, SolverConfig, SOLVER_OPTIONS, parse_number};
+
+const USAGE: &'static str = "\
+Usage: smt-bench [OPTIONS] [POINT FILES...]
+
+Runs every configuration on every instance and writes a CSV table with one
+row per run. Instances are point files, as read by smt, and generated
+instances.
+
+Options:
+  -c, --config <NAME=OPTIONS>  A solver configuration given by smt's solver
+                               options, e.g. 'weiszfeld=-a weiszfeld --no-ss'.
+                               May be repeated; without it the default
+                               configuration is run
+  -g, --generate <KIND>        Generated instances: uniform:<n>,
+                               clusters:<n>:<clusters>:<sigma>, sphere:<n>,
+                               lattice:<n>:<perturbation>, collinear:<n> or
+                               duplicates:<n>:<duplicates>. May be repeated
+      --dim <D>                Dimension of generated instances (default 2)
+      --count <C>              Instances per --generate (default 1)
+      --seed <S>               Seed of the first generated instance, the
+                               following use S+1, S+2, ... (default 1)
+  -r, --repeat <R>             Runs per instance and configuration (default 1)
+  -o, --output <FILE>          Write the table to FILE instead of standard
+                               output
+  -h, --help                   Print this help
+
+Solver options of a configuration:";
+
+struct Instance {
+    name: String,
+    terminals: Vec<Pt>
+}
+
+struct Options {
+    configs: Vec<(String, SolverConfig)>,
+    files: Vec<String>,
+    generators: Vec<String>,
+    dim: usize,
+    count: usize,
+    seed: u64,
+    repeat: usize,
+    output: Option<String>
+}
+
+fn parse_config(spec: &str) -> Result<(String, SolverConfig), String> {
+    let mut parts = spec.splitn(2, '=');
+    let name = parts.next().unwrap().trim().to_string();
+    let mut config = SolverConfig::new();
+    let mut args = parts.next().unwrap_or("").split_whitespace().map(|s| s.to_string());
+    while let Some(arg) = args.next() {
+        if !try!(config.parse_option(&arg, &mut args)) {
+            return Err(format!("'{}' is not a solver option in configuration '{}'", arg, name));
+        }
+    }
+    Ok((name, config))
+}
+
+fn generate(spec: &str, gen: &mut Generator) -> Result<Vec<Pt>, String> {
+    let parts: Vec<&str> = spec.split(':').collect();
+    let int = |i: usize| -> Result<usize, String> {
+        parts.get(i).and_then(|s| s.parse().ok())
+            .ok_or_else(|| format!("invalid instance kind '{}'", spec))
+    };
+    let real = |i: usize| -> Result<f64, String> {
+        parts.get(i).ok_or_else(|| format!("invalid instance kind '{}'", spec))
+            .and_then(|s| parse_number(spec, s))
+    };
+
+    match (parts[0], parts.len()) {
+        ("uniform", 2) => Ok(gen.uniform(try!(int(1)))),
+        ("clusters", 4) => Ok(gen.gaussian_clusters(try!(int(1)), try!(int(2)).max(1), try!(real(3)))),
+        ("sphere", 2) => Ok(gen.sphere(try!(int(1)), 1.0)),
+        ("lattice", 3) => Ok(gen.lattice(try!(int(1)), try!(real(2)))),
+        ("collinear", 2) => Ok(gen.collinear(try!(int(1)))),
+        ("duplicates", 3) => {
+            let (n, d) = (try!(int(1)), try!(int(2)));
+            if d >= n {
+                return Err(format!("'{}' needs fewer duplicates than points", spec));
+            }
+            Ok(gen.duplicates(n, d))
+        }
+        _ => Err(format!("invalid instance kind '{}'", spec))
+    }
+}
+
+fn parse_args<I: Iterator<Item=String>>(mut args: I) -> Result<Options, String> {
+    let mut opts = Options {
+        configs: vec![],
+        files: vec![],
+        generators: vec![],
+        dim: 2,
+        count: 1,
+        seed: 1,
+        repeat: 1,
+        output: None
+    };
+
+    while let Some(arg) = args.next() {
+        if !arg.starts_with('-') {
+            opts.files.push(arg);
+            continue;
+        }
+        if arg == "-h" || arg == "--help" {
+            println!("{}\n{}", USAGE, SOLVER_OPTIONS);
+            process::exit(0);
+        }
+
+        let value = try!(args.next().ok_or_else(|| format!("{} expects a value", arg)));
+        let positive = |v: &str| -> Result<usize, String> {
+            match v.parse() {
+                Ok(n) if n > 0 => Ok(n),
+                _ => Err(format!("{} expects a positive integer, found '{}'", arg, v))
+            }
+        };
+        match arg.as_ref() {
+            "-c" | "--config" => opts.configs.push(try!(parse_config(&value))),
+            "-g" | "--generate" => opts.generators.push(value),
+            "--dim" => opts.dim = try!(positive(&value)),
+            "--count" => opts.count = try!(positive(&value)),
+            "--seed" => opts.seed = try!(value.parse()
+                .map_err(|_| format!("--seed expects an integer, found '{}'", value))),
+            "-r" | "--repeat" => opts.repeat = try!(positive(&value)),
+            "-o" | "--output" => opts.output = Some(value),
+            _ => return Err(format!("unknown option '{}'", arg))
+        }
+    }
+
+    if opts.configs.is_empty() {
+        opts.configs.push(("default".to_string(), SolverConfig::new()));
+    }
+    if opts.files.is_empty() && opts.generators.is_empty() {
+        return Err("no instances given".to_string());
+    }
+    Ok(opts)
+}
+
+fn instances(opts: &Options) -> Result<Vec<Instance>, String> {
+    let mut res = vec![];
+    for f in opts.files.iter() {
+        let terminals = try!(points::open_points(f).map_err(|e| format!("Could not read '{}': {}", f, e)));
+        res.push(Instance { name: f.clone(), terminals: terminals });
+    }
+    for g in opts.generators.iter() {
+        for i in 0..opts.count {
+            let seed = opts.seed.wrapping_add(i as u64);
+            let mut gen = Generator::new(seed, opts.dim);
+            res.push(Instance {
+                name: format!("{}:dim={}:seed={}", g, opts.dim, seed),
+                terminals: try!(generate(g, &mut gen))
+            });
+        }
+    }
+
+    for i in res.iter() {
+        if i.terminals.len() < 3 {
+            return Err(format!("Instance '{}' has fewer than 3 points", i.name));
+        }
+    }
+    Ok(res)
+}
+
+/// Solves an instance with a fresh `SteinerBnB` and reports the statistics
+/// of the run. Errors and panics of the solver are reported in the status.
+/// The per-depth counts are padded with zeros to `depths`, so every run has
+/// the same columns.
+struct Measure<'a> {
+    config: &'a SolverConfig,
+    depths: usize
+}
+
+impl<'a> Solve for Measure<'a> {
+    type Output = Stats;
+
+    fn solve<P, M, K, S, U>(self, rmt: K, enumerator: GPEnumeration<P, S>,
+                            upperbound: U, terminals: Vec<P>, geo: &M) -> Self::Output
+        where P: Point + Send + Sync, P::R: Send + Sync,
+              M: MinkowskiSpace<P> + Send + Sync,
+              K: RMT<P, M> + Clone + Send,
+              S: TerminalSorter + Clone + Send,
+              U: UpperBound<P, M> {
+
+        let pre = self.config.preprocess;
+        let mut smt = Preprocess::new(SteinerBnB::new(rmt, enumerator, upperbound)
+            .degeneracy_eps(self.config.degeneracy))
+            .merge_duplicates(pre).normalize(pre);
+
+        let start = Instant::now();
+        let res = panic::catch_unwind(AssertUnwindSafe(|| smt.try_find(terminals, geo)));
+        let time = Instant::now() - start;
+
+        let (status, length) = match res {
+            Ok(Ok(tree)) => ("ok".to_string(), tree.len(geo).into()),
+            Ok(Err(e)) => (format!("error: {}", e), ::std::f64::NAN),
+            Err(e) => {
+                let message = e.downcast_ref::<&str>().map(|s| s.to_string())
+                    .or_else(|| e.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "unknown".to_string());
+                (format!("panic: {}", message), ::std::f64::NAN)
+            }
+        };
+
+        let bnb = smt.smt_alg();
+        let (best_updates, bound_pruned) = (bnb.data().best_updates(), bnb.data().pruned());
+        let (nodes, pruned, bsd_pruned, ss_pruned) = {
+            let data = bnb.enumerator().data();
+            (data.nodes(), data.pruned(), data.bsd_pruned().to_vec(), data.ss_pruned().to_vec())
+        };
+        // Not every RMT algorithm iterates or uses geometric medians, those
+        // columns are zero then
+        let rmt = bnb.rmt_alg();
+        let report = rmt.report();
+        let rmt_topologies = rmt.data().nodes();
+        let rmt_iterations = match report.get("iterations") {
+            Some(&Stat::Count(n)) => n,
+            _ => 0
+        };
+        let median_time = match report.path(&["median", "time"]) {
+            Some(&Stat::Seconds(t)) => t,
+            _ => 0.0
+        };
+
+        let pad = |counts: &[usize]| -> Vec<usize> {
+            (0..self.depths).map(|d| counts.get(d).cloned().unwrap_or(0)).collect()
+        };
+
+        Stats::new()
+            .text("status", &status)
+            .seconds("time", &time)
+            .count("nodes", nodes as u64)
+            .count("pruned", pruned as u64)
+            .count("bound_pruned", bound_pruned)
+            .count("best_updates", best_updates)
+            .count("rmt_topologies", rmt_topologies as u64)
+            .count("rmt_iterations", rmt_iterations)
+            .with("median_time", Stat::Seconds(median_time))
+            .value("length", length)
+            .counts("bsd_pruned", &pad(&bsd_pruned))
+            .counts("ss_pruned", &pad(&ss_pruned))
+    }
+}
+
+/// Only builds the solver, to check a configuration before any run.
+struct Check;
+
+impl Solve for Check {
+    type Output = ();
+
+    fn solve<P, M, K, S, U>(self, _: K, _: GPEnumeration<P, S>,
+                            _: U, _: Vec<P>, _: &M) -> Self::Output
+        where P: Point + Send + Sync, P::R: Send + Sync,
+              M: MinkowskiSpace<P> + Send + Sync,
+              K: RMT<P, M> + Clone + Send,
+              S: TerminalSorter + Clone + Send,
+              U: UpperBound<P, M> { }
+}
+
+fn run() -> Result<(), String> {
+    let opts = try!(parse_args(env::args().skip(1)));
+    let instances = try!(instances(&opts));
+    for &(ref name, ref config) in opts.configs.iter() {
+        for instance in instances.iter() {
+            try!(config.solve(Check, instance.terminals.clone())
+                .map_err(|e| format!("Configuration '{}' on '{}': {}", name, instance.name, e)));
+        }
+    }
+    let depths = instances.iter().map(|i| i.terminals.len() - 3).max().unwrap_or(0);
+
+    let out: Box<dyn Write> = match opts.output {
+        Some(ref path) => Box::new(try!(File::create(path)
+            .map_err(|e| format!("Could not create '{}': {}", path, e)))),
+        None => Box::new(io::stdout())
+    };
+    let mut out = BufWriter::new(out);
+    let io_err = |e: io::Error| format!("I/O error: {}", e);
+
+    let mut header = None;
+    for &(ref name, ref config) in opts.configs.iter() {
+        for instance in instances.iter() {
+            for r in 0..opts.repeat {
+                let m = try!(config.solve(Measure { config: config, depths: depths },
+                                          instance.terminals.clone()));
+                let row = m.entries().iter().fold(Stats::new()
+                    .text("config", name)
+                    .text("instance", &instance.name)
+                    .count("terminals", instance.terminals.len() as u64)
+                    .count("run", r as u64 + 1), |row, &(ref k, ref s)| row.with(k, s.clone()));
+
+                // All rows have the same columns, the header is written with the first
+                if header.is_none() {
+                    let columns: Vec<String> = row.columns().into_iter().map(|(k, _)| k).collect();
+                    try!(Stats::write_csv_header(&mut out, &columns).map_err(io_err));
+                    header = Some(columns);
+                }
+                try!(row.write_csv_row(&mut out, header.as_ref().unwrap()).map_err(io_err));
+                try!(out.flush().map_err(io_err));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run() {
+        let _ = writeln!(io::stderr(), "smt-bench: {}", e);
+        process::exit(1);
+    }
+}
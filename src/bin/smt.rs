@@ -0,0 +1,311 @@
+//! Command line front end of `SteinerBnB`: reads a point file, finds a
+//! Steiner minimal tree in the chosen space and prints it together with the
+//! statistics of the algorithms.
+
+extern crate minkowski_steiner;
+
+mod common;
+
+use std::env;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::process;
+use std::time::Duration;
+
+use minkowski_steiner::traits::*;
+use minkowski_steiner::algorithms::steinerbnb::{SteinerBnB, ParallelSteinerBnB};
+use minkowski_steiner::enumerator::{GPEnumeration};
+use minkowski_steiner::preprocess::{Preprocess};
+use minkowski_steiner::stats::{Stats};
+use minkowski_steiner::io::{json, stp, dot, points};
+
+use common::{Pt, Solve, SolverConfig, SOLVER_OPTIONS};
+
+const USAGE: &'static str = "\
+Usage: smt [OPTIONS] <POINT FILE>
+
+Finds a Steiner minimal tree of the points in the file, which lists one point
+per line as whitespace separated coordinates, or of standard input if the
+file is -. Lines starting with # are ignored.
+
+Options:";
+
+const OUTPUT_OPTIONS: &'static str = "  -t, --threads <N>          Solve in parallel with N threads (default 1)
+      --time-limit <SECS>    Stop after SECS seconds with the best tree found
+                             so far
+      --node-limit <N>       Stop after N topologies with the best tree found
+                             so far
+      --memory-limit <MIB>   Stop when the stored topologies take more than
+                             MIB mebibytes, with the best tree found so far
+  -f, --format <FORMAT>      Output of the tree: text (default), json, stp or
+                             dot. For formats other than text the statistics
+                             are written to standard error
+      --stats <FORMAT>       Statistics as text (default), json or csv
+  -o, --output <FILE>        Write to FILE instead of standard output
+  -h, --help                 Print this help";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Text,
+    Json,
+    Stp,
+    Dot
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StatsFormat {
+    Text,
+    Json,
+    Csv
+}
+
+#[derive(Debug, Clone)]
+struct Options {
+    input: String,
+    output: Option<String>,
+    solver: SolverConfig,
+    threads: usize,
+    time_limit: Option<Duration>,
+    node_limit: Option<u64>,
+    memory_limit: Option<usize>,
+    format: Format,
+    stats: StatsFormat
+}
+
+/// Errors that end the program, with the exit code they lead to.
+enum Failure {
+    Usage(String),
+    Run(String)
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Failure::Usage(ref m) => write!(f, "{}\n\nTry 'smt --help' for more information.", m),
+            Failure::Run(ref m) => write!(f, "{}", m)
+        }
+    }
+}
+
+impl From<io::Error> for Failure {
+    fn from(e: io::Error) -> Self {
+        Failure::Run(format!("I/O error: {}", e))
+    }
+}
+
+fn usage<T>(message: String) -> Result<T, Failure> {
+    Err(Failure::Usage(message))
+}
+
+fn parse_args<I: Iterator<Item=String>>(mut args: I) -> Result<Options, Failure> {
+    let mut opts = Options {
+        input: String::new(),
+        output: None,
+        solver: SolverConfig::new(),
+        threads: 1,
+        time_limit: None,
+        node_limit: None,
+        memory_limit: None,
+        format: Format::Text,
+        stats: StatsFormat::Text
+    };
+    let mut input = None;
+
+    while let Some(arg) = args.next() {
+        if !arg.starts_with('-') || arg == "-" {
+            if input.is_some() {
+                return usage(format!("unexpected argument '{}'", arg));
+            }
+            input = Some(arg);
+            continue;
+        }
+
+        if arg == "-h" || arg == "--help" {
+            println!("{}\n{}\n{}", USAGE, SOLVER_OPTIONS, OUTPUT_OPTIONS);
+            process::exit(0);
+        }
+        if try!(opts.solver.parse_option(&arg, &mut args).map_err(Failure::Usage)) {
+            continue;
+        }
+
+        let value = match args.next() {
+            Some(v) => v,
+            None => return usage(format!("{} expects a value", arg))
+        };
+        match arg.as_ref() {
+            "-t" | "--threads" => opts.threads = match value.parse() {
+                Ok(n) if n > 0 => n,
+                _ => return usage(format!("{} expects a positive integer, found '{}'", arg, value))
+            },
+            "--time-limit" => opts.time_limit = match value.parse::<f64>() {
+                Ok(t) if t >= 0.0 && t.is_finite() =>
+                    Some(Duration::new(t as u64, (t.fract() * 1e9) as u32)),
+                _ => return usage(format!("{} expects a number of seconds, found '{}'", arg, value))
+            },
+            "--node-limit" => opts.node_limit = match value.parse() {
+                Ok(n) => Some(n),
+                _ => return usage(format!("{} expects an integer, found '{}'", arg, value))
+            },
+            "--memory-limit" => opts.memory_limit = match value.parse::<usize>() {
+                Ok(n) => Some(n.saturating_mul(1 << 20)),
+                _ => return usage(format!("{} expects an integer, found '{}'", arg, value))
+            },
+            "-f" | "--format" => opts.format = match value.to_lowercase().as_ref() {
+                "text" => Format::Text,
+                "json" => Format::Json,
+                "stp" => Format::Stp,
+                "dot" => Format::Dot,
+                _ => return usage(format!("unknown format '{}'", value))
+            },
+            "--stats" => opts.stats = match value.to_lowercase().as_ref() {
+                "text" => StatsFormat::Text,
+                "json" => StatsFormat::Json,
+                "csv" => StatsFormat::Csv,
+                _ => return usage(format!("unknown statistics format '{}'", value))
+            },
+            "-o" | "--output" => opts.output = Some(value),
+            _ => return usage(format!("unknown option '{}'", arg))
+        }
+    }
+
+    opts.input = match input {
+        Some(i) => i,
+        None => return usage("no point file given".to_string())
+    };
+    Ok(opts)
+}
+
+fn write_stats<P, S, M, W>(w: &mut W, smt: &S, format: StatsFormat) -> io::Result<()>
+    where P: Point, S: SMT<P, M>, M: MinkowskiSpace<P>, W: Write {
+
+    match format {
+        StatsFormat::Text => smt.print_data(w),
+        StatsFormat::Json => smt.report().write_json(w),
+        StatsFormat::Csv => Stats::write_csv(w, &[smt.report()])
+    }
+}
+
+/// Runs `smt` and writes the tree and the statistics.
+fn report<P, S, M>(mut smt: S, terminals: Vec<P>, geo: &M, opts: &Options) -> Result<(), Failure>
+    where P: Point, S: SMT<P, M>, M: MinkowskiSpace<P> {
+
+    let tree = try!(smt.try_find(terminals, geo).map_err(|e| Failure::Run(e.to_string())));
+
+    let out: Box<dyn Write> = match opts.output {
+        Some(ref path) => Box::new(try!(File::create(path)
+            .map_err(|e| Failure::Run(format!("Could not create '{}': {}", path, e))))),
+        None => Box::new(io::stdout())
+    };
+    let mut out = BufWriter::new(out);
+
+    match opts.format {
+        Format::Text => {
+            try!(writeln!(out, "{}.\n", smt));
+            try!(writeln!(out, "Space: {}", geo));
+            try!(writeln!(out, "{}", tree));
+            try!(writeln!(out, "Length: {}\n", tree.len(geo)));
+            try!(write_stats(&mut out, &smt, opts.stats));
+        }
+        format => {
+            match format {
+                Format::Json => try!(json::write_tree(&mut out, &tree, geo)),
+                Format::Dot => try!(dot::write_tree(&mut out, &tree, geo, None)),
+                _ => try!(stp::write_tree(&mut out, &tree, geo, None))
+            }
+
+            let stderr = io::stderr();
+            let mut err = stderr.lock();
+            try!(writeln!(err, "{}.\n", smt));
+            try!(writeln!(err, "Length: {}\n", tree.len(geo)));
+            try!(write_stats(&mut err, &smt, opts.stats));
+        }
+    }
+
+    try!(out.flush());
+    Ok(())
+}
+
+impl<'a> Solve for &'a Options {
+    type Output = Result<(), Failure>;
+
+    fn solve<P, M, K, S, U>(self, rmt: K, enumerator: GPEnumeration<P, S>,
+                            upperbound: U, terminals: Vec<P>, geo: &M) -> Self::Output
+        where P: Point + Send + Sync, P::R: Send + Sync,
+              M: MinkowskiSpace<P> + Send + Sync,
+              K: RMT<P, M> + Clone + Send,
+              S: TerminalSorter + Clone + Send,
+              U: UpperBound<P, M> {
+
+        let pre = self.solver.preprocess;
+        if self.threads > 1 {
+            let mut bnb = ParallelSteinerBnB::new(rmt, enumerator, upperbound)
+                .threads(self.threads)
+                .degeneracy_eps(self.solver.degeneracy);
+            if let Some(limit) = self.time_limit {
+                bnb = bnb.time_limit(limit);
+            }
+            if let Some(limit) = self.node_limit {
+                bnb = bnb.node_limit(limit);
+            }
+            if let Some(limit) = self.memory_limit {
+                bnb = bnb.memory_limit(limit);
+            }
+            report(Preprocess::new(bnb).merge_duplicates(pre).normalize(pre), terminals, geo, self)
+        } else {
+            let mut bnb = SteinerBnB::new(rmt, enumerator, upperbound)
+                .degeneracy_eps(self.solver.degeneracy);
+            if let Some(limit) = self.time_limit {
+                bnb = bnb.time_limit(limit);
+            }
+            if let Some(limit) = self.node_limit {
+                bnb = bnb.node_limit(limit);
+            }
+            if let Some(limit) = self.memory_limit {
+                bnb = bnb.memory_limit(limit);
+            }
+            report(Preprocess::new(bnb).merge_duplicates(pre).normalize(pre), terminals, geo, self)
+        }
+    }
+}
+
+fn run() -> Result<(), Failure> {
+    let opts = try!(parse_args(env::args().skip(1)));
+
+    let terminals: Vec<Pt> = try!(if opts.input == "-" {
+        let stdin = io::stdin();
+        let res = points::read_points(stdin.lock());
+        res
+    } else {
+        points::open_points(&opts.input)
+    }.map_err(|e| Failure::Run(format!("Could not read '{}': {}", opts.input, e))));
+
+    match opts.solver.solve(&opts, terminals) {
+        Ok(res) => res,
+        Err(e) => Err(Failure::Run(e))
+    }
+}
+
+fn main() {
+    if let Err(e) = run() {
+        let _ = writeln!(io::stderr(), "smt: {}", e);
+        process::exit(match e {
+            Failure::Usage(_) => 2,
+            Failure::Run(_) => 1
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn options_are_aligned() {
+        for line in OUTPUT_OPTIONS.lines() {
+            assert!(line.starts_with("  "), "'{}' is not indented", line);
+            assert!(line.len() > 29 && line[..29].ends_with(' ') && !line[29..].starts_with(' '),
+                "'{}' is not aligned", line);
+        }
+    }
+}
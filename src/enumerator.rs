@@ -1,8 +1,12 @@
 use traits::*;
+use stats::{Stats};
 use steinertree::*;
 use algorithms::mst::*;
+use observer::{Observer, NoObserver, PruneReason};
 
 use std::marker::PhantomData;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
 use std::fmt;
 use std::iter::*;
 use std::time::{Duration, Instant};
@@ -13,10 +17,59 @@ use std::io::{self, BufWriter, Write};
 enum GPState {
     Done,
     Start,
+    /// Like `Start`, but the first topology was already enumerated by the
+    /// enumerator that this one was split off from.
+    Split,
     Running
 }
 
-pub struct GPEnumeration<P: Point, S: TerminalSorter> {
+/// The order in which `GPEnumeration` visits the topologies.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum GPOrder {
+    DepthFirst,
+    /// Visits the partial topology with the smallest parent length first.
+    /// The parent length is the length of the parent topology as it was
+    /// left when the enumerator was advanced, i.e. its relatively minimal
+    /// length when used by `SteinerBnB`. At most the given number of
+    /// topologies are kept in the frontier, when it is full the children of
+    /// the current topology are visited depth-first instead. The depth-first
+    /// dive keeps one topology per level, so at most the frontier size plus
+    /// the number of terminals topologies are stored.
+    BestFirst(usize)
+}
+
+/// A topology whose children are visited depth-first, from the edge `next`
+/// on.
+struct Dive<P> {
+    tree: SteinerTree<P>,
+    edges: Vec<(usize, usize)>,
+    next: usize
+}
+
+struct Frontier<P: Point> {
+    key: P::R,
+    tree: SteinerTree<P>
+}
+
+impl<P: Point> PartialEq for Frontier<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+impl<P: Point> Eq for Frontier<P> {}
+impl<P: Point> PartialOrd for Frontier<P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<P: Point> Ord for Frontier<P> {
+    // Reversed, so the max-heap pops the smallest key first.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.key.partial_cmp(&self.key).unwrap_or(Ordering::Equal)
+    }
+}
+
+pub struct GPEnumeration<P: Point, S: TerminalSorter, O = NoObserver> {
     tree: SteinerTree<P>,
     edges: Vec<(usize, usize)>,
     top: Vec<usize>,
@@ -26,14 +79,22 @@ pub struct GPEnumeration<P: Point, S: TerminalSorter> {
     t_len: usize,
     sorter: S,
 
+    order: GPOrder,
+    sorted: Vec<P>,
+    frontier: BinaryHeap<Frontier<P>>,
+    dive: Vec<Dive<P>>,
+
     e_bsd: bool,
     e_ss: bool,
+    mst: MSTAlgorithm,
+    pruned: bool,
     state: GPState,
     data: GPEnumerationData,
+    observer: O,
     _m: PhantomData<P>
 }
 
-impl<P: Point, S: TerminalSorter> fmt::Debug for GPEnumeration<P, S> {
+impl<P: Point, S: TerminalSorter, O> fmt::Debug for GPEnumeration<P, S, O> {
     fn fmt(&self, _: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         Ok(())
     }
@@ -46,6 +107,7 @@ impl<P: Point> Default for GPEnumeration<P, FurthestSiteOrdering> {
 }
 
 
+#[derive(Clone)]
 pub struct NoOrdering;
 impl TerminalSorter for NoOrdering {
     fn sort<P, M>(&mut self, _: &mut[P], _: &M)
@@ -62,11 +124,16 @@ impl fmt::Display for NoOrdering {
 }
 
 
+#[derive(Clone)]
 pub struct FurthestSiteOrdering;
 impl TerminalSorter for FurthestSiteOrdering {
     fn sort<P, M>(&mut self, t: &mut[P], geo: &M)
         where P: Point, M: MinkowskiSpace<P> {
 
+        if t.len() < 3 {
+            return;
+        }
+
         let mut sorting = [0; 3];
 
         // Three with max sum of distances
@@ -126,6 +193,8 @@ impl<P: Point, S: TerminalSorter> GPEnumeration<P, S> {
         GPEnumeration {
             e_bsd: e_bsd,
             e_ss: e_ss,
+            mst: MSTAlgorithm::default(),
+            pruned: false,
             state: GPState::Done,
             tree: SteinerTree::new(&[], &[], &[]),
             edges: Vec::new(),
@@ -135,7 +204,12 @@ impl<P: Point, S: TerminalSorter> GPEnumeration<P, S> {
             bsd: Vec::new(),
             ss: Vec::new(),
             t_len: 0,
+            order: GPOrder::DepthFirst,
+            sorted: Vec::new(),
+            frontier: BinaryHeap::new(),
+            dive: Vec::new(),
             data: GPEnumerationData::new(0),
+            observer: NoObserver,
             _m: PhantomData
         }
     }
@@ -143,6 +217,94 @@ impl<P: Point, S: TerminalSorter> GPEnumeration<P, S> {
     pub fn default_with_sorter(sorter: S) -> Self {
         GPEnumeration::new(true, true, sorter)
     }
+}
+
+impl<P: Point, S: TerminalSorter, O: Observer<P>> GPEnumeration<P, S, O> {
+    /// Reports the enumerated and pruned topologies to `observer`.
+    pub fn observer<Q: Observer<P>>(self, observer: Q) -> GPEnumeration<P, S, Q> {
+        GPEnumeration {
+            tree: self.tree,
+            edges: self.edges,
+            top: self.top,
+            t: self.t,
+            bsd: self.bsd,
+            ss: self.ss,
+            t_len: self.t_len,
+            sorter: self.sorter,
+            order: self.order,
+            sorted: self.sorted,
+            frontier: self.frontier,
+            dive: self.dive,
+            e_bsd: self.e_bsd,
+            e_ss: self.e_ss,
+            mst: self.mst,
+            pruned: self.pruned,
+            state: self.state,
+            data: self.data,
+            observer: observer,
+            _m: PhantomData
+        }
+    }
+
+    pub fn order(mut self, order: GPOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Enables or disables bottleneck Steiner distance pruning.
+    pub fn bsd_pruning(mut self, enabled: bool) -> Self {
+        self.e_bsd = enabled;
+        self
+    }
+
+    /// Enables or disables smallest spheres pruning.
+    pub fn ss_pruning(mut self, enabled: bool) -> Self {
+        self.e_ss = enabled;
+        self
+    }
+
+    /// The MST algorithm of the bottleneck Steiner distances, `Kruskal` by
+    /// default.
+    pub fn mst(mut self, mst: MSTAlgorithm) -> Self {
+        self.mst = mst;
+        self
+    }
+
+    /// Creates an enumerator of the topologies below the current topology,
+    /// which is the first topology it returns, without counting or reporting
+    /// it again. It needs no initialization
+    /// and shares the terminal ordering and the pruning distances of `self`.
+    pub fn subproblem(&self) -> Self
+        where S: Clone, O: Clone {
+
+        GPEnumeration {
+            e_bsd: self.e_bsd,
+            e_ss: self.e_ss,
+            mst: self.mst,
+            pruned: false,
+            state: GPState::Split,
+            tree: self.tree.clone(),
+            edges: self.edges.clone(),
+            top: vec![0],
+            t: self.t.clone(),
+            sorter: self.sorter.clone(),
+            bsd: self.bsd.clone(),
+            ss: self.ss.clone(),
+            t_len: self.t_len,
+            order: self.order,
+            sorted: self.t.clone(),
+            frontier: BinaryHeap::new(),
+            dive: Vec::new(),
+            data: GPEnumerationData::new(self.t_len),
+            observer: self.observer.clone(),
+            _m: PhantomData
+        }
+    }
+
+    fn enumerated(&self) {
+        let depth = self.tree.terminals().len().saturating_sub(3);
+        self.observer.enumerated(depth, &self.tree);
+    }
 
     #[inline]
     fn get_bottleneck(&self, p1: &P, p2: &P) -> P::R {
@@ -157,34 +319,20 @@ impl<P: Point, S: TerminalSorter> GPEnumeration<P, S> {
     fn calc_bsd<M>(&mut self, terms: &[P], geo: &M)
         where M: MinkowskiSpace<P> {
 
-        fn dfs_bottleneck<M, P>(start: &Node<P>, goal: &Node<P>, prev: &Node<P>, b: P::R, geo: &M) -> (bool, P::R)
-            where P: Point, M: MinkowskiSpace<P> {
-
-            for e in start.edges().filter(|e| e.nodes().1.p() != prev.p()) {
-                if e.nodes().1.p() == goal.p() {
-                    return (true, b.max(e.len(geo)));
-                }
-                if let res @ (true, _) = dfs_bottleneck(&e.nodes().1, goal, start, b.max(e.len(geo)), geo) {
-                    return res;
-                }
-            }
-            (false, P::R::zero())
-        }
-
-        let mst = Kruskal::new().find(terms, geo);
-        self.bsd = vec![vec![P::R::zero(); terms.len()]; terms.len()];
-
-        for (n1, n2) in mst.node_pairs() {
-            let (_, b) = dfs_bottleneck(&n1, &n2, &n1, n1.edges().next().unwrap().len(geo), geo);
-            self.bsd[n1.p().id()][n2.p().id()] = b;
-            self.bsd[n2.p().id()][n1.p().id()] = b;
-        }
+        // The ids of the terminals are their indices, which are kept as
+        // node ids by the spanning tree.
+        let mst = self.mst.find(terms, geo);
+        self.bsd = mst.bottleneck_distances(geo);
     }
 
     fn calc_ss<M>(&mut self, terms: &[P], geo: &M)
         where M: MinkowskiSpace<P> {
 
         self.ss = vec![P::R::zero(); terms.len()];
+        if terms.len() < 2 {
+            // A lone terminal has no nearest neighbour and is never pruned
+            return;
+        }
         for t in terms {
             let mut iter = terms.iter().filter(|p| p.id() != t.id());
             let mut d = geo.dist(iter.next().unwrap(), t);
@@ -213,7 +361,12 @@ impl<P: Point, S: TerminalSorter> GPEnumeration<P, S> {
     }
 
     fn backtrack(&mut self) -> bool {
-        if self.t.len() == 0 {
+        if self.t.len() == 0 || self.pruned {
+            self.pruned = false;
+            if self.top.len() == 1 {
+                self.state = GPState::Done;
+                return false;
+            }
             self.pop();
         }
 
@@ -247,6 +400,105 @@ impl<P: Point, S: TerminalSorter> GPEnumeration<P, S> {
     }
 
 
+    fn next_best_first<M>(&mut self, max_frontier: usize, geo: &M) -> bool
+        where M: MinkowskiSpace<P> {
+
+        if self.state == GPState::Done {
+            return false;
+        }
+
+        if !self.pruned && self.t.len() > 0 {
+            self.expand(max_frontier, geo);
+        }
+        self.pruned = false;
+
+        if self.next_dive(geo) {
+            self.data.nodes += 1;
+            return true;
+        }
+
+        match self.frontier.pop() {
+            Some(f) => {
+                let k = self.t_len - f.tree.terminals().len();
+                self.t.clear();
+                self.t.extend_from_slice(&self.sorted[..k]);
+                self.tree = f.tree;
+                self.data.nodes += 1;
+                true
+            },
+            None => {
+                self.state = GPState::Done;
+                false
+            }
+        }
+    }
+
+    /// Moves to the next unpruned child of the deepest topology of the dive.
+    /// Returns false when the dive is empty.
+    fn next_dive<M>(&mut self, geo: &M) -> bool
+        where M: MinkowskiSpace<P> {
+
+        while let Some(mut d) = self.dive.pop() {
+            let (a, b) = d.edges[d.next];
+            d.next += 1;
+            let k = self.t_len - d.tree.terminals().len();
+            self.t.clear();
+            self.t.extend_from_slice(&self.sorted[..k - 1]);
+            let p = self.sorted[k - 1].clone();
+
+            self.tree = if d.next < d.edges.len() {
+                let tree = d.tree.clone();
+                self.dive.push(d);
+                tree
+            } else {
+                d.tree
+            };
+            self.tree.remove_edge((a, b));
+            let ti = self.tree.push_node(p.clone(), &[], true).id();
+            let si = self.tree.push_node(p, &[a, b, ti], false).id();
+
+            if !self.prune_check(ti, si, geo) {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn expand<M>(&mut self, max_frontier: usize, geo: &M)
+        where M: MinkowskiSpace<P> {
+
+        let key = self.tree.len(geo);
+        let edges: Vec<(usize, usize)> = self.tree.edges()
+            .map(|e| (e.nodes().0.id(), e.nodes().1.id()))
+            .collect();
+        let p = self.t.pop().unwrap();
+
+        let mut i = 0;
+        while i < edges.len() && self.frontier.len() < max_frontier {
+            let (a, b) = edges[i];
+            self.tree.remove_edge((a, b));
+            let ti = self.tree.push_node(p.clone(), &[], true).id();
+            let si = self.tree.push_node(p.clone(), &[a, b, ti], false).id();
+
+            if !self.prune_check(ti, si, geo) {
+                self.frontier.push(Frontier { key: key, tree: self.tree.clone() });
+            }
+
+            self.tree.pop_node();
+            self.tree.pop_node();
+            self.tree.add_edge((a, b));
+            i += 1;
+        }
+
+        self.t.push(p);
+        self.data.frontier_peak = self.data.frontier_peak.max(self.frontier.len());
+        if i < edges.len() {
+            // The frontier is full, the other children are visited
+            // depth-first
+            self.dive.push(Dive { tree: self.tree.clone(), edges: edges, next: i });
+        }
+    }
+
     fn prune_paths_check<F>(s: &Node<P>, t: &Node<P>, f: &mut F) -> bool
         where F: FnMut(&P, &P, usize) -> bool {
 
@@ -258,15 +510,15 @@ impl<P: Point, S: TerminalSorter> GPEnumeration<P, S> {
             } else {
                 cur.neighbours()
                    .filter(|n| n.id() != prev.id())
-                   .any(|n| aux(n, cur, p, k + 1, f))
+                   .any(|n| aux(&n, cur, p, k + 1, f))
             }
         }
 
         let mut iter = s.neighbours().filter(|n| n.id() != t.id());
         let n1 = iter.next().unwrap();
         let n2 = iter.next().unwrap();
-        aux(n1, s, t.p(), 1, &mut |ps, p, k| f(ps, p, k) || (aux(n2, s, ps, k, f)))
-            || aux(n2, s, t.p(), 1, f)
+        aux(&n1, s, t.p(), 1, &mut |ps, p, k| f(ps, p, k) || (aux(&n2, s, ps, k, f)))
+            || aux(&n2, s, t.p(), 1, f)
     }
 
     fn prune_check<M>(&mut self, ti: usize, si: usize, geo: &M) -> bool
@@ -280,17 +532,19 @@ impl<P: Point, S: TerminalSorter> GPEnumeration<P, S> {
             let data = unsafe { &mut *(&mut self.data as *mut GPEnumerationData) };
             let (t, s) = (self.tree.i(ti), self.tree.i(si));
             if self.e_bsd || self.e_ss {
-                Self::prune_paths_check(s, t, &mut |p1, p2, k| {
+                Self::prune_paths_check(&s, &t, &mut |p1, p2, k| {
                     let j = self.t.len();
                     let d = geo.dist(p1, p2);
                     let b = self.get_bottleneck(p1, p2);
                     if self.e_bsd && d > P::R::from((k+j+1) as f64) * b {
                         data.bsd_pruned[depth] += 1;
+                        self.observer.pruned(depth + 1, PruneReason::Bsd);
                         true
                     } else if self.e_ss && d > self.get_ss(p1) + self.get_ss(p2)
                                                + P::R::from((k+j-1) as f64) * b
                     {
                         data.ss_pruned[depth] += 1;
+                        self.observer.pruned(depth + 1, PruneReason::Ss);
                         true
                     } else {
                         false
@@ -313,6 +567,7 @@ pub struct GPEnumerationData {
     bsd_pruned: Vec<usize>,
     ss_pruned: Vec<usize>,
     prune_times: Vec<Duration>,
+    frontier_peak: usize,
     sort_time: Duration,
     bsd_time: Duration,
     ss_time: Duration,
@@ -320,7 +575,7 @@ pub struct GPEnumerationData {
 }
 
 impl GPEnumerationData {
-    fn new(mut ts: usize) -> Self {
+    pub fn new(mut ts: usize) -> Self {
         ts = if ts < 3 { 0 } else { ts - 3 };
         GPEnumerationData {
             nodes: 0,
@@ -328,6 +583,7 @@ impl GPEnumerationData {
             bsd_pruned: vec![0; ts],
             ss_pruned: vec![0; ts],
             prune_times: vec![Duration::new(0, 0); ts],
+            frontier_peak: 0,
             sort_time: Duration::new(0, 0),
             bsd_time: Duration::new(0, 0),
             ss_time: Duration::new(0, 0),
@@ -342,12 +598,41 @@ impl GPEnumerationData {
         self.bsd_pruned = vec![0; ts];
         self.ss_pruned = vec![0; ts];
         self.prune_times = vec![Duration::new(0, 0); ts];
+        self.frontier_peak = 0;
         self.sort_time = Duration::new(0,0);
         self.bsd_time = Duration::new(0,0);
         self.ss_time = Duration::new(0,0);
         self.init_time = Duration::new(0,0);
     }
 
+    /// Adds the statistics of `other` to these, e.g. to combine the data of
+    /// enumerators working on subproblems of the same instance.
+    pub fn merge(&mut self, other: &Self) {
+        fn add<T: Copy + ::std::ops::AddAssign>(a: &mut Vec<T>, b: &[T]) {
+            if b.is_empty() {
+                // Nothing was enumerated
+            } else if a.is_empty() {
+                a.extend_from_slice(b);
+            } else {
+                debug_assert_eq!(a.len(), b.len());
+                for (x, &y) in a.iter_mut().zip(b.iter()) {
+                    *x += y;
+                }
+            }
+        }
+
+        self.nodes += other.nodes;
+        self.time += other.time;
+        add(&mut self.bsd_pruned, &other.bsd_pruned);
+        add(&mut self.ss_pruned, &other.ss_pruned);
+        add(&mut self.prune_times, &other.prune_times);
+        self.frontier_peak = self.frontier_peak.max(other.frontier_peak);
+        self.sort_time += other.sort_time;
+        self.bsd_time += other.bsd_time;
+        self.ss_time += other.ss_time;
+        self.init_time += other.init_time;
+    }
+
     pub fn ss_pruned(&self) -> &[usize]  {
         &self.ss_pruned
     }
@@ -360,6 +645,10 @@ impl GPEnumerationData {
         &self.prune_times
     }
 
+    pub fn frontier_peak(&self) -> usize  {
+        self.frontier_peak
+    }
+
     pub fn sort_time(&self) -> &Duration  {
         &self.sort_time
     }
@@ -392,6 +681,24 @@ impl EnumeratorData for GPEnumerationData {
     }
 }
 
+impl Report for GPEnumerationData {
+    fn report(&self) -> Stats {
+        let prune_time = self.prune_times.iter().fold(Duration::new(0, 0), |a, &t| a + t);
+        Stats::new()
+            .count("nodes", self.nodes as u64)
+            .count("pruned", self.pruned() as u64)
+            .seconds("time", &self.time)
+            .counts("bsd_pruned", &self.bsd_pruned)
+            .counts("ss_pruned", &self.ss_pruned)
+            .seconds("prune_time", &prune_time)
+            .value("frontier_peak", self.frontier_peak as f64)
+            .seconds("sort_time", &self.sort_time)
+            .seconds("bsd_time", &self.bsd_time)
+            .seconds("ss_time", &self.ss_time)
+            .seconds("init_time", &self.init_time)
+    }
+}
+
 impl fmt::Display for GPEnumerationData {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         fn printdur(dur: &Duration) -> f64 {
@@ -400,6 +707,7 @@ impl fmt::Display for GPEnumerationData {
 
         try!(writeln!(f, "Data for the Gilbert-Pollak enumeration:"));
         try!(writeln!(f, "\tNumber of nodes enumerated (i.e. not pruned): {}", self.nodes));
+        try!(writeln!(f, "\tMaximum number of nodes in the best-first frontier: {}", self.frontier_peak));
 
         try!(writeln!(f, "\n\tInit times:"));
         try!(writeln!(f, "\t\tBottleneck Steiner distances: {}", printdur(&self.bsd_time)));
@@ -416,7 +724,7 @@ impl fmt::Display for GPEnumerationData {
     }
 }
 
-impl<P: Point, S: TerminalSorter> Enumerator<P> for GPEnumeration<P, S> {
+impl<P: Point, S: TerminalSorter, O: Observer<P>> Enumerator<P> for GPEnumeration<P, S, O> {
     type D = GPEnumerationData;
 
     fn init<M>(&mut self, mut terms: Vec<P>, geo: &M)
@@ -432,13 +740,17 @@ impl<P: Point, S: TerminalSorter> Enumerator<P> for GPEnumeration<P, S> {
         self.t.clear();
         self.edges.clear();
         self.t_len = terms.len();
+        self.pruned = false;
+        self.frontier.clear();
+        self.dive.clear();
         self.state = GPState::Start;
 
         for (i, t) in terms.iter_mut().enumerate() {
             t.set_id(i);
         }
 
-        if self.e_bsd {
+        // Smallest spheres pruning uses the bottleneck distances as well
+        if self.e_bsd || self.e_ss {
             let bsdstart = Instant::now();
             self.calc_bsd(&terms[..], geo);
             self.data.bsd_time = Instant::now() - bsdstart;
@@ -464,6 +776,7 @@ impl<P: Point, S: TerminalSorter> Enumerator<P> for GPEnumeration<P, S> {
 
             self.edges.extend_from_slice(&[(0,3),(1,3),(2,3)]);
             self.top.push(0);
+            self.sorted = terms.clone();
             self.t = terms;
         }
         self.data.init_time = Instant::now() - start;
@@ -474,7 +787,10 @@ impl<P: Point, S: TerminalSorter> Enumerator<P> for GPEnumeration<P, S> {
 
         let start = Instant::now();
 
-        if self.state == GPState::Start {
+        if self.state == GPState::Start || self.state == GPState::Split {
+            if self.state == GPState::Start {
+                self.enumerated();
+            }
             if self.t.len() == 0 {
                 self.state = GPState::Done;
             } else {
@@ -484,6 +800,15 @@ impl<P: Point, S: TerminalSorter> Enumerator<P> for GPEnumeration<P, S> {
             return true;
         }
 
+        if let GPOrder::BestFirst(max_frontier) = self.order {
+            let res = self.next_best_first(max_frontier, geo);
+            if res {
+                self.enumerated();
+            }
+            self.data.time = Instant::now() - start;
+            return res;
+        }
+
         if self.state == GPState::Done || !self.backtrack() {
             self.data.time = Instant::now() - start;
             return false;
@@ -506,6 +831,7 @@ impl<P: Point, S: TerminalSorter> Enumerator<P> for GPEnumeration<P, S> {
                 }
             } else {
                 self.data.nodes += 1;
+                self.enumerated();
                 self.data.time = Instant::now() - start;
                 return true;
             }
@@ -514,6 +840,10 @@ impl<P: Point, S: TerminalSorter> Enumerator<P> for GPEnumeration<P, S> {
 
 
 
+    fn prune(&mut self) {
+        self.pruned = true;
+    }
+
     fn tree(&self) -> &SteinerTree<P> {
         &self.tree
     }
@@ -522,6 +852,10 @@ impl<P: Point, S: TerminalSorter> Enumerator<P> for GPEnumeration<P, S> {
         &mut self.tree
     }
 
+    fn stored(&self) -> usize {
+        1 + self.frontier.len() + self.dive.len()
+    }
+
     fn data(&self) -> &Self::D {
         &self.data
     }
@@ -534,9 +868,15 @@ impl<P: Point, S: TerminalSorter> Enumerator<P> for GPEnumeration<P, S> {
             Ok(())
         }
 
-        try!(writeln!(f, "Gilbert-Pollak enumerator (depth-first) with:"));
+        match self.order {
+            GPOrder::DepthFirst =>
+                try!(writeln!(f, "Gilbert-Pollak enumerator (depth-first) with:")),
+            GPOrder::BestFirst(max) =>
+                try!(writeln!(f, "Gilbert-Pollak enumerator (best-first, \
+                    frontier of at most {} nodes) with:", max)),
+        }
         if self.e_bsd {
-            try!({indent(f, inde); writeln!(f, "  - bottleneck Steiner distance pruning;") });
+            try!({indent(f, inde); writeln!(f, "  - bottleneck Steiner distance pruning ({:?} MST);", self.mst) });
         }
         if self.e_ss {
             try!({indent(f, inde); writeln!(f, "  - smallest spheres pruning;") });
@@ -551,8 +891,81 @@ impl<P: Point, S: TerminalSorter> Enumerator<P> for GPEnumeration<P, S> {
     }
 }
 
-impl<P: Point, S: TerminalSorter> fmt::Display for GPEnumeration<P, S> {
+impl<P: Point, S: TerminalSorter, O: Observer<P>> fmt::Display for GPEnumeration<P, S, O> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         self.print(f, 0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::points::Point2d;
+    use geo::spaces::EuclideanSpace;
+    use generators::Generator;
+    use algorithms::steinerbnb::SteinerBnB;
+    use algorithms::rmt::GeoMedianIter;
+    use upperbounds::LineTree;
+
+    fn points(seed: u64, n: usize) -> Vec<Point2d<f64>> {
+        Generator::new(seed, 2).uniform(n)
+    }
+
+    /// Number of full topologies over `n` terminals, (2n-5)!!.
+    fn full_topologies(n: usize) -> usize {
+        (3..n).fold(1, |acc, k| acc * (2 * k - 3))
+    }
+
+    /// Enumerates without pruning, returning the number of full topologies
+    /// and the largest number of stored topologies.
+    fn enumerate(n: usize, order: GPOrder) -> (usize, usize) {
+        let geo = EuclideanSpace::new();
+        let mut e = GPEnumeration::new(false, false, NoOrdering).order(order);
+        e.init(points(1, n), &geo);
+        let (mut full, mut stored) = (0, 0);
+        while e.next(&geo) {
+            if e.tree().terminals().len() == n {
+                full += 1;
+            }
+            stored = stored.max(e.frontier.len() + e.dive.len());
+        }
+        (full, stored)
+    }
+
+    #[test]
+    fn all_orders_enumerate_every_topology() {
+        for n in 3..8 {
+            assert_eq!(enumerate(n, GPOrder::DepthFirst).0, full_topologies(n));
+            for &max in [0, 1, 10, 100000].iter() {
+                assert_eq!(enumerate(n, GPOrder::BestFirst(max)).0, full_topologies(n));
+            }
+        }
+    }
+
+    #[test]
+    fn best_first_memory_is_bounded() {
+        let n = 8;
+        for &max in [0, 1, 10, 50].iter() {
+            let (_, stored) = enumerate(n, GPOrder::BestFirst(max));
+            assert!(stored <= max + n, "{} topologies stored for a frontier of {}", stored, max);
+        }
+    }
+
+    #[test]
+    fn best_first_finds_the_optimum() {
+        let geo = EuclideanSpace::new();
+        for seed in 0..3 {
+            let t = points(seed, 8);
+            let mut depth = SteinerBnB::default();
+            let mut best = SteinerBnB::new(
+                GeoMedianIter::default(),
+                GPEnumeration::default().order(GPOrder::BestFirst(20)),
+                LineTree);
+            let a = depth.find(t.clone(), &geo).len(&geo);
+            let b = best.find(t, &geo).len(&geo);
+            // The relatively minimal trees are only found approximately, and
+            // their lengths depend on the path to the topology
+            assert!((a - b).abs() < 1e-3 * a, "{} != {}", a, b);
+        }
+    }
+}
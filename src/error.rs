@@ -0,0 +1,136 @@
+//! Errors of the fallible `try_*` variants of the algorithm traits.
+
+use std::cmp::Ordering;
+use std::error;
+use std::fmt;
+
+use traits::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SteinerError {
+    /// No terminals were given
+    NoTerminals,
+    /// A terminal whose dimension differs from the first terminal
+    Dimension { terminal: usize, expected: usize, found: usize },
+    /// A terminal with a NaN or infinite coordinate
+    NonFinite { terminal: usize },
+    /// Two terminals at the same position
+    Duplicate { first: usize, second: usize },
+    /// An iterative algorithm was stopped at its iteration limit
+    NoConvergence { algorithm: &'static str },
+    /// A closed form median was asked for a Steiner point of unsupported
+    /// degree
+    Degree { steiner_point: usize, degree: usize },
+    /// The computation produced a tree of NaN or infinite length
+    NonFiniteLength
+}
+
+impl fmt::Display for SteinerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SteinerError::NoTerminals => write!(f, "No terminals were given"),
+            SteinerError::Dimension { terminal, expected, found } =>
+                write!(f, "Terminal {} has {} coordinates, expected {}", terminal, found, expected),
+            SteinerError::NonFinite { terminal } =>
+                write!(f, "Terminal {} has a coordinate that is NaN or infinite", terminal),
+            SteinerError::Duplicate { first, second } =>
+                write!(f, "Terminals {} and {} are at the same position", first, second),
+            SteinerError::NoConvergence { algorithm } =>
+                write!(f, "The {} did not converge within its iteration limit", algorithm),
+            SteinerError::Degree { steiner_point, degree } =>
+                write!(f, "Steiner point {} has degree {}, but the closed form median only \
+                    handles Steiner points of degree 3", steiner_point, degree),
+            SteinerError::NonFiniteLength =>
+                write!(f, "The computed tree has a length that is NaN or infinite")
+        }
+    }
+}
+
+impl error::Error for SteinerError { }
+
+/// Checks that `points` is non-empty, of one dimension and finite. Errors
+/// refer to points by their index in `points`.
+pub fn check_points<P: Point>(points: &[P]) -> Result<(), SteinerError> {
+    let dim = match points.first() {
+        Some(p) => p.dim(),
+        None => return Err(SteinerError::NoTerminals)
+    };
+
+    for (i, p) in points.iter().enumerate() {
+        if p.dim() != dim {
+            return Err(SteinerError::Dimension { terminal: i, expected: dim, found: p.dim() });
+        }
+        if !p.iter().all(|c| c.is_number()) {
+            return Err(SteinerError::NonFinite { terminal: i });
+        }
+    }
+    Ok(())
+}
+
+/// Checks `terminals` like `check_points` and additionally that no two of
+/// them are at the same position.
+pub fn check_terminals<P: Point>(terminals: &[P]) -> Result<(), SteinerError> {
+    try!(check_points(terminals));
+
+    // Lexicographic sorting puts equal points next to each other.
+    // `check_points` has rejected NaN, but a `Real` whose `is_number`
+    // accepts it must not make the check panic either.
+    let cmp = |a: &P, b: &P| a.iter().zip(b.iter())
+        .map(|(x, y)| x.cmp_nan_last(y))
+        .find(|&o| o != Ordering::Equal)
+        .unwrap_or(Ordering::Equal);
+
+    let mut order: Vec<usize> = (0..terminals.len()).collect();
+    order.sort_by(|&i, &j| cmp(&terminals[i], &terminals[j]));
+    for w in order.windows(2) {
+        if cmp(&terminals[w[0]], &terminals[w[1]]) == Ordering::Equal {
+            return Err(SteinerError::Duplicate { first: w[0].min(w[1]), second: w[0].max(w[1]) });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::{NAN, INFINITY};
+    use geo::points::{Point2d, PointNd};
+    use algorithms::mst::Kruskal;
+    use geo::spaces::EuclideanSpace;
+
+    #[test]
+    fn nan_sorts_last() {
+        let mut xs = vec![2.0, NAN, -INFINITY, 1.0, NAN, 0.0];
+        xs.sort_by(|a, b| a.cmp_nan_last(b));
+        assert_eq!(&xs[..4], &[-INFINITY, 0.0, 1.0, 2.0]);
+        assert!(xs[4].is_nan() && xs[5].is_nan());
+        assert_eq!(NAN.cmp_nan_last(&NAN), Ordering::Equal);
+    }
+
+    #[test]
+    fn checks_terminals() {
+        assert_eq!(check_terminals::<Point2d<f64>>(&[]), Err(SteinerError::NoTerminals));
+        assert_eq!(check_terminals(&[Point2d::new([0.0, 0.0]), Point2d::new([NAN, 0.0]),
+                                     Point2d::new([NAN, 0.0])]),
+            Err(SteinerError::NonFinite { terminal: 1 }));
+        assert_eq!(check_terminals(&[Point2d::new([0.0, 0.0]), Point2d::new([0.0, INFINITY])]),
+            Err(SteinerError::NonFinite { terminal: 1 }));
+        assert_eq!(check_terminals(&[Point2d::new([0.0, 1.0]), Point2d::new([1.0, 0.0]),
+                                     Point2d::new([0.0, 1.0])]),
+            Err(SteinerError::Duplicate { first: 0, second: 2 }));
+        assert_eq!(check_terminals(&[Point2d::new([0.0, 1.0]), Point2d::new([1.0, 0.0])]), Ok(()));
+
+        let ps = vec![PointNd::new(vec![0.0, 0.0]), PointNd::new(vec![0.0, 0.0, 1.0])];
+        assert_eq!(check_terminals(&ps),
+            Err(SteinerError::Dimension { terminal: 1, expected: 2, found: 3 }));
+    }
+
+    #[test]
+    fn nan_distances_do_not_panic_the_mst() {
+        let t = vec![Point2d::new([0.0, 0.0]), Point2d::new([NAN, 0.0]), Point2d::new([1.0, 0.0])];
+        let st = Kruskal::new().find(&t, &EuclideanSpace);
+        assert_eq!(st.edges().count(), 2);
+        assert_eq!(Kruskal::new().try_find(&t, &EuclideanSpace).err(),
+            Some(SteinerError::NonFinite { terminal: 1 }));
+    }
+}
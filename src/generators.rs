@@ -0,0 +1,302 @@
+//! Seeded random terminal sets for experiments and benchmarks.
+//!
+//! The points are generated with a small built-in PRNG, so the same seed,
+//! dimension and sequence of calls always give the same points, on every
+//! platform and for every point type. Coordinates are drawn as `f64` and
+//! converted to the real type of the points afterwards. Point `i` of a
+//! generated set gets the id `i`.
+//!
+//! ```text
+//! let mut gen = Generator::new(42, 2);
+//! let square: Vec<Point2d<f64>> = gen.uniform(10);
+//! let clusters: Vec<PointNd<f64>> = Generator::new(42, 5).gaussian_clusters(20, 3, 0.05);
+//! ```
+
+use traits::*;
+
+use std::f64::consts::PI;
+
+/// The SplitMix64 generator of Steele, Lea and Flood. Small and fast with
+/// good statistical quality, which is all that is needed for instances.
+#[derive(Debug, Clone)]
+pub struct SplitMix64 {
+    state: u64
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        SplitMix64 { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1).
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform in [a, b).
+    pub fn range(&mut self, a: f64, b: f64) -> f64 {
+        a + (b - a) * self.next_f64()
+    }
+
+    /// Uniform in 0..n, for n > 0.
+    pub fn below(&mut self, n: usize) -> usize {
+        // Multiply-shift maps a 64 bit value to 0..n with negligible bias
+        ((self.next_u64() as u128 * n as u128) >> 64) as usize
+    }
+
+    /// Standard normal, by the Box-Muller transform.
+    pub fn normal(&mut self) -> f64 {
+        let u = 1.0 - self.next_f64();
+        let v = self.next_f64();
+        (-2.0 * u.ln()).sqrt() * (2.0 * PI * v).cos()
+    }
+
+    /// Shuffles `v` uniformly (Fisher-Yates).
+    pub fn shuffle<T>(&mut self, v: &mut [T]) {
+        for i in (1..v.len()).rev() {
+            let j = self.below(i + 1);
+            v.swap(i, j);
+        }
+    }
+}
+
+/// Generator of random terminal sets in a fixed dimension.
+///
+/// The methods are generic over the point type, e.g. `Point2d<f64>` or
+/// `PointNd<f64>`, and panic if the dimension of the generator does not fit
+/// the point type.
+#[derive(Debug, Clone)]
+pub struct Generator {
+    rng: SplitMix64,
+    dim: usize
+}
+
+impl Generator {
+    pub fn new(seed: u64, dim: usize) -> Self {
+        assert!(dim > 0, "Points need at least one coordinate");
+        Generator {
+            rng: SplitMix64::new(seed),
+            dim: dim
+        }
+    }
+
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    pub fn rng(&mut self) -> &mut SplitMix64 {
+        &mut self.rng
+    }
+
+    fn points<P: FromCoords>(&self, coords: Vec<Vec<f64>>) -> Vec<P> {
+        coords.into_iter().enumerate().map(|(i, c)| {
+            let c: Vec<P::R> = c.into_iter().map(P::R::from).collect();
+            let mut p = P::from_coords(&c).unwrap_or_else(||
+                panic!("A generator of dimension {} can't create this point type", self.dim));
+            p.set_id(i);
+            p
+        }).collect()
+    }
+
+    fn uniform_coords(&mut self) -> Vec<f64> {
+        (0..self.dim).map(|_| self.rng.next_f64()).collect()
+    }
+
+    /// `n` points uniformly distributed in the unit hypercube [0, 1)^d.
+    pub fn uniform<P: FromCoords>(&mut self, n: usize) -> Vec<P> {
+        let cs = (0..n).map(|_| self.uniform_coords()).collect();
+        self.points(cs)
+    }
+
+    /// `n` points spread over `clusters` clusters whose centers are uniform
+    /// in the unit hypercube. Every point picks a cluster uniformly and is
+    /// normally distributed around its center with standard deviation
+    /// `sigma` in each coordinate.
+    pub fn gaussian_clusters<P: FromCoords>(&mut self, n: usize, clusters: usize, sigma: f64) -> Vec<P> {
+        assert!(clusters > 0, "At least one cluster is needed");
+        let centers: Vec<Vec<f64>> = (0..clusters).map(|_| self.uniform_coords()).collect();
+        let cs = (0..n).map(|_| {
+            let c = &centers[self.rng.below(clusters)];
+            c.iter().map(|&x| x + sigma * self.rng.normal()).collect()
+        }).collect();
+        self.points(cs)
+    }
+
+    /// `n` points uniformly distributed on the sphere of radius `radius`
+    /// around the origin, i.e. on a circle in the plane.
+    pub fn sphere<P: FromCoords>(&mut self, n: usize, radius: f64) -> Vec<P> {
+        let cs = (0..n).map(|_| {
+            loop {
+                let v: Vec<f64> = (0..self.dim).map(|_| self.rng.normal()).collect();
+                let norm = v.iter().fold(0.0, |s, x| s + x * x).sqrt();
+                if norm > 1e-12 {
+                    return v.into_iter().map(|x| radius * x / norm).collect();
+                }
+            }
+        }).collect();
+        self.points(cs)
+    }
+
+    /// The first `n` points of the regular grid with `ceil(n^(1/d))` points
+    /// per side in the unit hypercube, in random order. Every coordinate is
+    /// moved by up to `perturbation` times the grid spacing; 0 gives the
+    /// exact lattice, which has many cocircular points.
+    pub fn lattice<P: FromCoords>(&mut self, n: usize, perturbation: f64) -> Vec<P> {
+        let mut side = (n as f64).powf(1.0 / self.dim as f64).round().max(1.0) as usize;
+        while side.pow(self.dim as u32) < n {
+            side += 1;
+        }
+        let spacing = 1.0 / side as f64;
+
+        let mut cs: Vec<Vec<f64>> = (0..n).map(|i| {
+            let mut k = i;
+            (0..self.dim).map(|_| {
+                let x = (k % side) as f64 * spacing;
+                k /= side;
+                x + perturbation * spacing * self.rng.range(-1.0, 1.0)
+            }).collect()
+        }).collect();
+        self.rng.shuffle(&mut cs);
+        self.points(cs)
+    }
+
+    /// `n` points on a random line through the unit hypercube.
+    pub fn collinear<P: FromCoords>(&mut self, n: usize) -> Vec<P> {
+        let (a, b) = (self.uniform_coords(), self.uniform_coords());
+        let cs = (0..n).map(|_| {
+            let t = self.rng.next_f64();
+            a.iter().zip(b.iter()).map(|(&x, &y)| x + t * (y - x)).collect()
+        }).collect();
+        self.points(cs)
+    }
+
+    /// `n` points uniform in the unit hypercube of which `duplicates` are
+    /// exact copies of other points of the set, in random order.
+    pub fn duplicates<P: FromCoords>(&mut self, n: usize, duplicates: usize) -> Vec<P> {
+        assert!(duplicates < n || n == 0, "At least one point must be unique");
+        let distinct = n - duplicates.min(n);
+        let mut cs: Vec<Vec<f64>> = (0..distinct).map(|_| self.uniform_coords()).collect();
+        for _ in 0..duplicates.min(n) {
+            let c = cs[self.rng.below(distinct)].clone();
+            cs.push(c);
+        }
+        self.rng.shuffle(&mut cs);
+        self.points(cs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::points::{Point2d, Point3d, PointNd};
+
+    fn coords<P: Point>(ps: &[P]) -> Vec<Vec<f64>> {
+        ps.iter().map(|p| p.iter().map(|&c| c.into()).collect()).collect()
+    }
+
+    fn distinct(cs: &[Vec<f64>]) -> usize {
+        let mut cs = cs.to_vec();
+        cs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        cs.dedup();
+        cs.len()
+    }
+
+    #[test]
+    fn splitmix64_reference_values() {
+        // The first outputs for seed 0, as published with the algorithm
+        let mut rng = SplitMix64::new(0);
+        assert_eq!(rng.next_u64(), 0xE220A8397B1DCDAF);
+        assert_eq!(rng.next_u64(), 0x6E789E6AA1B965F4);
+        assert_eq!(rng.next_u64(), 0x06C45D188009454F);
+    }
+
+    #[test]
+    fn rng_ranges() {
+        let mut rng = SplitMix64::new(5);
+        for _ in 0..1000 {
+            let x = rng.next_f64();
+            assert!(x >= 0.0 && x < 1.0);
+            let y = rng.range(-2.0, 3.0);
+            assert!(y >= -2.0 && y < 3.0);
+            assert!(rng.below(7) < 7);
+        }
+
+        let mut v: Vec<usize> = (0..20).collect();
+        rng.shuffle(&mut v);
+        assert!(v != (0..20).collect::<Vec<_>>());
+        v.sort();
+        assert_eq!(v, (0..20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn same_points_for_every_point_type() {
+        let a: Vec<Point3d<f64>> = Generator::new(9, 3).gaussian_clusters(10, 2, 0.1);
+        let b: Vec<PointNd<f64>> = Generator::new(9, 3).gaussian_clusters(10, 2, 0.1);
+        assert_eq!(coords(&a), coords(&b));
+        assert!(a.iter().enumerate().all(|(i, p)| p.id() == i));
+
+        let c: Vec<Point3d<f64>> = Generator::new(10, 3).gaussian_clusters(10, 2, 0.1);
+        assert!(coords(&a) != coords(&c));
+    }
+
+    #[test]
+    #[should_panic]
+    fn dimension_must_fit_the_point_type() {
+        let _: Vec<Point2d<f64>> = Generator::new(1, 3).uniform(1);
+    }
+
+    #[test]
+    fn uniform_and_sphere() {
+        let ps: Vec<PointNd<f64>> = Generator::new(1, 4).uniform(100);
+        assert!(coords(&ps).iter().all(|c| c.len() == 4 && c.iter().all(|&x| x >= 0.0 && x < 1.0)));
+
+        let ps: Vec<Point3d<f64>> = Generator::new(1, 3).sphere(100, 2.5);
+        assert!(coords(&ps).iter().all(|c| (c.iter().fold(0.0, |s, x| s + x * x).sqrt() - 2.5).abs() < 1e-12));
+    }
+
+    #[test]
+    fn clusters_without_spread_are_their_centers() {
+        let ps: Vec<Point2d<f64>> = Generator::new(2, 2).gaussian_clusters(50, 3, 0.0);
+        assert!(distinct(&coords(&ps)) <= 3);
+    }
+
+    #[test]
+    fn exact_lattice() {
+        let ps: Vec<Point2d<f64>> = Generator::new(3, 2).lattice(9, 0.0);
+        let cs = coords(&ps);
+        assert_eq!(distinct(&cs), 9);
+        assert!(cs.iter().all(|c| c.iter().all(|&x| [0.0, 1.0 / 3.0, 2.0 / 3.0].contains(&x))));
+
+        // 10 points need a 4x4 grid
+        let ps: Vec<Point2d<f64>> = Generator::new(3, 2).lattice(10, 0.1);
+        assert!(coords(&ps).iter().all(|c| c.iter().all(|&x| {
+            let off = x * 4.0 - (x * 4.0).round();
+            off.abs() <= 0.1 + 1e-12
+        })));
+    }
+
+    #[test]
+    fn collinear_points() {
+        let cs = coords(&Generator::new(4, 2).collinear::<Point2d<f64>>(20));
+        let (a, b) = (&cs[0], &cs[1]);
+        for c in cs.iter() {
+            let cross = (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0]);
+            assert!(cross.abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn duplicated_points() {
+        let cs = coords(&Generator::new(5, 3).duplicates::<Point3d<f64>>(20, 6));
+        assert_eq!(cs.len(), 20);
+        assert_eq!(distinct(&cs), 14);
+    }
+}
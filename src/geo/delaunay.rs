@@ -0,0 +1,1002 @@
+//! Delaunay triangulations of points in the plane, and their Voronoi
+//! diagrams.
+//!
+//! The triangulation is built incrementally with the Bowyer-Watson
+//! algorithm: every point replaces the triangles whose circumcircle
+//! contains it. The points are inserted in a biased randomized order, so
+//! for any input, like points on a parabola, the insertions create an
+//! expected O(n) triangles. The outside of the convex hull is covered by
+//! ghost triangles sharing a vertex at infinity, so points outside the hull
+//! need no special case. All decisions are made by the exact predicates of
+//! `geo::predicates`, so degenerate input like grids or cocircular points
+//! gives a valid triangulation.
+//!
+//! The vertices are the indices of the points, so for the terminals of a
+//! `SteinerTree` they are the ids of its nodes:
+//!
+//! ```text
+//! let del = Delaunay::new(&terminals);
+//! for (t, tri) in del.triangles() {
+//!     let [a, b, c] = tri.vertices();
+//!     let neighbours = del.neighbours(t);
+//! }
+//! let nearest = del.nearest_neighbour(0);
+//! let voronoi = del.voronoi();
+//! ```
+
+use std::collections::HashMap;
+use std::mem;
+use std::ops::Range;
+use std::f64;
+use std::usize;
+
+use traits::*;
+use steinertree::{SteinerTree};
+use error::{SteinerError};
+use geo::predicates::{orient2d, incircle};
+use generators::{SplitMix64};
+
+/// The vertex at infinity of the ghost triangles.
+const GHOST: usize = usize::MAX;
+
+/// A triangle of a `Delaunay` triangulation.
+///
+/// Internally also a ghost triangle `(a, b, GHOST)`, which lies outside
+/// the hull edge from `b` to `a`. `n[i]` is the triangle on the other side
+/// of the edge opposite `v[i]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Triangle {
+    v: [usize; 3],
+    n: [usize; 3]
+}
+
+impl Triangle {
+    /// The vertices in counterclockwise order.
+    pub fn vertices(&self) -> [usize; 3] {
+        self.v
+    }
+
+    /// The edge opposite the `i`th vertex, in counterclockwise order.
+    pub fn edge(&self, i: usize) -> (usize, usize) {
+        (self.v[(i + 1) % 3], self.v[(i + 2) % 3])
+    }
+
+    fn is_ghost(&self) -> bool {
+        self.v.contains(&GHOST)
+    }
+
+    fn index_of(&self, v: usize) -> usize {
+        self.v.iter().position(|&x| x == v).unwrap()
+    }
+}
+
+/// Buffers of the insertions, kept to save allocations. Removed triangles
+/// are marked by neighbours `GHOST` until their slot is reused.
+#[derive(Debug, Clone, Default)]
+struct Scratch {
+    free: Vec<usize>,
+    cavity: Vec<usize>,
+    boundary: Vec<(usize, usize, usize)>,
+    starts: HashMap<usize, usize>,
+    mark: Vec<usize>,
+    stamp: usize
+}
+
+/// A Delaunay triangulation of a set of points in the plane. Vertices are
+/// the indices of the points, triangles are numbered from 0 but their
+/// numbers change when points are inserted.
+///
+/// A point at the position of an earlier point is not a vertex; see
+/// `duplicates`. While all points are collinear there are no triangles,
+/// and the points are joined in their order along the line.
+#[derive(Debug, Clone)]
+pub struct Delaunay {
+    points: Vec<[f64; 2]>,
+    triangles: Vec<Triangle>,
+    // A triangle at every vertex, GHOST for duplicates and without triangles
+    vertex_triangle: Vec<usize>,
+    // The vertex at the position of every point, and a duplicate of every
+    // vertex (GHOST if none)
+    vertex: Vec<usize>,
+    duplicate: Vec<usize>,
+    duplicates: Vec<(usize, usize)>,
+    // While there are no triangles: the vertices in lexicographic order
+    line: Vec<usize>,
+    last: usize,
+    scratch: Scratch
+}
+
+fn coords<P: Point>(p: &P) -> [f64; 2] {
+    assert_eq!(p.dim(), 2, "Delaunay triangulations need points in the plane");
+    [p.coords()[0].into(), p.coords()[1].into()]
+}
+
+/// The coordinates of point `i`, if it is two-dimensional and finite.
+fn checked_coords<P: Point>(i: usize, p: &P) -> Result<[f64; 2], SteinerError> {
+    if p.dim() != 2 {
+        return Err(SteinerError::Dimension { terminal: i, expected: 2, found: p.dim() });
+    }
+    let c = coords(p);
+    if c[0].is_finite() && c[1].is_finite() {
+        Ok(c)
+    } else {
+        Err(SteinerError::NonFinite { terminal: i })
+    }
+}
+
+/// The grid of `hilbert` has `2^HILBERT_BITS` cells in each direction.
+const HILBERT_BITS: u32 = 16;
+const HILBERT_MAX: u64 = (1 << HILBERT_BITS) - 1;
+
+/// Vertices are inserted in one round until there are this many.
+const BRIO_FIRST_ROUND: usize = 64;
+
+/// The position of the grid cell `(x, y)` along a Hilbert curve through
+/// the grid.
+fn hilbert(mut x: u64, mut y: u64) -> u64 {
+    let mut d = 0;
+    let mut s = 1 << (HILBERT_BITS - 1);
+    while s > 0 {
+        let (rx, ry) = ((x & s > 0) as u64, (y & s > 0) as u64);
+        d += s * s * ((3 * rx) ^ ry);
+        // Rotates the quadrant so the curve in it starts at its corner
+        if ry == 0 {
+            if rx == 1 {
+                x = HILBERT_MAX - x;
+                y = HILBERT_MAX - y;
+            }
+            mem::swap(&mut x, &mut y);
+        }
+        s /= 2;
+    }
+    d
+}
+
+fn dist2(a: &[f64; 2], b: &[f64; 2]) -> f64 {
+    (a[0] - b[0]) * (a[0] - b[0]) + (a[1] - b[1]) * (a[1] - b[1])
+}
+
+/// Whether `p`, which is on the line through `a` and `b`, lies strictly
+/// between them.
+fn between(a: &[f64; 2], b: &[f64; 2], p: &[f64; 2]) -> bool {
+    let k = if a[0] != b[0] { 0 } else { 1 };
+    (a[k] < p[k] && p[k] < b[k]) || (b[k] < p[k] && p[k] < a[k])
+}
+
+impl Delaunay {
+    /// Triangulates `points`. Panics if they are not two-dimensional and
+    /// finite, see `try_new`.
+    pub fn new<P: Point>(points: &[P]) -> Self {
+        Delaunay::try_new(points).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like `new`, but fails if a point is not two-dimensional or has a
+    /// NaN or infinite coordinate.
+    pub fn try_new<P: Point>(points: &[P]) -> Result<Self, SteinerError> {
+        let points: Vec<[f64; 2]> = try!(points.iter().enumerate()
+            .map(|(i, p)| checked_coords(i, p)).collect());
+        let n = points.len();
+
+        // In lexicographic order duplicates are adjacent, and the first
+        // triangle is found without inserting all points. The coordinates
+        // are finite, so the order is total.
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|&i, &j| points[i].partial_cmp(&points[j]).unwrap().then(i.cmp(&j)));
+
+        let mut del = Delaunay {
+            points: points,
+            triangles: Vec::new(),
+            vertex_triangle: vec![GHOST; n],
+            vertex: (0..n).collect(),
+            duplicate: vec![GHOST; n],
+            duplicates: Vec::new(),
+            line: Vec::with_capacity(n),
+            last: 0,
+            scratch: Scratch::default()
+        };
+        for &i in order.iter() {
+            match del.line.last() {
+                Some(&j) if del.points[j] == del.points[i] => del.add_duplicate(j, i),
+                _ => del.line.push(i)
+            }
+        }
+        del.triangulate_line();
+        Ok(del)
+    }
+
+    /// Triangulates the nodes of `tree`, so the vertices are the node ids.
+    pub fn from_tree<P: Point>(tree: &SteinerTree<P>) -> Self {
+        let points: Vec<P> = tree.nodes().map(|n| n.p().clone()).collect();
+        Delaunay::new(&points)
+    }
+
+    /// Inserts `p` into the triangulation and returns its vertex, which is
+    /// the number of points before. Panics if `p` is not two-dimensional
+    /// and finite, see `try_insert`.
+    pub fn insert<P: Point>(&mut self, p: &P) -> usize {
+        self.try_insert(p).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like `insert`, but fails and leaves the triangulation unchanged if
+    /// `p` is not two-dimensional or not finite.
+    pub fn try_insert<P: Point>(&mut self, p: &P) -> Result<usize, SteinerError> {
+        let v = self.points.len();
+        let c = try!(checked_coords(v, p));
+        self.points.push(c);
+        self.vertex_triangle.push(GHOST);
+        self.vertex.push(v);
+        self.duplicate.push(GHOST);
+
+        if self.triangles.is_empty() {
+            let pos = {
+                let points = &self.points;
+                self.line.binary_search_by(|&i| points[i].partial_cmp(&points[v]).unwrap())
+            };
+            match pos {
+                Ok(i) => {
+                    let d = self.line[i];
+                    self.add_duplicate(d, v);
+                }
+                Err(i) => {
+                    self.line.insert(i, v);
+                    self.triangulate_line();
+                }
+            }
+            return Ok(v);
+        }
+
+        let t = self.locate(self.last, v);
+        match self.triangles[t].v.iter().find(|&&u| u != GHOST && self.points[u] == self.points[v]) {
+            Some(&u) => self.add_duplicate(u, v),
+            None => {
+                let mut scratch = mem::take(&mut self.scratch);
+                self.last = self.insert_vertex(t, v, &mut scratch);
+                self.scratch = scratch;
+            }
+        }
+        Ok(v)
+    }
+
+    fn add_duplicate(&mut self, vertex: usize, p: usize) {
+        self.vertex[p] = vertex;
+        if self.duplicate[vertex] == GHOST {
+            self.duplicate[vertex] = p;
+        }
+        self.duplicates.push((vertex, p));
+    }
+
+    /// Builds the first triangle once the points of `line` are not all
+    /// collinear, and inserts the others.
+    fn triangulate_line(&mut self) {
+        // The first triangle is spanned by the first two points and the
+        // first point off their line.
+        let s = self.line.clone();
+        let k = match (2..s.len()).find(|&k| orient2d(&self.points[s[0]], &self.points[s[1]],
+                                                      &self.points[s[k]]) != 0.0) {
+            Some(k) => k,
+            None => return
+        };
+        let (a, mut b, mut c) = (s[0], s[1], s[k]);
+        if orient2d(&self.points[a], &self.points[b], &self.points[c]) < 0.0 {
+            mem::swap(&mut b, &mut c);
+        }
+        self.triangles = vec![
+            Triangle { v: [a, b, c], n: [1, 2, 3] },
+            Triangle { v: [c, b, GHOST], n: [3, 2, 0] },
+            Triangle { v: [a, c, GHOST], n: [1, 3, 0] },
+            Triangle { v: [b, a, GHOST], n: [2, 1, 0] }
+        ];
+        for &u in [a, b, c].iter() {
+            self.vertex_triangle[u] = 0;
+        }
+        self.line.clear();
+
+        let rest: Vec<usize> = s.iter().enumerate().filter(|&(i, _)| i >= 2 && i != k)
+            .map(|(_, &p)| p).collect();
+        let mut scratch = mem::take(&mut self.scratch);
+        self.last = 0;
+        for p in self.insertion_order(rest) {
+            let t = self.locate(self.last, p);
+            self.last = self.insert_vertex(t, p, &mut scratch);
+        }
+        self.scratch = scratch;
+    }
+
+    /// Orders `vertices` for insertion by a biased randomized insertion
+    /// order (Amenta, Choi and Rote): after a shuffle, the vertices are
+    /// inserted in rounds that double in size, each sorted along a Hilbert
+    /// curve. The rounds keep the expected number of created triangles
+    /// linear whatever the input, and the sorting keeps the walks of
+    /// `locate` short. The seed is fixed, so the triangulation of the same
+    /// points is always the same.
+    fn insertion_order(&self, mut vertices: Vec<usize>) -> Vec<usize> {
+        SplitMix64::new(0x5EED).shuffle(&mut vertices);
+
+        let (mut lo, mut hi) = ([f64::INFINITY; 2], [f64::NEG_INFINITY; 2]);
+        for &v in vertices.iter() {
+            for k in 0..2 {
+                lo[k] = lo[k].min(self.points[v][k]);
+                hi[k] = hi[k].max(self.points[v][k]);
+            }
+        }
+        let key = |v: usize| -> u64 {
+            let cell = |k: usize| -> u64 {
+                let extent = hi[k] - lo[k];
+                if extent > 0.0 {
+                    ((self.points[v][k] - lo[k]) / extent * HILBERT_MAX as f64) as u64
+                } else {
+                    0
+                }
+            };
+            hilbert(cell(0), cell(1))
+        };
+
+        // The last round is the second half of the vertices, the one before
+        // the second quarter and so on
+        let mut end = vertices.len();
+        while end > BRIO_FIRST_ROUND {
+            let start = end / 2;
+            vertices[start..end].sort_by_key(|&v| key(v));
+            end = start;
+        }
+        vertices[..end].sort_by_key(|&v| key(v));
+        vertices
+    }
+
+    /// Whether `p` is inside the circumcircle of triangle `t`. The
+    /// circumcircle of a ghost triangle is the open half-plane beyond its
+    /// hull edge together with the inside of the edge.
+    fn conflicts(&self, t: usize, p: usize) -> bool {
+        let v = &self.triangles[t].v;
+        let pt = &self.points[p];
+        match v.iter().position(|&x| x == GHOST) {
+            None => incircle(&self.points[v[0]], &self.points[v[1]], &self.points[v[2]], pt) > 0.0,
+            Some(g) => {
+                let (a, b) = (&self.points[v[(g + 1) % 3]], &self.points[v[(g + 2) % 3]]);
+                let o = orient2d(a, b, pt);
+                o > 0.0 || (o == 0.0 && between(a, b, pt))
+            }
+        }
+    }
+
+    /// Walks from triangle `t` towards `p`, and returns a triangle in
+    /// conflict with `p` or, if `p` is at a vertex, a triangle at it.
+    fn locate(&self, mut t: usize, p: usize) -> usize {
+        let pt = &self.points[p];
+        // A walk in a Delaunay triangulation never visits a triangle twice
+        for _ in 0..self.triangles.len() {
+            let tri = &self.triangles[t];
+            if let Some(g) = tri.v.iter().position(|&x| x == GHOST) {
+                if self.conflicts(t, p) {
+                    return t;
+                }
+                t = tri.n[g];
+                continue;
+            }
+            let next = (0..3).find(|&i| {
+                let (u, w) = tri.edge(i);
+                orient2d(&self.points[u], &self.points[w], pt) < 0.0
+            });
+            match next {
+                Some(i) => t = tri.n[i],
+                None => return t
+            }
+        }
+        unreachable!("The walk to an inserted point did not end")
+    }
+
+    /// Inserts `p` into the cavity of the triangles in conflict with it,
+    /// starting from triangle `t`. Returns one of the new triangles.
+    fn insert_vertex(&mut self, t: usize, p: usize, scratch: &mut Scratch) -> usize {
+        let Scratch { ref mut free, ref mut cavity, ref mut boundary, ref mut starts,
+                      ref mut mark, ref mut stamp } = *scratch;
+
+        // Triangles in conflict, and the edges of the cavity with the
+        // triangle outside of them
+        *stamp += 1;
+        mark.resize(self.triangles.len(), 0);
+        cavity.clear();
+        boundary.clear();
+        cavity.push(t);
+        mark[t] = *stamp;
+        let mut c = 0;
+        while c < cavity.len() {
+            let ct = cavity[c];
+            c += 1;
+            for i in 0..3 {
+                let ((u, w), n) = (self.triangles[ct].edge(i), self.triangles[ct].n[i]);
+                if mark[n] == *stamp {
+                    continue;
+                }
+                if self.conflicts(n, p) {
+                    mark[n] = *stamp;
+                    cavity.push(n);
+                } else {
+                    boundary.push((u, w, n));
+                }
+            }
+        }
+        for &ct in cavity.iter() {
+            self.triangles[ct].n = [GHOST; 3];
+            free.push(ct);
+        }
+
+        // A new triangle for every boundary edge, joined to the triangle
+        // outside of it. There are two more of them than removed ones.
+        starts.clear();
+        for &(u, w, n) in boundary.iter() {
+            let tri = Triangle { v: [u, w, p], n: [GHOST, GHOST, n] };
+            let nt = match free.pop() {
+                Some(f) => {
+                    self.triangles[f] = tri;
+                    f
+                }
+                None => {
+                    self.triangles.push(tri);
+                    self.triangles.len() - 1
+                }
+            };
+            {
+                let outer = &mut self.triangles[n];
+                let j = (0..3).find(|&j| outer.v[j] != u && outer.v[j] != w).unwrap();
+                outer.n[j] = nt;
+            }
+            for &x in [u, w, p].iter() {
+                if x != GHOST {
+                    self.vertex_triangle[x] = nt;
+                }
+            }
+            starts.insert(u, nt);
+        }
+
+        // The new triangles around `p`: the one starting at `w` follows the
+        // one from `u` to `w`
+        for (_, &nt) in starts.iter() {
+            let w = self.triangles[nt].v[1];
+            let next = starts[&w];
+            self.triangles[nt].n[0] = next;
+            self.triangles[next].n[1] = nt;
+        }
+
+        starts[&boundary[0].0]
+    }
+
+    /// Number of points, including duplicates.
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn point(&self, v: usize) -> [f64; 2] {
+        self.points[v]
+    }
+
+    /// Number of triangles.
+    pub fn triangle_count(&self) -> usize {
+        self.triangles.iter().filter(|tri| !tri.is_ghost()).count()
+    }
+
+    /// The triangles with their numbers.
+    pub fn triangles(&self) -> Triangles {
+        Triangles {
+            triangles: &self.triangles[..],
+            iter: 0..self.triangles.len()
+        }
+    }
+
+    pub fn triangle(&self, t: usize) -> &Triangle {
+        debug_assert!(!self.triangles[t].is_ghost());
+        &self.triangles[t]
+    }
+
+    /// The triangles across the edges of triangle `t`, in the order of its
+    /// vertices: the `i`th is across the edge opposite the `i`th vertex,
+    /// `None` on the hull.
+    pub fn neighbours(&self, t: usize) -> [Option<usize>; 3] {
+        let mut neighbours = [None; 3];
+        for (i, &n) in self.triangles[t].n.iter().enumerate() {
+            if !self.triangles[n].is_ghost() {
+                neighbours[i] = Some(n);
+            }
+        }
+        neighbours
+    }
+
+    /// The triangles at vertex `v` in counterclockwise order, including
+    /// ghosts, starting from any of them.
+    fn around(&self, v: usize) -> Vec<usize> {
+        let start = self.vertex_triangle[v];
+        let mut around = Vec::new();
+        if start == GHOST {
+            return around;
+        }
+        let mut t = start;
+        loop {
+            around.push(t);
+            let tri = &self.triangles[t];
+            t = tri.n[(tri.index_of(v) + 1) % 3];
+            if t == start {
+                return around;
+            }
+        }
+    }
+
+    /// Whether `v` is at the position of an earlier point, so it is not a
+    /// vertex.
+    pub fn is_duplicate(&self, v: usize) -> bool {
+        self.vertex[v] != v
+    }
+
+    /// The vertices joined to vertex `v` by an edge, in counterclockwise
+    /// order, starting at the hull for vertices on the hull. Duplicates have
+    /// no neighbours.
+    pub fn vertex_neighbours(&self, v: usize) -> Vec<usize> {
+        if self.is_duplicate(v) {
+            return Vec::new();
+        }
+        if self.triangles.is_empty() {
+            let i = self.line.iter().position(|&u| u == v).unwrap();
+            return self.line[i.saturating_sub(1)..(i + 2).min(self.line.len())].iter()
+                .cloned().filter(|&u| u != v).collect();
+        }
+
+        let around = self.around(v);
+        // Start after the ghost triangles of a hull vertex
+        let first = (0..around.len())
+            .find(|&i| self.triangles[around[i]].is_ghost()
+                && !self.triangles[around[(i + 1) % around.len()]].is_ghost())
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        (0..around.len())
+            .map(|i| {
+                let tri = &self.triangles[around[(first + i) % around.len()]];
+                tri.v[(tri.index_of(v) + 1) % 3]
+            })
+            .filter(|&u| u != GHOST)
+            .collect()
+    }
+
+    /// The neighbours of all vertices, see `vertex_neighbours`.
+    pub fn adjacency(&self) -> Vec<Vec<usize>> {
+        (0..self.points.len()).map(|v| self.vertex_neighbours(v)).collect()
+    }
+
+    /// The edges of the triangulation, each once. Duplicates are not
+    /// joined, see `duplicates`. If all points are on a line, these are the
+    /// edges between consecutive points on it.
+    pub fn edges(&self) -> Vec<(usize, usize)> {
+        if self.triangles.is_empty() {
+            return self.line.windows(2).map(|w| (w[0], w[1])).collect();
+        }
+
+        let mut edges = Vec::with_capacity(3 * self.points.len());
+        for tri in self.triangles.iter().filter(|tri| !tri.is_ghost()) {
+            for i in 0..3 {
+                let (u, w) = tri.edge(i);
+                if u < w || self.triangles[tri.n[i]].is_ghost() {
+                    edges.push((u, w));
+                }
+            }
+        }
+        edges
+    }
+
+    /// Pairs of a vertex and a later point at its position, which is left
+    /// out of the triangulation.
+    pub fn duplicates(&self) -> &[(usize, usize)] {
+        &self.duplicates
+    }
+
+    /// The vertices of the convex hull in counterclockwise order, including
+    /// vertices inside of hull edges. Without triangles these are the ends
+    /// of the line, or the single vertex.
+    pub fn hull(&self) -> Vec<usize> {
+        if self.triangles.is_empty() {
+            let mut ends: Vec<usize> = self.line.first().into_iter()
+                .chain(self.line.last()).cloned().collect();
+            ends.dedup();
+            return ends;
+        }
+
+        let start = self.triangles.iter().position(|tri| tri.is_ghost()).unwrap();
+        let mut hull = Vec::new();
+        let mut t = start;
+        loop {
+            let tri = &self.triangles[t];
+            let g = tri.index_of(GHOST);
+            hull.push(tri.v[(g + 2) % 3]);
+            t = tri.n[(g + 2) % 3];
+            if t == start {
+                return hull;
+            }
+        }
+    }
+
+    /// The point closest to point `v` other than `v`, which is a neighbour
+    /// or at the same position. `None` for a single point.
+    pub fn nearest_neighbour(&self, v: usize) -> Option<usize> {
+        if self.is_duplicate(v) {
+            return Some(self.vertex[v]);
+        }
+        if self.duplicate[v] != GHOST {
+            return Some(self.duplicate[v]);
+        }
+        let p = &self.points[v];
+        self.vertex_neighbours(v).into_iter().fold(None, |min: Option<usize>, u| match min {
+            Some(m) if dist2(&self.points[m], p) <= dist2(&self.points[u], p) => min,
+            _ => Some(u)
+        })
+    }
+
+    /// The vertex closest to `p`, found by walking along the edges towards
+    /// `p`. `None` without points.
+    pub fn nearest<P: Point>(&self, p: &P) -> Option<usize> {
+        let p = coords(p);
+        let mut v = match self.line.first() {
+            Some(&v) => v,
+            None if self.triangles.is_empty() => return None,
+            None => self.triangles[self.last].v.iter().cloned().find(|&u| u != GHOST).unwrap()
+        };
+        // In a Delaunay triangulation every vertex but the nearest has a
+        // neighbour closer to `p`.
+        loop {
+            let d = dist2(&self.points[v], &p);
+            match self.vertex_neighbours(v).into_iter().find(|&u| dist2(&self.points[u], &p) < d) {
+                Some(u) => v = u,
+                None => return Some(v)
+            }
+        }
+    }
+
+    /// The centre of the circle through the vertices of triangle `t`, which
+    /// is a vertex of the Voronoi diagram.
+    pub fn circumcenter(&self, t: usize) -> [f64; 2] {
+        let v = self.triangle(t).v;
+        let (a, b, c) = (&self.points[v[0]], &self.points[v[1]], &self.points[v[2]]);
+        let (bx, by) = (b[0] - a[0], b[1] - a[1]);
+        let (cx, cy) = (c[0] - a[0], c[1] - a[1]);
+        let d = 2.0 * orient2d(a, b, c);
+        let (b2, c2) = (bx * bx + by * by, cx * cx + cy * cy);
+        [a[0] + (cy * b2 - by * c2) / d, a[1] + (bx * c2 - cx * b2) / d]
+    }
+
+    /// The Voronoi diagram of the points, dual to the triangulation.
+    pub fn voronoi(&self) -> Voronoi {
+        let mut index = vec![GHOST; self.triangles.len()];
+        let mut vertices = Vec::new();
+        let mut triangles = Vec::new();
+        for (t, _) in self.triangles() {
+            index[t] = vertices.len();
+            vertices.push(self.circumcenter(t));
+            triangles.push(t);
+        }
+
+        let cells = (0..self.points.len()).map(|v| {
+            let around = self.around(v);
+            let ghost = |i: usize| self.triangles[around[i % around.len()]].is_ghost();
+            match (0..around.len()).find(|&i| ghost(i) && !ghost(i + 1)) {
+                None => VoronoiCell {
+                    vertices: around.iter().map(|&t| index[t]).collect(),
+                    rays: None
+                },
+                Some(i) => {
+                    // The real triangles from the hull edge after the
+                    // ghosts to the one before them
+                    let fan: Vec<usize> = (1..around.len() + 1)
+                        .map(|j| around[(i + j) % around.len()])
+                        .take_while(|&t| !self.triangles[t].is_ghost())
+                        .collect();
+                    let (first, last) = (&self.triangles[fan[0]], &self.triangles[fan[fan.len() - 1]]);
+                    let into = first.v[(first.index_of(v) + 1) % 3];
+                    let out = last.v[(last.index_of(v) + 2) % 3];
+                    VoronoiCell {
+                        vertices: fan.iter().map(|&t| index[t]).collect(),
+                        rays: Some((self.outward(v, into), self.outward(out, v)))
+                    }
+                }
+            }
+        }).collect();
+
+        Voronoi {
+            vertices: vertices,
+            triangles: triangles,
+            cells: cells
+        }
+    }
+
+    /// The outward normal of the hull edge from `u` to `w`.
+    fn outward(&self, u: usize, w: usize) -> [f64; 2] {
+        let (a, b) = (&self.points[u], &self.points[w]);
+        [b[1] - a[1], a[0] - b[0]]
+    }
+}
+
+/// Iterator over the triangles of a `Delaunay` triangulation.
+pub struct Triangles<'a> {
+    triangles: &'a [Triangle],
+    iter: Range<usize>
+}
+
+impl<'a> Iterator for Triangles<'a> {
+    type Item = (usize, &'a Triangle);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let triangles = self.triangles;
+        self.iter.by_ref()
+            .find(|&t| !triangles[t].is_ghost())
+            .map(|t| (t, &triangles[t]))
+    }
+}
+
+/// The Voronoi diagram of the points of a `Delaunay` triangulation. Its
+/// vertices are the circumcenters of the triangles and its cells belong to
+/// the points.
+#[derive(Debug, Clone)]
+pub struct Voronoi {
+    vertices: Vec<[f64; 2]>,
+    triangles: Vec<usize>,
+    cells: Vec<VoronoiCell>
+}
+
+impl Voronoi {
+    pub fn vertices(&self) -> &[[f64; 2]] {
+        &self.vertices
+    }
+
+    /// The triangle of every vertex.
+    pub fn triangles(&self) -> &[usize] {
+        &self.triangles
+    }
+
+    /// The cell of point `v`.
+    pub fn cell(&self, v: usize) -> &VoronoiCell {
+        &self.cells[v]
+    }
+
+    pub fn cells(&self) -> &[VoronoiCell] {
+        &self.cells
+    }
+}
+
+/// The region of the plane closer to a point than to any other.
+///
+/// Duplicates, and all points of a triangulation without triangles, have
+/// empty cells.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoronoiCell {
+    vertices: Vec<usize>,
+    rays: Option<([f64; 2], [f64; 2])>
+}
+
+impl VoronoiCell {
+    /// The indices of the vertices in counterclockwise order.
+    pub fn vertices(&self) -> &[usize] {
+        &self.vertices
+    }
+
+    /// The directions to infinity of the two rays of an unbounded cell,
+    /// i.e. of a point on the hull, which start at the first and the last
+    /// vertex.
+    pub fn rays(&self) -> Option<([f64; 2], [f64; 2])> {
+        self.rays
+    }
+
+    pub fn is_bounded(&self) -> bool {
+        self.rays.is_none() && !self.vertices.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use generators::Generator;
+    use geo::points::{Point2d, PointNd};
+
+    fn dist(a: &[f64; 2], b: &[f64; 2]) -> f64 {
+        dist2(a, b).sqrt()
+    }
+
+    /// Checks the empty circle property and the counts of triangles and
+    /// edges of a triangulation of distinct points, not all collinear.
+    fn assert_delaunay(del: &Delaunay) {
+        for (_, tri) in del.triangles() {
+            let v = tri.vertices();
+            let (a, b, c) = (del.point(v[0]), del.point(v[1]), del.point(v[2]));
+            assert!(orient2d(&a, &b, &c) > 0.0);
+            for u in (0..del.len()).filter(|u| !v.contains(u)) {
+                assert!(incircle(&a, &b, &c, &del.point(u)) <= 0.0);
+            }
+        }
+
+        let (n, h) = (del.len() - del.duplicates().len(), del.hull().len());
+        assert_eq!(del.triangle_count(), 2 * n - 2 - h);
+        assert_eq!(del.edges().len(), 3 * n - 3 - h);
+    }
+
+    #[test]
+    fn random_points() {
+        for seed in 0..5 {
+            let t: Vec<Point2d<f64>> = Generator::new(seed, 2).uniform(200);
+            let del = Delaunay::new(&t);
+            assert_delaunay(&del);
+
+            for (t, _) in del.triangles() {
+                for (i, &n) in del.neighbours(t).iter().enumerate() {
+                    if let Some(n) = n {
+                        let (u, w) = del.triangle(t).edge(i);
+                        assert!(del.neighbours(n).iter().enumerate()
+                            .any(|(j, &m)| m == Some(t) && del.triangle(n).edge(j) == (w, u)));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn points_on_a_parabola() {
+        // All points are on the hull, and in lexicographic order most
+        // insertions would replace a linear number of triangles
+        let n = 20000;
+        let t: Vec<Point2d<f64>> = (0..n as i64).map(|i| i - n as i64 / 2)
+            .map(|x| Point2d::new([x as f64, (x * x) as f64])).collect();
+        let del = Delaunay::new(&t);
+        assert_eq!(del.hull().len(), n);
+        assert_eq!(del.triangle_count(), n - 2);
+
+        // Every edge is locally Delaunay, so the triangulation is Delaunay
+        for (t, tri) in del.triangles() {
+            let v = tri.vertices();
+            for (i, &nb) in del.neighbours(t).iter().enumerate() {
+                if let Some(nb) = nb {
+                    let (u, w) = tri.edge(i);
+                    let x = del.triangle(nb).vertices().iter().cloned()
+                        .find(|&x| x != u && x != w).unwrap();
+                    assert!(incircle(&del.point(v[0]), &del.point(v[1]), &del.point(v[2]),
+                                     &del.point(x)) <= 0.0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn grid() {
+        let t: Vec<Point2d<f64>> = (0..25).map(|i| Point2d::new([(i % 5) as f64, (i / 5) as f64]))
+            .collect();
+        let del = Delaunay::new(&t);
+        assert_delaunay(&del);
+        assert_eq!(del.triangle_count(), 32);
+        assert_eq!(del.hull().len(), 16);
+    }
+
+    #[test]
+    fn insert_like_new() {
+        let t: Vec<Point2d<f64>> = Generator::new(3, 2).uniform(100);
+        let mut del = Delaunay::new(&t[..0]);
+        for (i, p) in t.iter().enumerate() {
+            assert_eq!(del.insert(p), i);
+        }
+        assert_delaunay(&del);
+
+        let mut expected = Delaunay::new(&t).edges();
+        let mut edges = del.edges();
+        for e in expected.iter_mut().chain(edges.iter_mut()) {
+            *e = (e.0.min(e.1), e.0.max(e.1));
+        }
+        expected.sort();
+        edges.sort();
+        assert_eq!(edges, expected);
+    }
+
+    #[test]
+    fn duplicates() {
+        let t = vec![Point2d::new([0.0, 0.0]), Point2d::new([1.0, 0.0]), Point2d::new([0.0, 1.0]),
+                     Point2d::new([1.0, 0.0]), Point2d::new([0.3, 0.3])];
+        let mut del = Delaunay::new(&t);
+        assert_eq!(del.duplicates(), &[(1, 3)]);
+        assert!(del.is_duplicate(3) && !del.is_duplicate(1));
+        assert_eq!(del.nearest_neighbour(1), Some(3));
+        assert_eq!(del.nearest_neighbour(3), Some(1));
+        assert!(del.vertex_neighbours(3).is_empty());
+        assert_delaunay(&del);
+
+        assert_eq!(del.insert(&Point2d::new([0.3, 0.3])), 5);
+        assert_eq!(del.duplicates(), &[(1, 3), (4, 5)]);
+        assert_delaunay(&del);
+    }
+
+    #[test]
+    fn collinear_points() {
+        let t: Vec<Point2d<f64>> = [3.0, 0.0, 4.0, 1.0, 2.0].iter()
+            .map(|&x| Point2d::new([x, 2.0 * x])).collect();
+        let mut del = Delaunay::new(&t);
+        assert_eq!(del.triangle_count(), 0);
+        assert_eq!(del.edges(), vec![(1, 3), (3, 4), (4, 0), (0, 2)]);
+        assert_eq!(del.hull(), vec![1, 2]);
+        assert_eq!(del.vertex_neighbours(4), vec![3, 0]);
+        assert!(del.voronoi().cells().iter().all(|c| c.vertices().is_empty()));
+
+        del.insert(&Point2d::new([0.0, 1.0]));
+        assert_delaunay(&del);
+        assert_eq!(del.triangle_count(), 4);
+    }
+
+    #[test]
+    fn nearest_points() {
+        let t: Vec<Point2d<f64>> = Generator::new(4, 2).uniform(150);
+        let del = Delaunay::new(&t);
+        let points: Vec<[f64; 2]> = (0..t.len()).map(|v| del.point(v)).collect();
+        let closest = |q: &[f64; 2], skip: usize| (0..points.len()).filter(|&u| u != skip)
+            .map(|u| dist(&points[u], q)).fold(f64::INFINITY, f64::min);
+
+        for v in 0..t.len() {
+            let u = del.nearest_neighbour(v).unwrap();
+            assert_eq!(dist(&points[u], &points[v]), closest(&points[v], v));
+        }
+        for q in Generator::new(5, 2).uniform::<Point2d<f64>>(50) {
+            let q2 = [q.coords()[0], q.coords()[1]];
+            let u = del.nearest(&q).unwrap();
+            assert_eq!(dist(&points[u], &q2), closest(&q2, usize::MAX));
+        }
+        assert_eq!(Delaunay::new(&t[..1]).nearest_neighbour(0), None);
+        assert_eq!(Delaunay::new(&t[..0]).nearest(&t[0]), None);
+    }
+
+    #[test]
+    fn invalid_points() {
+        let nan = vec![Point2d::new([0.0, 0.0]), Point2d::new([f64::NAN, 1.0])];
+        assert_eq!(Delaunay::try_new(&nan).err(), Some(SteinerError::NonFinite { terminal: 1 }));
+        let inf = vec![Point2d::new([0.0, f64::INFINITY])];
+        assert_eq!(Delaunay::try_new(&inf).err(), Some(SteinerError::NonFinite { terminal: 0 }));
+        let space = vec![PointNd::new(vec![0.0, 0.0]), PointNd::new(vec![1.0, 0.0, 0.0])];
+        assert_eq!(Delaunay::try_new(&space).err(),
+            Some(SteinerError::Dimension { terminal: 1, expected: 2, found: 3 }));
+
+        let t = vec![Point2d::new([0.0, 0.0]), Point2d::new([1.0, 0.0]), Point2d::new([0.0, 1.0])];
+        let mut del = Delaunay::new(&t);
+        let edges = del.edges();
+        assert_eq!(del.try_insert(&Point2d::new([f64::NEG_INFINITY, 0.0])).err(),
+            Some(SteinerError::NonFinite { terminal: 3 }));
+        assert_eq!(del.len(), 3);
+        assert_eq!(del.edges(), edges);
+        assert_eq!(del.try_insert(&Point2d::new([1.0, 1.0])), Ok(3));
+        assert_delaunay(&del);
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_panics_on_nan() {
+        Delaunay::new(&[Point2d::new([f64::NAN, 0.0])]);
+    }
+
+    #[test]
+    fn voronoi() {
+        let t: Vec<Point2d<f64>> = Generator::new(6, 2).uniform(100);
+        let del = Delaunay::new(&t);
+        let vor = del.voronoi();
+        assert_eq!(vor.vertices().len(), del.triangle_count());
+
+        for (c, &t) in vor.vertices().iter().zip(vor.triangles()) {
+            let v = del.triangle(t).vertices();
+            let r = dist(c, &del.point(v[0]));
+            for &u in v[1..].iter() {
+                assert!((dist(c, &del.point(u)) - r).abs() < 1e-9 * r.max(1.0));
+            }
+        }
+
+        let hull = del.hull();
+        for v in 0..t.len() {
+            let cell = vor.cell(v);
+            let degree = del.vertex_neighbours(v).len();
+            if hull.contains(&v) {
+                assert!(!cell.is_bounded() && cell.rays().is_some());
+                assert_eq!(cell.vertices().len(), degree - 1);
+            } else {
+                assert!(cell.is_bounded());
+                assert_eq!(cell.vertices().len(), degree);
+            }
+        }
+    }
+}
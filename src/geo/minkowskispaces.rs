@@ -6,11 +6,14 @@ use std::fmt;
 pub struct LpSpace<R> {
     p: R
 }
-impl<R: Real> LpSpace<R> {
+impl<R> LpSpace<R> {
+    /// Lp space for a real `p` >= 1, `LpSpace::new(L1)` for the rectilinear
+    /// space or `LpSpace::new(LInfinity)` for the L∞ (Chebyshev) space.
     pub fn new(p: R) -> Self {
         LpSpace { p: p }
     }
-
+}
+impl<R: Real> LpSpace<R> {
     pub fn p(&self) -> R {
         self.p
     }
@@ -35,17 +38,38 @@ impl<R: Real> fmt::Display for LpSpace<R> {
         write!(f, "Lp space with p={}", self.p)
     }
 }
-#[derive(Debug)]
+/// The rectilinear (L1) space as its own type, for the algorithms that are
+/// exact only there.
+#[derive(Debug, Clone, Copy)]
+pub struct L1;
+impl<P: Point> MinkowskiSpace<P> for LpSpace<L1> {
+    fn norm(&self, p: &P) -> P::R {
+        p.iter().fold(P::R::zero(), |sum, &c| sum + c.abs())
+    }
+    fn dist(&self, p1: &P, p2: &P) -> P::R {
+         p1.iter()
+             .zip(p2.iter())
+             .fold(P::R::zero(), |sum, (&c1, &c2)|
+                sum + (c1 - c2).abs())
+    }
+}
+impl fmt::Display for LpSpace<L1> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Lp space with p=1")
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
 pub struct LInfinity;
 impl<P: Point> MinkowskiSpace<P> for LpSpace<LInfinity> {
     fn norm(&self, p: &P) -> P::R {
-        p.iter().fold(P::R::zero(), |a, &c| a.abs().max(c))
+        p.iter().fold(P::R::zero(), |a, &c| a.max(c.abs()))
     }
     fn dist(&self, p1: &P, p2: &P) -> P::R {
          p1.iter()
              .zip(p2.iter())
              .fold(P::R::zero(), |a, (&c1, &c2)|
-                a.abs().max(c1-c2))
+                a.max((c1-c2).abs()))
     }
 
 }
@@ -79,3 +103,36 @@ impl fmt::Display for EuclideanSpace {
         write!(f, "Euclidean space")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::points::{Point2d, Point3d};
+
+    #[test]
+    fn linfinity_takes_the_largest_absolute_coordinate() {
+        let geo = LpSpace::new(LInfinity);
+        let p = Point3d::new([1.0, -4.0, 2.0]);
+        assert_eq!(geo.norm(&p), 4.0);
+        assert_eq!(geo.dist(&p, &Point3d::new([-2.0, -3.0, 2.5])), 3.0);
+        assert_eq!(geo.norm(&Point2d::new([-1.0, -0.5])), 1.0);
+    }
+
+    #[test]
+    fn lp_norms() {
+        let (a, b) = (Point2d::new([0.0, 0.0]), Point2d::new([3.0, -4.0]));
+        assert_eq!(LpSpace::new(1.0).dist(&a, &b), 7.0);
+        assert!((LpSpace::new(2.0).dist(&a, &b) - 5.0).abs() < 1e-12);
+        assert!(LpSpace::new(8.0).dist(&a, &b) < 5.0);
+        assert!(LpSpace::new(8.0).dist(&a, &b) > LpSpace::new(LInfinity).dist(&a, &b));
+        assert_eq!(EuclideanSpace::new().norm(&b), 5.0);
+    }
+
+    #[test]
+    fn l1_matches_lp_with_p_one() {
+        let (a, b) = (Point3d::new([0.5, 2.0, -1.0]), Point3d::new([3.0, -4.0, 1.0]));
+        assert_eq!(LpSpace::new(L1).dist(&a, &b), 10.5);
+        assert_eq!(LpSpace::new(L1).dist(&a, &b), LpSpace::new(1.0).dist(&a, &b));
+        assert_eq!(LpSpace::new(L1).norm(&a), 3.5);
+    }
+}
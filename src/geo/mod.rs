@@ -1,10 +1,14 @@
 pub mod points;
 
 pub mod hyperplanes;
+pub mod predicates;
+pub mod delaunay;
 mod minkowskispaces;
 mod hyperellipsoidspaces;
+mod polytopespaces;
 
 pub mod spaces {
     pub use super::minkowskispaces::*;
     pub use super::hyperellipsoidspaces::*;
+    pub use super::polytopespaces::*;
 }
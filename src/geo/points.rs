@@ -47,6 +47,12 @@ impl<R: Real> Point for PointNd<R> {
     }
 }
 
+impl<R: Real> FromCoords for PointNd<R> {
+    fn from_coords(c: &[R]) -> Option<Self> {
+        Some(PointNd::new(c.to_vec()))
+    }
+}
+
 impl<E: Real + Clone> Clone for PointNd<E> {
     fn clone(&self) -> Self {
         PointNd { id: self.id, coords: self.coords.clone() }
@@ -115,6 +121,16 @@ impl<R: Real> Point for Point2d<R>  {
     }
 }
 
+impl<R: Real> FromCoords for Point2d<R> {
+    fn from_coords(c: &[R]) -> Option<Self> {
+        if c.len() == 2 {
+            Some(Point2d::new([c[0], c[1]]))
+        } else {
+            None
+        }
+    }
+}
+
 impl<E: Real + Clone> PartialEq for Point2d<E> {
     fn eq(&self, other: &Self) -> bool {
         self.iter().zip(other.iter()).all(&|(c, oc)| c == oc)
@@ -217,6 +233,16 @@ impl<E: Copy> Clone for Point3d<E> {
     }
 }
 
+impl<R: Real> FromCoords for Point3d<R> {
+    fn from_coords(c: &[R]) -> Option<Self> {
+        if c.len() == 3 {
+            Some(Point3d::new([c[0], c[1], c[2]]))
+        } else {
+            None
+        }
+    }
+}
+
 impl<E: Real + Clone> PartialEq for Point3d<E> {
     fn eq(&self, other: &Self) -> bool {
         self.iter().zip(other.iter()).all(&|(c, oc)| c == oc)
@@ -278,6 +304,16 @@ impl<E: Copy> Clone for Point4d<E> {
     }
 }
 
+impl<R: Real> FromCoords for Point4d<R> {
+    fn from_coords(c: &[R]) -> Option<Self> {
+        if c.len() == 4 {
+            Some(Point4d::new([c[0], c[1], c[2], c[3]]))
+        } else {
+            None
+        }
+    }
+}
+
 impl<E: Real + Clone> PartialEq for Point4d<E> {
     fn eq(&self, other: &Self) -> bool {
         self.iter().zip(other.iter()).all(&|(c, oc)| c == oc)
@@ -343,6 +379,16 @@ impl<E: Copy> Clone for Point5d<E> {
     }
 }
 
+impl<R: Real> FromCoords for Point5d<R> {
+    fn from_coords(c: &[R]) -> Option<Self> {
+        if c.len() == 5 {
+            Some(Point5d::new([c[0], c[1], c[2], c[3], c[4]]))
+        } else {
+            None
+        }
+    }
+}
+
 impl<E: Real + Clone> PartialEq for Point5d<E> {
     fn eq(&self, other: &Self) -> bool {
         self.iter().zip(other.iter()).all(&|(c, oc)| c == oc)
@@ -407,6 +453,16 @@ impl<E: Copy> Clone for Point6d<E> {
     }
 }
 
+impl<R: Real> FromCoords for Point6d<R> {
+    fn from_coords(c: &[R]) -> Option<Self> {
+        if c.len() == 6 {
+            Some(Point6d::new([c[0], c[1], c[2], c[3], c[4], c[5]]))
+        } else {
+            None
+        }
+    }
+}
+
 impl<E: Real + Clone> PartialEq for Point6d<E> {
     fn eq(&self, other: &Self) -> bool {
         self.iter().zip(other.iter()).all(&|(c, oc)| c == oc)
@@ -471,6 +527,16 @@ impl<E: Copy> Clone for Point7d<E> {
     }
 }
 
+impl<R: Real> FromCoords for Point7d<R> {
+    fn from_coords(c: &[R]) -> Option<Self> {
+        if c.len() == 7 {
+            Some(Point7d::new([c[0], c[1], c[2], c[3], c[4], c[5], c[6]]))
+        } else {
+            None
+        }
+    }
+}
+
 impl<E: Real + Clone> PartialEq for Point7d<E> {
     fn eq(&self, other: &Self) -> bool {
         self.iter().zip(other.iter()).all(&|(c, oc)| c == oc)
@@ -535,6 +601,16 @@ impl<E: Copy> Clone for Point8d<E> {
     }
 }
 
+impl<R: Real> FromCoords for Point8d<R> {
+    fn from_coords(c: &[R]) -> Option<Self> {
+        if c.len() == 8 {
+            Some(Point8d::new([c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7]]))
+        } else {
+            None
+        }
+    }
+}
+
 impl<E: Real + Clone> PartialEq for Point8d<E> {
     fn eq(&self, other: &Self) -> bool {
         self.iter().zip(other.iter()).all(&|(c, oc)| c == oc)
@@ -599,6 +675,16 @@ impl<E: Copy> Clone for Point9d<E> {
     }
 }
 
+impl<R: Real> FromCoords for Point9d<R> {
+    fn from_coords(c: &[R]) -> Option<Self> {
+        if c.len() == 9 {
+            Some(Point9d::new([c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7], c[8]]))
+        } else {
+            None
+        }
+    }
+}
+
 impl<E: Real + Clone> PartialEq for Point9d<E> {
     fn eq(&self, other: &Self) -> bool {
         self.iter().zip(other.iter()).all(&|(c, oc)| c == oc)
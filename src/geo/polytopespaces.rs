@@ -0,0 +1,273 @@
+use traits::{Point, Real, MinkowskiSpace};
+use geo::points::{Point2d};
+
+use std::fmt;
+
+const EPS: f64 = 1e-9;
+
+fn same<P: Point>(p1: &P, p2: &P) -> bool {
+    p1.iter().zip(p2.iter()).all(|(&a, &b)| (a - b).abs().into() < EPS)
+}
+
+/// Solves the square system `a x = b` by Gaussian elimination with partial
+/// pivoting, or returns `None` if `a` is singular.
+fn solve(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for c in 0..n {
+        let r = (c..n).max_by(|&i, &j| a[i][c].abs().cmp_nan_last(&a[j][c].abs())).unwrap();
+        if a[r][c].abs() < EPS {
+            return None;
+        }
+        a.swap(r, c);
+        b.swap(r, c);
+
+        for i in c+1..n {
+            let f = a[i][c] / a[c][c];
+            for j in c..n {
+                a[i][j] -= f * a[c][j];
+            }
+            b[i] -= f * b[c];
+        }
+    }
+
+    let mut x = vec![0.0; n];
+    for i in (0..n).rev() {
+        let s = (i+1..n).fold(b[i], |s, j| s - a[i][j] * x[j]);
+        x[i] = s / a[i][i];
+    }
+    Some(x)
+}
+
+/// Calls `f` with every `k` element subset of `0..n` in lexicographic order.
+fn subsets<F: FnMut(&[usize])>(n: usize, k: usize, f: &mut F) {
+    fn rec<F: FnMut(&[usize])>(n: usize, k: usize, cur: &mut Vec<usize>, f: &mut F) {
+        if cur.len() == k {
+            f(&cur[..]);
+            return;
+        }
+        let start = cur.last().map(|&i| i + 1).unwrap_or(0);
+        for i in start..n {
+            cur.push(i);
+            rec(n, k, cur, f);
+            cur.pop();
+        }
+    }
+    rec(n, k, &mut Vec::with_capacity(k), f);
+}
+
+/// Minkowski space whose unit ball is a centrally symmetric polytope. The
+/// polytope is stored by the normals `a` of its facets `<a, x> = 1`, so
+/// the norm of `x` is the largest `<a, x>`. This covers L1, L∞, hexagonal
+/// norms and λ-geometries.
+#[derive(Debug, Clone)]
+pub struct PolytopeSpace<P> {
+    normals: Vec<P>
+}
+impl<P: Point> PolytopeSpace<P> {
+    /// Polytope `{x : <a, x> <= 1}` over the given normals `a` and their
+    /// negations, which are added where missing.
+    pub fn from_facets(normals: Vec<P>) -> Self {
+        let mut all: Vec<P> = Vec::with_capacity(2 * normals.len());
+        for a in normals {
+            let mut na = a.clone();
+            na.neg();
+            for p in vec![a, na] {
+                if !all.iter().any(|q| same(q, &p)) {
+                    all.push(p);
+                }
+            }
+        }
+
+        PolytopeSpace { normals: all }
+    }
+
+    /// Polytope spanned by the given vertices and their negations. The
+    /// facets are found as the vertices of the polar polytope, by solving
+    /// for every set of `dim` vertices spanning a hyperplane, so this is only
+    /// meant for polytopes with few vertices. Panics if the vertices do not
+    /// span their space, see `try_from_vertices`.
+    pub fn from_vertices(vertices: Vec<P>) -> Self {
+        PolytopeSpace::try_from_vertices(vertices)
+            .expect("the vertices of a polytope space must span its dimension")
+    }
+
+    /// Like `from_vertices`, but `None` if there are no vertices, they
+    /// differ in dimension or they do not span their space, so the polytope
+    /// would not be the unit ball of a norm.
+    pub fn try_from_vertices(vertices: Vec<P>) -> Option<Self> {
+        let dim = match vertices.first() {
+            Some(v) if v.dim() > 0 => v.dim(),
+            _ => return None
+        };
+        if vertices.iter().any(|v| v.dim() != dim) {
+            return None;
+        }
+
+        let mut vs: Vec<Vec<f64>> = Vec::with_capacity(2 * vertices.len());
+        for v in vertices.iter() {
+            vs.push(v.iter().map(|&c| c.into()).collect());
+            vs.push(v.iter().map(|&c| -c.into()).collect());
+        }
+
+        let mut normals: Vec<P> = vec![];
+        subsets(vs.len(), dim, &mut |s| {
+            let a: Vec<Vec<f64>> = s.iter().map(|&i| vs[i].clone()).collect();
+            let n = match solve(a, vec![1.0; dim]) {
+                Some(n) => n,
+                None => return
+            };
+
+            let outside = vs.iter().any(|v|
+                v.iter().zip(n.iter()).fold(0.0, |s, (&vc, &nc)| s + vc*nc) > 1.0 + EPS);
+            if outside {
+                return;
+            }
+
+            let mut p = vertices[0].clone();
+            for (c, &nc) in p.iter_mut().zip(n.iter()) {
+                *c = P::R::from(nc);
+            }
+            if !normals.iter().any(|q| same(q, &p)) {
+                normals.push(p);
+            }
+        });
+
+        // Vertices in a proper subspace have no facets through `dim` of them
+        if normals.is_empty() {
+            return None;
+        }
+        Some(PolytopeSpace { normals: normals })
+    }
+
+    pub fn normals(&self) -> &[P] {
+        &self.normals[..]
+    }
+}
+impl<P: Point> MinkowskiSpace<P> for PolytopeSpace<P> {
+    fn norm(&self, p: &P) -> P::R {
+        self.normals.iter().fold(P::R::zero(), |m, a| m.max(a.dot(p)))
+    }
+}
+impl<P: Point> fmt::Display for PolytopeSpace<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Polytope space with {} facets", self.normals.len())
+    }
+}
+
+/// λ-geometry in the plane, where edges may only use the λ orientations
+/// `iπ/λ`. A segment is measured by its shortest path of at most two such
+/// orientations, which makes the unit ball the regular 2λ-gon with vertices
+/// at those orientations. λ=2 is rectilinear, λ=3 hexagonal and λ=4
+/// octilinear routing.
+#[derive(Debug)]
+pub struct LambdaSpace<R> {
+    lambda: usize,
+    polytope: PolytopeSpace<Point2d<R>>
+}
+impl<R: Real> LambdaSpace<R> {
+    pub fn new(lambda: usize) -> Self {
+        assert!(lambda >= 2, "λ-geometries need λ >= 2");
+
+        // The facet between orientations iπ/λ and (i+1)π/λ lies at distance
+        // cos(π/2λ) from the origin
+        let half = ::std::f64::consts::PI / (2.0 * lambda as f64);
+        let normals = (0..lambda).map(|i| {
+            let a = (2 * i + 1) as f64 * half;
+            Point2d::new([R::from(a.cos() / half.cos()), R::from(a.sin() / half.cos())])
+        }).collect();
+
+        LambdaSpace {
+            lambda: lambda,
+            polytope: PolytopeSpace::from_facets(normals)
+        }
+    }
+
+    pub fn lambda(&self) -> usize {
+        self.lambda
+    }
+
+    /// The unit ball as a polytope space.
+    pub fn polytope(&self) -> &PolytopeSpace<Point2d<R>> {
+        &self.polytope
+    }
+}
+impl<R: Real> MinkowskiSpace<Point2d<R>> for LambdaSpace<R> {
+    fn norm(&self, p: &Point2d<R>) -> R {
+        self.polytope.norm(p)
+    }
+}
+impl<R: Real> fmt::Display for LambdaSpace<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "λ-geometry space with λ={}", self.lambda)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::points::{Point3d, PointNd};
+
+    #[test]
+    fn diamond_is_l1() {
+        let geo = PolytopeSpace::from_vertices(vec![Point2d::new([1.0, 0.0]),
+                                                    Point2d::new([0.0, 1.0])]);
+        assert_eq!(geo.normals().len(), 4);
+        let (a, b) = (Point2d::new([0.5, -1.0]), Point2d::new([-1.0, 1.0]));
+        assert!((geo.dist(&a, &b) - 3.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn vertices_must_span_the_space() {
+        assert!(PolytopeSpace::<Point2d<f64>>::try_from_vertices(vec![]).is_none());
+        let segment = vec![Point2d::new([1.0, 1.0]), Point2d::new([-2.0, -2.0])];
+        assert!(PolytopeSpace::try_from_vertices(segment).is_none());
+        let square = vec![Point3d::new([1.0, 0.0, 0.0]), Point3d::new([0.0, 1.0, 0.0]),
+                          Point3d::new([1.0, 1.0, 0.0])];
+        assert!(PolytopeSpace::try_from_vertices(square).is_none());
+        let mixed = vec![PointNd::new(vec![1.0, 0.0]), PointNd::new(vec![0.0, 1.0, 0.0])];
+        assert!(PolytopeSpace::try_from_vertices(mixed).is_none());
+        assert!(PolytopeSpace::try_from_vertices(vec![Point2d::new([1.0, 0.0]),
+                                                      Point2d::new([0.0, 1.0])]).is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "span")]
+    fn from_vertices_panics_without_facets() {
+        PolytopeSpace::<Point2d<f64>>::from_vertices(vec![]);
+    }
+
+    #[test]
+    fn facets_of_the_cube_give_linfinity() {
+        let geo = PolytopeSpace::from_facets(vec![
+            Point3d::new([1.0, 0.0, 0.0]), Point3d::new([0.0, 1.0, 0.0]),
+            Point3d::new([0.0, 0.0, 1.0]), Point3d::new([-1.0, 0.0, 0.0])]);
+        assert_eq!(geo.normals().len(), 6);
+        assert_eq!(geo.norm(&Point3d::new([0.5, -2.0, 1.0])), 2.0);
+    }
+
+    #[test]
+    fn lambda_ball_is_the_regular_polygon() {
+        for lambda in 2..7 {
+            let geo = LambdaSpace::<f64>::new(lambda);
+            let step = ::std::f64::consts::PI / lambda as f64;
+            let vertices = (0..lambda)
+                .map(|i| Point2d::new([(i as f64 * step).cos(), (i as f64 * step).sin()]));
+            let polygon = PolytopeSpace::from_vertices(vertices.collect());
+            for &(x, y) in [(1.0, 0.0), (0.3, 0.7), (-2.0, 1.0), (0.0, -1.5)].iter() {
+                let q = Point2d::new([x, y]);
+                assert!((geo.norm(&q) - polygon.norm(&q)).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn lambda_distances() {
+        let octilinear = LambdaSpace::<f64>::new(4);
+        // Along an orientation a segment costs its Euclidean length,
+        // otherwise it is bent into two orientations
+        assert!((octilinear.norm(&Point2d::new([1.0, 1.0])) - 2f64.sqrt()).abs() < 1e-9);
+        assert!((octilinear.norm(&Point2d::new([2.0, 1.0])) - (1.0 + 2f64.sqrt())).abs() < 1e-9);
+        // λ=2 is rectilinear
+        assert!((LambdaSpace::<f64>::new(2).norm(&Point2d::new([2.0, -1.0])) - 3.0).abs() < 1e-9);
+    }
+}
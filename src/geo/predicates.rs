@@ -0,0 +1,257 @@
+//! Robust geometric predicates in the plane.
+//!
+//! The predicates follow Shewchuk, "Adaptive Precision Floating-Point
+//! Arithmetic and Fast Robust Geometric Predicates" (1997): the determinant
+//! is evaluated in floating point first, and only when it is smaller than
+//! its error bound it is evaluated again exactly, with the sums and
+//! products as expansions, i.e. sums of non-overlapping floats. The sign of
+//! the result is therefore always correct, at the cost of the floating
+//! point evaluation in all but nearly degenerate cases.
+//!
+//! Coordinates must be finite, and the exact stage assumes that no
+//! intermediate product overflows or underflows.
+
+/// 2^-53, half the distance between 1 and the next float
+const EPSILON: f64 = 1.1102230246251565e-16;
+/// 2^27 + 1, splits a float into two halves of 26 bits
+const SPLITTER: f64 = 134217729.0;
+const CCW_ERRBOUND: f64 = (3.0 + 16.0 * EPSILON) * EPSILON;
+const ICC_ERRBOUND: f64 = (10.0 + 96.0 * EPSILON) * EPSILON;
+
+/// `a + b` as the rounded sum and its error.
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    let bv = x - a;
+    let av = x - bv;
+    (x, (a - av) + (b - bv))
+}
+
+fn split(a: f64) -> (f64, f64) {
+    let c = SPLITTER * a;
+    let hi = c - (c - a);
+    (hi, a - hi)
+}
+
+/// `a * b` as the rounded product and its error.
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let x = a * b;
+    let (ahi, alo) = split(a);
+    let (bhi, blo) = split(b);
+    let err = x - ahi * bhi - alo * bhi - ahi * blo;
+    (x, alo * blo - err)
+}
+
+/// `a - b` as an expansion.
+fn diff(a: f64, b: f64) -> Vec<f64> {
+    let (x, y) = two_sum(a, -b);
+    compress(vec![y, x])
+}
+
+/// Drops the zero components of an expansion, keeping one for zero.
+fn compress(mut e: Vec<f64>) -> Vec<f64> {
+    e.retain(|&c| c != 0.0);
+    if e.is_empty() {
+        e.push(0.0);
+    }
+    e
+}
+
+/// `e + b` for an expansion `e`.
+fn grow(e: &[f64], b: f64) -> Vec<f64> {
+    let mut h = Vec::with_capacity(e.len() + 1);
+    let mut q = b;
+    for &c in e {
+        let (s, err) = two_sum(q, c);
+        h.push(err);
+        q = s;
+    }
+    h.push(q);
+    compress(h)
+}
+
+fn sum(e: &[f64], f: &[f64]) -> Vec<f64> {
+    f.iter().fold(e.to_vec(), |h, &b| grow(&h, b))
+}
+
+/// `e * b` for an expansion `e`.
+fn scale(e: &[f64], b: f64) -> Vec<f64> {
+    let mut h = Vec::with_capacity(2 * e.len());
+    let (mut q, err) = two_product(e[0], b);
+    h.push(err);
+    for &c in e[1..].iter() {
+        let (p1, p0) = two_product(c, b);
+        let (s, err) = two_sum(q, p0);
+        h.push(err);
+        let (s, err) = two_sum(p1, s);
+        h.push(err);
+        q = s;
+    }
+    h.push(q);
+    compress(h)
+}
+
+fn product(e: &[f64], f: &[f64]) -> Vec<f64> {
+    f.iter().fold(vec![0.0], |h, &b| sum(&h, &scale(e, b)))
+}
+
+fn negate(e: &[f64]) -> Vec<f64> {
+    e.iter().map(|&c| -c).collect()
+}
+
+/// The largest component, which has the sign of the expansion.
+fn sign_of(e: &[f64]) -> f64 {
+    *e.last().unwrap()
+}
+
+/// Positive if `a`, `b` and `c` are in counterclockwise order, negative if
+/// they are in clockwise order and zero if they are collinear. The value
+/// approximates twice the signed area of the triangle.
+pub fn orient2d(a: &[f64; 2], b: &[f64; 2], c: &[f64; 2]) -> f64 {
+    let left = (a[0] - c[0]) * (b[1] - c[1]);
+    let right = (a[1] - c[1]) * (b[0] - c[0]);
+    let det = left - right;
+
+    let detsum = if left > 0.0 {
+        if right <= 0.0 {
+            return det;
+        }
+        left + right
+    } else if left < 0.0 {
+        if right >= 0.0 {
+            return det;
+        }
+        -left - right
+    } else {
+        return det;
+    };
+    let bound = CCW_ERRBOUND * detsum;
+    if det >= bound || -det >= bound {
+        return det;
+    }
+
+    let (acx, acy) = (diff(a[0], c[0]), diff(a[1], c[1]));
+    let (bcx, bcy) = (diff(b[0], c[0]), diff(b[1], c[1]));
+    sign_of(&sum(&product(&acx, &bcy), &negate(&product(&acy, &bcx))))
+}
+
+/// Positive if `d` is inside the circle through `a`, `b` and `c`, negative
+/// if it is outside and zero if the four points are cocircular. The points
+/// `a`, `b` and `c` must be in counterclockwise order, else the sign is
+/// reversed.
+pub fn incircle(a: &[f64; 2], b: &[f64; 2], c: &[f64; 2], d: &[f64; 2]) -> f64 {
+    let (adx, ady) = (a[0] - d[0], a[1] - d[1]);
+    let (bdx, bdy) = (b[0] - d[0], b[1] - d[1]);
+    let (cdx, cdy) = (c[0] - d[0], c[1] - d[1]);
+
+    let (bdxcdy, cdxbdy) = (bdx * cdy, cdx * bdy);
+    let (cdxady, adxcdy) = (cdx * ady, adx * cdy);
+    let (adxbdy, bdxady) = (adx * bdy, bdx * ady);
+    let alift = adx * adx + ady * ady;
+    let blift = bdx * bdx + bdy * bdy;
+    let clift = cdx * cdx + cdy * cdy;
+
+    let det = alift * (bdxcdy - cdxbdy) + blift * (cdxady - adxcdy) + clift * (adxbdy - bdxady);
+    let permanent = (bdxcdy.abs() + cdxbdy.abs()) * alift
+        + (cdxady.abs() + adxcdy.abs()) * blift
+        + (adxbdy.abs() + bdxady.abs()) * clift;
+    let bound = ICC_ERRBOUND * permanent;
+    if det > bound || -det > bound {
+        return det;
+    }
+
+    let (adx, ady) = (diff(a[0], d[0]), diff(a[1], d[1]));
+    let (bdx, bdy) = (diff(b[0], d[0]), diff(b[1], d[1]));
+    let (cdx, cdy) = (diff(c[0], d[0]), diff(c[1], d[1]));
+    let lift = |x: &[f64], y: &[f64]| sum(&product(x, x), &product(y, y));
+    let cross = |x1: &[f64], y1: &[f64], x2: &[f64], y2: &[f64]|
+        sum(&product(x1, y2), &negate(&product(x2, y1)));
+
+    let a = product(&lift(&adx, &ady), &cross(&bdx, &bdy, &cdx, &cdy));
+    let b = product(&lift(&bdx, &bdy), &cross(&cdx, &cdy, &adx, &ady));
+    let c = product(&lift(&cdx, &cdy), &cross(&adx, &ady, &bdx, &bdy));
+    sign_of(&sum(&sum(&a, &b), &c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A simple generator of reproducible integers.
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self, range: i64) -> i64 {
+            self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            ((self.0 >> 33) as i64) % range
+        }
+    }
+
+    fn sign(x: f64) -> i32 {
+        if x > 0.0 { 1 } else if x < 0.0 { -1 } else { 0 }
+    }
+
+    fn exact_sign(x: i128) -> i32 {
+        if x > 0 { 1 } else if x < 0 { -1 } else { 0 }
+    }
+
+    #[test]
+    fn orientation_signs() {
+        let (a, b) = ([0.0, 0.0], [1.0, 0.0]);
+        assert!(orient2d(&a, &b, &[0.0, 1.0]) > 0.0);
+        assert!(orient2d(&a, &b, &[0.0, -1.0]) < 0.0);
+        assert_eq!(orient2d(&a, &b, &[7.0, 0.0]), 0.0);
+        assert_eq!(orient2d(&[0.5, 0.5], &[12.0, 12.0], &[24.0, 24.0]), 0.0);
+    }
+
+    #[test]
+    fn orientation_is_exact() {
+        // Shewchuk's example: points within a few ulps of (0.5, 0.5) against
+        // the line through (12, 12) and (24, 24). In units of 2^-53 all
+        // coordinates are integers.
+        let unit = 1.0 / (1u64 << 53) as f64;
+        let (b, c) = ([12i128 << 53, 12i128 << 53], [24i128 << 53, 24i128 << 53]);
+        for i in 0..32 {
+            for j in 0..32 {
+                let a = [(1i128 << 52) + i, (1i128 << 52) + j];
+                let det = (a[0] - c[0]) * (b[1] - c[1]) - (a[1] - c[1]) * (b[0] - c[0]);
+                let fa = [0.5 + i as f64 * unit, 0.5 + j as f64 * unit];
+                assert_eq!(sign(orient2d(&fa, &[12.0, 12.0], &[24.0, 24.0])), exact_sign(det),
+                    "{} {}", i, j);
+            }
+        }
+    }
+
+    #[test]
+    fn incircle_signs() {
+        let (a, b, c) = ([0.0, 0.0], [1.0, 0.0], [0.0, 1.0]);
+        assert!(incircle(&a, &b, &c, &[0.5, 0.5]) > 0.0);
+        assert!(incircle(&a, &b, &c, &[2.0, 2.0]) < 0.0);
+        assert_eq!(incircle(&a, &b, &c, &[1.0, 1.0]), 0.0);
+        // Clockwise order reverses the sign
+        assert!(incircle(&a, &c, &b, &[0.5, 0.5]) < 0.0);
+    }
+
+    #[test]
+    fn incircle_is_exact() {
+        // Integer points on a circle of radius 5k, and a fourth point near
+        // it, all shifted so that their differences are exact but their
+        // products are not
+        let mut rng = Lcg(2);
+        for _ in 0..2000 {
+            let k = 1 + rng.next(1 << 20);
+            let shift = [rng.next(1 << 20), rng.next(1 << 20)];
+            let p: Vec<[i64; 2]> = [[5 * k, 0], [3 * k, 4 * k], [-4 * k, 3 * k],
+                                    [-3 * k + rng.next(3) - 1, -4 * k + rng.next(3) - 1]].iter()
+                .map(|q| [q[0] + shift[0], q[1] + shift[1]]).collect();
+            let d = &p[3];
+            let r: Vec<[i128; 2]> = p[..3].iter()
+                .map(|q| [(q[0] - d[0]) as i128, (q[1] - d[1]) as i128]).collect();
+            let lift = |q: &[i128; 2]| q[0] * q[0] + q[1] * q[1];
+            let cross = |q: &[i128; 2], s: &[i128; 2]| q[0] * s[1] - q[1] * s[0];
+            let det = lift(&r[0]) * cross(&r[1], &r[2]) + lift(&r[1]) * cross(&r[2], &r[0])
+                + lift(&r[2]) * cross(&r[0], &r[1]);
+            let f: Vec<[f64; 2]> = p.iter().map(|q| [q[0] as f64, q[1] as f64]).collect();
+            assert_eq!(sign(incircle(&f[0], &f[1], &f[2], &f[3])), exact_sign(det), "{:?}", p);
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Graphviz DOT export of Steiner trees.
+
+use std::io::{self, Write};
+
+use traits::*;
+use steinertree::{SteinerTree};
+
+/// Writes `tree` as an undirected Graphviz graph. Node `i` of the tree is
+/// node `i` of the graph, terminals are drawn as filled black points and
+/// Steiner points as hollow red circles, and every edge is labelled with its
+/// length in `geo`. Two-dimensional trees get pinned `pos` attributes so
+/// that `neato -n` or `fdp` reproduce the geometry.
+pub fn write_tree<W, P, M>(w: &mut W, tree: &SteinerTree<P>, geo: &M, name: Option<&str>) -> io::Result<()>
+    where W: Write, P: Point, M: MinkowskiSpace<P> {
+
+    let name = name.unwrap_or("steiner_tree").replace('"', "\\\"");
+    try!(writeln!(w, "graph \"{}\" {{", name));
+    try!(writeln!(w, "  label=\"length {}\";", tree.len(geo)));
+    try!(writeln!(w, "  node [label=\"\", width=0.1, height=0.1, fixedsize=true];"));
+
+    for n in tree.nodes() {
+        let coords: Vec<String> = n.p().iter().map(|c| c.to_string()).collect();
+        try!(write!(w, "  {} [", n.id()));
+        if n.is_terminal() {
+            try!(write!(w, "shape=point, color=black"));
+        } else {
+            try!(write!(w, "shape=circle, color=red"));
+        }
+        try!(write!(w, ", tooltip=\"({})\"", coords.join(", ")));
+        if coords.len() == 2 {
+            try!(write!(w, ", pos=\"{},{}!\"", coords[0], coords[1]));
+        }
+        try!(writeln!(w, "];"));
+    }
+
+    for e in tree.edges() {
+        let (a, b) = e.nodes();
+        try!(writeln!(w, "  {} -- {} [label=\"{}\"];", a.id(), b.id(), e.len(geo)));
+    }
+    writeln!(w, "}}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::points::{Point2d, Point3d};
+    use geo::spaces::EuclideanSpace;
+
+    #[test]
+    fn writes_nodes_and_edges() {
+        let ts = [Point2d::new([0.0, 0.0]), Point2d::new([3.0, 0.0])];
+        let mut tree = SteinerTree::new(&ts, &[Point2d::new([0.0, 4.0])], &[]);
+        tree.add_edge((0, 2));
+        tree.add_edge((1, 2));
+        let mut buf = vec![];
+        write_tree(&mut buf, &tree, &EuclideanSpace, Some("a \"tree\"")).unwrap();
+        let dot = String::from_utf8(buf).unwrap();
+
+        assert!(dot.starts_with("graph \"a \\\"tree\\\"\" {\n"));
+        assert!(dot.trim_end().ends_with('}'));
+        assert!(dot.contains("label=\"length 9\""));
+        assert!(dot.contains("  0 [shape=point, color=black, tooltip=\"(0, 0)\", pos=\"0,0!\"];"));
+        assert!(dot.contains("  2 [shape=circle, color=red, tooltip=\"(0, 4)\", pos=\"0,4!\"];"));
+        assert!(dot.contains("  1 -- 2 [label=\"5\"];") || dot.contains("  2 -- 1 [label=\"5\"];"));
+        assert_eq!(dot.matches(" -- ").count(), 2);
+    }
+
+    #[test]
+    fn only_planar_trees_are_pinned() {
+        let tree = SteinerTree::new(&[Point3d::new([0.0, 1.0, 2.0])], &[], &[]);
+        let mut buf = vec![];
+        write_tree(&mut buf, &tree, &EuclideanSpace, None).unwrap();
+        let dot = String::from_utf8(buf).unwrap();
+        assert!(dot.starts_with("graph \"steiner_tree\""));
+        assert!(dot.contains("tooltip=\"(0, 1, 2)\""));
+        assert!(!dot.contains("pos="));
+    }
+}
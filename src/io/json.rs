@@ -0,0 +1,555 @@
+//! JSON export and import of Steiner trees.
+//!
+//! A tree is written as
+//!
+//! ```text
+//! {
+//!   "dim": 2,
+//!   "length": 2.732,
+//!   "nodes": [
+//!     {"id": 0, "terminal": true, "coords": [0, 0]},
+//!     ...
+//!   ],
+//!   "edges": [[0, 4], ...]
+//! }
+//! ```
+//!
+//! where node ids are the indices of the tree. `length` is optional and
+//! ignored when reading.
+
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use traits::*;
+use steinertree::{SteinerTree};
+
+#[derive(Debug)]
+pub enum JsonError {
+    Io(io::Error),
+    /// Invalid JSON, with the 1-based line and column of the problem
+    Syntax { line: usize, column: usize, message: String },
+    /// Valid JSON that does not describe a Steiner tree
+    Schema(String)
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            JsonError::Io(ref e) => write!(f, "I/O error: {}", e),
+            JsonError::Syntax { line, column, ref message } =>
+                write!(f, "Syntax error on line {}, column {}: {}", line, column, message),
+            JsonError::Schema(ref message) => write!(f, "Invalid Steiner tree: {}", message)
+        }
+    }
+}
+
+impl error::Error for JsonError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            JsonError::Io(ref e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+impl From<io::Error> for JsonError {
+    fn from(e: io::Error) -> Self {
+        JsonError::Io(e)
+    }
+}
+
+/// A parsed JSON document.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    /// Members in the order they were written
+    Object(Vec<(String, JsonValue)>)
+}
+
+impl JsonValue {
+    pub fn parse(s: &str) -> Result<JsonValue, JsonError> {
+        let mut p = Parser { s: s.as_bytes(), pos: 0 };
+        let v = try!(p.value());
+        p.whitespace();
+        if p.pos < p.s.len() {
+            return Err(p.error("trailing characters after the document"));
+        }
+        Ok(v)
+    }
+
+    /// The member `key` of an object.
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match *self {
+            JsonValue::Object(ref members) => members.iter().find(|m| m.0 == key).map(|m| &m.1),
+            _ => None
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            JsonValue::Number(n) => Some(n),
+            _ => None
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            JsonValue::Bool(b) => Some(b),
+            _ => None
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[JsonValue]> {
+        match *self {
+            JsonValue::Array(ref a) => Some(&a[..]),
+            _ => None
+        }
+    }
+
+    /// Non-negative integral numbers.
+    pub fn as_usize(&self) -> Option<usize> {
+        match *self {
+            JsonValue::Number(n) if n >= 0.0 && n.fract() == 0.0 => Some(n as usize),
+            _ => None
+        }
+    }
+}
+
+/// Writes `s` as a quoted JSON string.
+pub fn write_string<W: fmt::Write>(w: &mut W, s: &str) -> fmt::Result {
+    try!(w.write_char('"'));
+    for c in s.chars() {
+        match c {
+            '"' => try!(w.write_str("\\\"")),
+            '\\' => try!(w.write_str("\\\\")),
+            '\n' => try!(w.write_str("\\n")),
+            '\r' => try!(w.write_str("\\r")),
+            '\t' => try!(w.write_str("\\t")),
+            c if (c as u32) < 0x20 => try!(write!(w, "\\u{:04x}", c as u32)),
+            c => try!(w.write_char(c))
+        }
+    }
+    w.write_char('"')
+}
+
+/// Writes `n` as a JSON number; JSON has no infinities or NaN, so those are
+/// written as `null`.
+pub fn write_number<W: fmt::Write>(w: &mut W, n: f64) -> fmt::Result {
+    if n.is_finite() {
+        write!(w, "{}", n)
+    } else {
+        w.write_str("null")
+    }
+}
+
+impl fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            JsonValue::Null => write!(f, "null"),
+            JsonValue::Bool(b) => write!(f, "{}", b),
+            JsonValue::Number(n) => write_number(f, n),
+            JsonValue::String(ref s) => write_string(f, s),
+            JsonValue::Array(ref a) => {
+                try!(write!(f, "["));
+                for (i, v) in a.iter().enumerate() {
+                    if i > 0 {
+                        try!(write!(f, ","));
+                    }
+                    try!(write!(f, "{}", v));
+                }
+                write!(f, "]")
+            }
+            JsonValue::Object(ref members) => {
+                try!(write!(f, "{{"));
+                for (i, &(ref k, ref v)) in members.iter().enumerate() {
+                    if i > 0 {
+                        try!(write!(f, ","));
+                    }
+                    try!(write_string(f, k));
+                    try!(write!(f, ":{}", v));
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    s: &'a [u8],
+    pos: usize
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> JsonError {
+        let before = &self.s[..self.pos.min(self.s.len())];
+        let line = before.iter().filter(|&&c| c == b'\n').count() + 1;
+        let column = before.iter().rev().take_while(|&&c| c != b'\n').count() + 1;
+        JsonError::Syntax { line: line, column: column, message: message.to_string() }
+    }
+
+    fn whitespace(&mut self) {
+        while self.pos < self.s.len() && (self.s[self.pos] as char).is_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.whitespace();
+        self.s.get(self.pos).cloned()
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), JsonError> {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", c as char)))
+        }
+    }
+
+    fn literal(&mut self, word: &str, v: JsonValue) -> Result<JsonValue, JsonError> {
+        if self.s[self.pos..].starts_with(word.as_bytes()) {
+            self.pos += word.len();
+            Ok(v)
+        } else {
+            Err(self.error("unexpected character"))
+        }
+    }
+
+    fn value(&mut self) -> Result<JsonValue, JsonError> {
+        match self.peek() {
+            None => Err(self.error("unexpected end of input")),
+            Some(b'n') => self.literal("null", JsonValue::Null),
+            Some(b't') => self.literal("true", JsonValue::Bool(true)),
+            Some(b'f') => self.literal("false", JsonValue::Bool(false)),
+            Some(b'"') => self.string().map(JsonValue::String),
+            Some(b'[') => {
+                self.pos += 1;
+                let mut a = vec![];
+                if self.peek() == Some(b']') {
+                    self.pos += 1;
+                    return Ok(JsonValue::Array(a));
+                }
+                loop {
+                    a.push(try!(self.value()));
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b']') => {
+                            self.pos += 1;
+                            return Ok(JsonValue::Array(a));
+                        }
+                        _ => return Err(self.error("expected ',' or ']'"))
+                    }
+                }
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                let mut members = vec![];
+                if self.peek() == Some(b'}') {
+                    self.pos += 1;
+                    return Ok(JsonValue::Object(members));
+                }
+                loop {
+                    if self.peek() != Some(b'"') {
+                        return Err(self.error("expected a member name"));
+                    }
+                    let k = try!(self.string());
+                    try!(self.expect(b':'));
+                    members.push((k, try!(self.value())));
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b'}') => {
+                            self.pos += 1;
+                            return Ok(JsonValue::Object(members));
+                        }
+                        _ => return Err(self.error("expected ',' or '}'"))
+                    }
+                }
+            }
+            Some(_) => self.number()
+        }
+    }
+
+    fn number(&mut self) -> Result<JsonValue, JsonError> {
+        let start = self.pos;
+        while self.pos < self.s.len() && b"+-0123456789.eE".contains(&self.s[self.pos]) {
+            self.pos += 1;
+        }
+        let text = String::from_utf8_lossy(&self.s[start..self.pos]).into_owned();
+        match text.parse::<f64>() {
+            Ok(n) if !text.is_empty() => Ok(JsonValue::Number(n)),
+            _ => {
+                self.pos = start;
+                Err(self.error("expected a value"))
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        try!(self.expect(b'"'));
+        let mut bytes = vec![];
+        loop {
+            match self.s.get(self.pos).cloned() {
+                None => return Err(self.error("unterminated string")),
+                Some(b'"') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    let c = match self.s.get(self.pos).cloned() {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => {
+                            let hex = String::from_utf8_lossy(
+                                &self.s[self.pos + 1..(self.pos + 5).min(self.s.len())]).into_owned();
+                            let c = u32::from_str_radix(&hex, 16).ok().and_then(::std::char::from_u32);
+                            match c {
+                                Some(c) if hex.len() == 4 => {
+                                    self.pos += 4;
+                                    c
+                                }
+                                _ => return Err(self.error("invalid unicode escape"))
+                            }
+                        }
+                        _ => return Err(self.error("invalid escape"))
+                    };
+                    let mut buf = [0; 4];
+                    bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                    self.pos += 1;
+                }
+                Some(c) => {
+                    bytes.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+        String::from_utf8(bytes).map_err(|_| self.error("invalid UTF-8 in string"))
+    }
+}
+
+/// Writes `tree` as JSON, including its length in `geo`.
+pub fn write_tree<W, P, M>(w: &mut W, tree: &SteinerTree<P>, geo: &M) -> io::Result<()>
+    where W: Write, P: Point, M: MinkowskiSpace<P> {
+
+    let num = |n: P::R| JsonValue::Number(n.into());
+    let dim = tree.nodes().next().map(|n| n.p().dim()).unwrap_or(0);
+
+    try!(writeln!(w, "{{\n  \"dim\": {},\n  \"length\": {},\n  \"nodes\": [", dim, num(tree.len(geo))));
+    for (i, n) in tree.nodes().enumerate() {
+        let coords: Vec<String> = n.p().iter().map(|&c| num(c).to_string()).collect();
+        try!(write!(w, "{}    {{\"id\": {}, \"terminal\": {}, \"coords\": [{}]}}",
+            if i == 0 { "" } else { ",\n" }, n.id(), n.is_terminal(), coords.join(", ")));
+    }
+    try!(write!(w, "\n  ],\n  \"edges\": ["));
+    for (i, e) in tree.edges().enumerate() {
+        let (a, b) = e.nodes();
+        try!(write!(w, "{}[{}, {}]", if i == 0 { "" } else { ", " }, a.id(), b.id()));
+    }
+    writeln!(w, "]\n}}")
+}
+
+/// Reads a tree written by `write_tree`. The tree is rebuilt with
+/// `SteinerTree::new`, so terminals come first, followed by the Steiner
+/// points, each in the order of the file; ids are renumbered accordingly.
+pub fn read_tree<R: Read, P: FromCoords>(r: &mut R) -> Result<SteinerTree<P>, JsonError> {
+    let mut s = String::new();
+    try!(r.read_to_string(&mut s));
+    let doc = try!(JsonValue::parse(&s));
+    tree_from_json(&doc)
+}
+
+/// Builds a tree from a parsed JSON document, see `read_tree`.
+pub fn tree_from_json<P: FromCoords>(doc: &JsonValue) -> Result<SteinerTree<P>, JsonError> {
+    let schema = |m: String| JsonError::Schema(m);
+
+    let nodes = try!(doc.get("nodes").and_then(|n| n.as_array())
+        .ok_or_else(|| schema("missing array \"nodes\"".to_string())));
+    let edges = try!(doc.get("edges").and_then(|e| e.as_array())
+        .ok_or_else(|| schema("missing array \"edges\"".to_string())));
+
+    let (mut ts, mut ss) = (vec![], vec![]);
+    let mut ids = vec![];
+    for (i, n) in nodes.iter().enumerate() {
+        let id = try!(n.get("id").and_then(|id| id.as_usize())
+            .ok_or_else(|| schema(format!("node {} has no valid \"id\"", i))));
+        let terminal = try!(n.get("terminal").and_then(|t| t.as_bool())
+            .ok_or_else(|| schema(format!("node {} has no boolean \"terminal\"", id))));
+        let coords = try!(n.get("coords").and_then(|c| c.as_array())
+            .ok_or_else(|| schema(format!("node {} has no array \"coords\"", id))));
+
+        let mut cs = Vec::with_capacity(coords.len());
+        for c in coords {
+            cs.push(P::R::from(try!(c.as_f64()
+                .ok_or_else(|| schema(format!("node {} has a coordinate that is not a number", id))))));
+        }
+        let p = try!(P::from_coords(&cs)
+            .ok_or_else(|| schema(format!("node {} has {} coordinates, which do not fit the point type",
+                id, cs.len()))));
+
+        if terminal {
+            ts.push(p);
+        } else {
+            ss.push(p);
+        }
+        ids.push((id, terminal));
+    }
+
+    // Terminals first, then Steiner points
+    let mut index = HashMap::new();
+    let (mut t, mut s) = (0, ts.len());
+    for &(id, terminal) in ids.iter() {
+        let i = if terminal { t += 1; t - 1 } else { s += 1; s - 1 };
+        if index.insert(id, i).is_some() {
+            return Err(schema(format!("node id {} is used twice", id)));
+        }
+    }
+
+    let mut es = Vec::with_capacity(edges.len());
+    for e in edges {
+        let ends = e.as_array().and_then(|e|
+            if e.len() == 2 { Some((e[0].as_usize(), e[1].as_usize())) } else { None });
+        let (a, b) = match ends {
+            Some((Some(a), Some(b))) => (a, b),
+            _ => return Err(schema(format!("edge {} is not a pair of node ids", e)))
+        };
+
+        match (index.get(&a), index.get(&b)) {
+            (Some(&a), Some(&b)) if a != b => es.push((a, b)),
+            (Some(_), Some(_)) => return Err(schema(format!("edge {} is a loop", e))),
+            _ => return Err(schema(format!("edge {} refers to an unknown node", e)))
+        }
+    }
+
+    Ok(SteinerTree::new(&ts, &ss, &es))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use generators::Generator;
+    use geo::points::{Point2d, Point3d};
+    use geo::spaces::EuclideanSpace;
+    use algorithms::steinerbnb::SteinerBnB;
+
+    fn syntax_at(s: &str) -> (usize, usize) {
+        match JsonValue::parse(s) {
+            Err(JsonError::Syntax { line, column, .. }) => (line, column),
+            r => panic!("expected a syntax error, got {:?}", r)
+        }
+    }
+
+    fn schema_error(s: &str) {
+        match read_tree::<_, Point2d<f64>>(&mut s.as_bytes()) {
+            Err(JsonError::Schema(_)) => { }
+            r => panic!("expected a schema error, got {:?}", r)
+        }
+    }
+
+    /// The edges of `tree` as sorted pairs of ids.
+    fn edges<P: Point>(tree: &SteinerTree<P>) -> Vec<(usize, usize)> {
+        let mut es: Vec<(usize, usize)> = tree.edges().map(|e| {
+            let (a, b) = e.nodes();
+            (a.id().min(b.id()), a.id().max(b.id()))
+        }).collect();
+        es.sort();
+        es
+    }
+
+    /// The terminals and the edges of `tree` by their coordinates, sorted.
+    fn geometry<P: Point>(tree: &SteinerTree<P>) -> (Vec<Vec<f64>>, Vec<(Vec<f64>, Vec<f64>)>) {
+        let coords = |p: &P| -> Vec<f64> { p.iter().map(|&c| c.into()).collect() };
+        let mut ts: Vec<Vec<f64>> = tree.terminals().map(|t| coords(t.p())).collect();
+        let mut es: Vec<(Vec<f64>, Vec<f64>)> = tree.edges().map(|e| {
+            let (a, b) = e.nodes();
+            let (a, b) = (coords(a.p()), coords(b.p()));
+            if a < b { (a, b) } else { (b, a) }
+        }).collect();
+        ts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        es.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        (ts, es)
+    }
+
+    #[test]
+    fn parses_json() {
+        let v = JsonValue::parse(" {\"a\": [1, -2.5e3, true, null], \"b\\n\\u00e9\": {}, \"c\": \"x\\\"y\"}\n").unwrap();
+        assert_eq!(v.get("a").unwrap().as_array().unwrap(), &[JsonValue::Number(1.0),
+            JsonValue::Number(-2500.0), JsonValue::Bool(true), JsonValue::Null]);
+        assert_eq!(v.get("b\n\u{e9}"), Some(&JsonValue::Object(vec![])));
+        assert_eq!(v.get("c"), Some(&JsonValue::String("x\"y".to_string())));
+        assert_eq!(JsonValue::parse(&v.to_string()).unwrap(), v);
+
+        assert_eq!(JsonValue::Number(3.0).as_usize(), Some(3));
+        assert_eq!(JsonValue::Number(3.5).as_usize(), None);
+        assert_eq!(JsonValue::Number(::std::f64::NAN).to_string(), "null");
+    }
+
+    #[test]
+    fn reports_syntax_errors_with_their_position() {
+        assert_eq!(syntax_at("[1,\n 2,,3]"), (2, 4));
+        assert_eq!(syntax_at("{\"a\" 1}"), (1, 6));
+        assert_eq!(syntax_at("\"abc"), (1, 5));
+        assert_eq!(syntax_at("[1] x"), (1, 5));
+        assert_eq!(syntax_at("tru"), (1, 1));
+        assert_eq!(syntax_at(""), (1, 1));
+    }
+
+    #[test]
+    fn trees_round_trip() {
+        let terminals: Vec<Point3d<f64>> = Generator::new(3, 3).uniform(6);
+        let tree = SteinerBnB::default().find(terminals, &EuclideanSpace);
+        let mut buf = vec![];
+        write_tree(&mut buf, &tree, &EuclideanSpace).unwrap();
+
+        // Nodes may be renumbered, the geometry is kept
+        let read: SteinerTree<Point3d<f64>> = read_tree(&mut &buf[..]).unwrap();
+        assert_eq!(read.node_count(), tree.node_count());
+        assert_eq!(geometry(&read), geometry(&tree));
+        assert_eq!(read.len(&EuclideanSpace), tree.len(&EuclideanSpace));
+
+        let doc = JsonValue::parse(&String::from_utf8(buf).unwrap()).unwrap();
+        assert_eq!(doc.get("dim").and_then(|d| d.as_usize()), Some(3));
+        assert_eq!(doc.get("length").and_then(|l| l.as_f64()), Some(tree.len(&EuclideanSpace)));
+    }
+
+    #[test]
+    fn terminals_are_renumbered_first() {
+        let tree: SteinerTree<Point2d<f64>> = read_tree(&mut "{\"nodes\": [
+            {\"id\": 7, \"terminal\": false, \"coords\": [0.5, 0.3]},
+            {\"id\": 2, \"terminal\": true, \"coords\": [0, 0]},
+            {\"id\": 5, \"terminal\": true, \"coords\": [1, 0]},
+            {\"id\": 0, \"terminal\": true, \"coords\": [0.5, 1]}
+        ], \"edges\": [[7, 2], [5, 7], [7, 0]]}".as_bytes()).unwrap();
+
+        assert_eq!(tree.terminals().count(), 3);
+        assert_eq!(*tree.i(3).p(), Point2d::new([0.5, 0.3]));
+        assert_eq!(*tree.i(1).p(), Point2d::new([1.0, 0.0]));
+        assert_eq!(edges(&tree), vec![(0, 3), (1, 3), (2, 3)]);
+    }
+
+    #[test]
+    fn rejects_documents_that_are_not_trees() {
+        let node = |id: usize| format!("{{\"id\": {}, \"terminal\": true, \"coords\": [{}, 0]}}", id, id);
+        let tree = |edges: &str| format!("{{\"nodes\": [{}, {}], \"edges\": [{}]}}", node(0), node(1), edges);
+        schema_error(&tree("[0, 2]"));
+        schema_error(&tree("[1, 1]"));
+        schema_error(&tree("[0]"));
+        schema_error(&format!("{{\"nodes\": [{}, {}], \"edges\": []}}", node(1), node(1)));
+        schema_error("{\"nodes\": [{\"id\": 0, \"terminal\": true, \"coords\": [1, 2, 3]}], \"edges\": []}");
+        schema_error("{\"nodes\": [{\"id\": 0, \"coords\": [1, 2]}], \"edges\": []}");
+        schema_error("{\"edges\": []}");
+        assert!(read_tree::<_, Point2d<f64>>(&mut tree("[0, 1]").as_bytes()).is_ok());
+    }
+}
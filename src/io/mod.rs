@@ -0,0 +1,6 @@
+pub mod stp;
+pub mod orlib;
+pub mod svg;
+pub mod json;
+pub mod dot;
+pub mod points;
@@ -0,0 +1,388 @@
+//! Reading Beasley's OR-Library `estein` files of Euclidean Steiner tree
+//! instances, and checking SMT algorithms against reference optima.
+//!
+//! An `estein` file starts with the number of instances, followed by each
+//! instance as its number of points and then the x and y coordinate of every
+//! point. Numbers are separated by arbitrary whitespace.
+
+use std::error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Lines};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use std::vec;
+
+use traits::*;
+use geo::points::{Point2d};
+
+#[derive(Debug)]
+pub enum OrLibError {
+    Io(io::Error),
+    /// A token that is not the expected number, with its 1-based line number
+    Syntax { line: usize, message: String },
+    /// The file ended inside instance `instance` (counted from 1)
+    UnexpectedEof { instance: usize },
+    /// The number of reference optima does not match the number of instances
+    OptimaCount { instances: usize, optima: usize }
+}
+
+impl fmt::Display for OrLibError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            OrLibError::Io(ref e) => write!(f, "I/O error: {}", e),
+            OrLibError::Syntax { line, ref message } =>
+                write!(f, "Syntax error on line {}: {}", line, message),
+            OrLibError::UnexpectedEof { instance } =>
+                write!(f, "The file ended in the middle of instance {}", instance),
+            OrLibError::OptimaCount { instances, optima } =>
+                write!(f, "{} reference optima given for {} instances", optima, instances)
+        }
+    }
+}
+
+impl error::Error for OrLibError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            OrLibError::Io(ref e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+impl From<io::Error> for OrLibError {
+    fn from(e: io::Error) -> Self {
+        OrLibError::Io(e)
+    }
+}
+
+/// Whitespace separated tokens with their line numbers.
+struct Tokens<B> {
+    lines: Lines<B>,
+    line: usize,
+    current: vec::IntoIter<String>
+}
+
+impl<B: BufRead> Tokens<B> {
+    fn new(r: B) -> Self {
+        Tokens {
+            lines: r.lines(),
+            line: 0,
+            current: vec![].into_iter()
+        }
+    }
+
+    fn next(&mut self) -> Result<Option<String>, OrLibError> {
+        loop {
+            if let Some(t) = self.current.next() {
+                return Ok(Some(t));
+            }
+
+            match self.lines.next() {
+                Some(line) => {
+                    let line = try!(line);
+                    self.line += 1;
+                    self.current = line.split_whitespace()
+                        .map(|s| s.to_string())
+                        .collect::<Vec<_>>()
+                        .into_iter();
+                }
+                None => return Ok(None)
+            }
+        }
+    }
+
+    fn parse<T: ::std::str::FromStr>(&mut self, what: &str, instance: usize) -> Result<T, OrLibError> {
+        match try!(self.next()) {
+            Some(t) => t.parse().map_err(|_| OrLibError::Syntax {
+                line: self.line,
+                message: format!("expected {}, found '{}'", what, t)
+            }),
+            None => Err(OrLibError::UnexpectedEof { instance: instance })
+        }
+    }
+}
+
+/// Iterator over the instances of an `estein` file.
+pub struct EsteinReader<B> {
+    tokens: Tokens<B>,
+    instances: usize,
+    read: usize
+}
+
+impl EsteinReader<BufReader<File>> {
+    pub fn open<Q: AsRef<Path>>(path: Q) -> Result<Self, OrLibError> {
+        let file = try!(File::open(path));
+        Self::new(BufReader::new(file))
+    }
+}
+
+impl<B: BufRead> EsteinReader<B> {
+    /// Reads the header of the file; the instances are read as the iterator
+    /// advances.
+    pub fn new(r: B) -> Result<Self, OrLibError> {
+        let mut tokens = Tokens::new(r);
+        let instances = try!(tokens.parse("the number of instances", 1));
+        Ok(EsteinReader {
+            tokens: tokens,
+            instances: instances,
+            read: 0
+        })
+    }
+
+    /// The number of instances announced by the file.
+    pub fn instances(&self) -> usize {
+        self.instances
+    }
+
+    fn read_instance(&mut self) -> Result<Vec<Point2d<f64>>, OrLibError> {
+        let instance = self.read;
+        let n: usize = try!(self.tokens.parse("the number of points", instance));
+        let mut points = Vec::with_capacity(n);
+        for _ in 0..n {
+            let x = try!(self.tokens.parse("an x coordinate", instance));
+            let y = try!(self.tokens.parse("a y coordinate", instance));
+            points.push(Point2d::new([x, y]));
+        }
+        Ok(points)
+    }
+}
+
+impl<B: BufRead> Iterator for EsteinReader<B> {
+    type Item = Result<Vec<Point2d<f64>>, OrLibError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.read == self.instances {
+            return None;
+        }
+
+        self.read += 1;
+        let res = self.read_instance();
+        if res.is_err() {
+            // Stop after the first error, the rest of the file can't be trusted
+            self.read = self.instances;
+        }
+        Some(res)
+    }
+
+    /// Exact, unless an error ends the iteration early.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.instances - self.read, Some(self.instances - self.read))
+    }
+}
+
+/// Reads reference optima, one number per instance in order. Numbers are
+/// separated by whitespace and lines starting with `#` are ignored.
+pub fn read_optima<B: BufRead>(r: B) -> Result<Vec<f64>, OrLibError> {
+    let mut optima = vec![];
+    for (i, line) in r.lines().enumerate() {
+        let line = try!(line);
+        if line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        for t in line.split_whitespace() {
+            optima.push(try!(t.parse().map_err(|_| OrLibError::Syntax {
+                line: i + 1,
+                message: format!("expected an optimal length, found '{}'", t)
+            })));
+        }
+    }
+    Ok(optima)
+}
+
+/// Result of one instance checked by `verify`.
+#[derive(Debug, Clone)]
+pub struct Verification {
+    pub instance: usize,
+    pub terminals: usize,
+    pub length: f64,
+    pub optimum: f64,
+    pub time: Duration
+}
+
+impl Verification {
+    /// `(length - optimum) / optimum`; negative if the tree beats the
+    /// reference, which usually means the reference is rounded.
+    pub fn relative_error(&self) -> f64 {
+        (self.length - self.optimum) / self.optimum
+    }
+}
+
+impl fmt::Display for Verification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn printdur(dur: &Duration) -> f64 {
+            (dur.as_secs() as f64) + (dur.subsec_nanos() as f64) / 1000000000.0
+        }
+
+        write!(f, "Instance {} ({} terminals): length {}, optimum {}, relative error {:e}, time {}",
+            self.instance, self.terminals, self.length, self.optimum,
+            self.relative_error(), printdur(&self.time))
+    }
+}
+
+/// The results of `verify` for every instance of a file.
+#[derive(Debug, Clone)]
+pub struct VerificationReport {
+    pub results: Vec<Verification>
+}
+
+impl VerificationReport {
+    /// The largest relative error, by absolute value.
+    pub fn max_relative_error(&self) -> f64 {
+        self.results.iter().fold(0.0, |m: f64, r| m.max(r.relative_error().abs()))
+    }
+
+    /// The instances whose relative error exceeds `tolerance` by absolute
+    /// value.
+    pub fn failures(&self, tolerance: f64) -> Vec<&Verification> {
+        self.results.iter().filter(|r| r.relative_error().abs() > tolerance).collect()
+    }
+}
+
+impl fmt::Display for VerificationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for r in self.results.iter() {
+            try!(writeln!(f, "{}", r));
+        }
+        writeln!(f, "Largest relative error over {} instances: {:e}",
+            self.results.len(), self.max_relative_error())
+    }
+}
+
+/// Runs `smt` on every instance and compares the tree lengths with the
+/// reference `optima`, given in the same order as the instances. If the
+/// size hint of the instances rules out one optimum per instance, e.g.
+/// because `EsteinReader::instances` differs from `optima.len()`, the
+/// mismatch is reported before any instance is solved.
+pub fn verify<S, M, I>(smt: &mut S, instances: I, optima: &[f64], geo: &M)
+    -> Result<VerificationReport, OrLibError>
+    where S: SMT<Point2d<f64>, M>,
+          M: MinkowskiSpace<Point2d<f64>>,
+          I: IntoIterator<Item=Result<Vec<Point2d<f64>>, OrLibError>> {
+
+    let instances = instances.into_iter();
+    let (lower, upper) = instances.size_hint();
+    if lower > optima.len() {
+        return Err(OrLibError::OptimaCount { instances: lower, optima: optima.len() });
+    }
+    if let Some(n) = upper.filter(|&n| n < optima.len()) {
+        return Err(OrLibError::OptimaCount { instances: n, optima: optima.len() });
+    }
+
+    let mut results = vec![];
+    for (i, instance) in instances.enumerate() {
+        let instance = try!(instance);
+        let optimum = match optima.get(i) {
+            Some(&o) => o,
+            None => return Err(OrLibError::OptimaCount { instances: i + 1, optima: optima.len() })
+        };
+
+        let terminals = instance.len();
+        let start = Instant::now();
+        let tree = smt.find(instance, geo);
+        results.push(Verification {
+            instance: i + 1,
+            terminals: terminals,
+            length: tree.len(geo),
+            optimum: optimum,
+            time: Instant::now() - start
+        });
+    }
+
+    if results.len() != optima.len() {
+        return Err(OrLibError::OptimaCount { instances: results.len(), optima: optima.len() });
+    }
+
+    Ok(VerificationReport { results: results })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::spaces::EuclideanSpace;
+    use algorithms::steinerbnb::SteinerBnB;
+
+    const ESTEIN: &'static str = "2
+3
+0.0 0.0  1.0 0.0
+0.5 0.8660254037844386
+4
+0 0 1 0 1 1
+0 1
+";
+
+    fn reader(s: &str) -> EsteinReader<&[u8]> {
+        EsteinReader::new(s.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn reads_every_instance() {
+        let r = reader(ESTEIN);
+        assert_eq!(r.instances(), 2);
+        assert_eq!(r.size_hint(), (2, Some(2)));
+
+        let instances: Vec<Vec<Point2d<f64>>> = r.map(|i| i.unwrap()).collect();
+        assert_eq!(instances.len(), 2);
+        assert_eq!(instances[0][2], Point2d::new([0.5, 0.8660254037844386]));
+        assert_eq!(instances[1][3], Point2d::new([0.0, 1.0]));
+    }
+
+    #[test]
+    fn reports_malformed_files() {
+        let broken = ESTEIN.replace("0 0 1 0 1 1", "0 0 1 x 1 1");
+        let mut r = reader(&broken);
+        assert!(r.next().unwrap().is_ok());
+        match r.next() {
+            Some(Err(OrLibError::Syntax { line: 6, .. })) => { }
+            res => panic!("expected a syntax error, got {:?}", res)
+        }
+        assert!(r.next().is_none());
+
+        let mut r = reader("1\n3\n0 0 1 0\n");
+        match r.next() {
+            Some(Err(OrLibError::UnexpectedEof { instance: 1 })) => { }
+            res => panic!("expected the end of the file, got {:?}", res)
+        }
+        assert!(EsteinReader::new("".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn optima_skip_comments() {
+        let optima = read_optima("# optima\n1.5 2\n  # more\n3e0\n".as_bytes()).unwrap();
+        assert_eq!(optima, vec![1.5, 2.0, 3.0]);
+        match read_optima("1\n2 x\n".as_bytes()) {
+            Err(OrLibError::Syntax { line: 2, .. }) => { }
+            res => panic!("expected a syntax error, got {:?}", res)
+        }
+    }
+
+    #[test]
+    fn verify_compares_with_the_optima() {
+        let optima = [2.0 * 0.8660254037844386, 1.0 + 3f64.sqrt()];
+        let report = verify(&mut SteinerBnB::default(), reader(ESTEIN), &optima, &EuclideanSpace).unwrap();
+        assert_eq!(report.results.len(), 2);
+        assert_eq!(report.results[1].terminals, 4);
+        assert!(report.max_relative_error() < 1e-6);
+        assert!(report.failures(1e-6).is_empty());
+        assert_eq!(report.failures(-1.0).len(), 2);
+    }
+
+    #[test]
+    fn verify_checks_the_optima_count_first() {
+        for optima in [vec![1.0], vec![1.0, 2.0, 3.0]].iter() {
+            let instances = reader(ESTEIN).inspect(|_| panic!("an instance was solved"));
+            match verify(&mut SteinerBnB::default(), instances, optima, &EuclideanSpace) {
+                Err(OrLibError::OptimaCount { instances: 2, .. }) => { }
+                res => panic!("expected an optima count error, got {:?}", res)
+            }
+        }
+
+        // Without a size hint the count is checked as the instances are read
+        let instances = reader(ESTEIN).filter(|_| true);
+        match verify(&mut SteinerBnB::default(), instances, &[1.0, 2.0, 3.0], &EuclideanSpace) {
+            Err(OrLibError::OptimaCount { instances: 2, optima: 3 }) => { }
+            res => panic!("expected an optima count error, got {:?}", res)
+        }
+    }
+}
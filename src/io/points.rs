@@ -0,0 +1,99 @@
+//! Plain point files: one point per line, given by its coordinates separated
+//! by whitespace. Empty lines and lines starting with `#` are ignored.
+
+use std::error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+use traits::*;
+
+#[derive(Debug)]
+pub enum PointFileError {
+    Io(io::Error),
+    /// A token that is not a number, with its 1-based line number
+    Syntax { line: usize, message: String },
+    /// A point whose dimension differs from the first point
+    Dimension { line: usize, expected: usize, found: usize },
+    /// The coordinates do not fit the point type asked for
+    PointType(usize)
+}
+
+impl fmt::Display for PointFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PointFileError::Io(ref e) => write!(f, "I/O error: {}", e),
+            PointFileError::Syntax { line, ref message } =>
+                write!(f, "Syntax error on line {}: {}", line, message),
+            PointFileError::Dimension { line, expected, found } =>
+                write!(f, "The point on line {} has {} coordinates, expected {}", line, found, expected),
+            PointFileError::PointType(dim) =>
+                write!(f, "Points with {} coordinates do not fit the requested point type", dim)
+        }
+    }
+}
+
+impl error::Error for PointFileError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            PointFileError::Io(ref e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+impl From<io::Error> for PointFileError {
+    fn from(e: io::Error) -> Self {
+        PointFileError::Io(e)
+    }
+}
+
+pub fn open_points<P: FromCoords, Q: AsRef<Path>>(path: Q) -> Result<Vec<P>, PointFileError> {
+    let file = try!(File::open(path));
+    read_points(BufReader::new(file))
+}
+
+/// Reads the points of a point file, e.g. as `Vec<Point2d<f64>>` or as
+/// `Vec<PointNd<f64>>` for files of any dimension.
+pub fn read_points<P: FromCoords, B: BufRead>(r: B) -> Result<Vec<P>, PointFileError> {
+    let mut points = vec![];
+    let mut dim = None;
+
+    for (i, line) in r.lines().enumerate() {
+        let line = try!(line);
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let cs: Vec<P::R> = try!(line.split_whitespace()
+            .map(|w| w.parse::<f64>()
+                .map(P::R::from)
+                .map_err(|_| PointFileError::Syntax {
+                    line: i + 1,
+                    message: format!("'{}' is not a number", w)
+                }))
+            .collect());
+
+        match dim {
+            None => dim = Some(cs.len()),
+            Some(d) if d != cs.len() =>
+                return Err(PointFileError::Dimension { line: i + 1, expected: d, found: cs.len() }),
+            _ => { }
+        }
+
+        points.push(try!(P::from_coords(&cs).ok_or(PointFileError::PointType(cs.len()))));
+    }
+
+    Ok(points)
+}
+
+/// Writes `points` in the format read by `read_points`.
+pub fn write_points<W: Write, P: Point>(w: &mut W, points: &[P]) -> io::Result<()> {
+    for p in points {
+        let cs: Vec<String> = p.iter().map(|c| c.to_string()).collect();
+        try!(writeln!(w, "{}", cs.join(" ")));
+    }
+    Ok(())
+}
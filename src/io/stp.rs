@@ -0,0 +1,449 @@
+//! Reading and writing geometric instances in the SteinLib STP format.
+//!
+//! Only the parts of the format used by geometric instances are interpreted:
+//! the name in the `Comment` section, the `Terminals` section and the
+//! `Coordinates` section with its `DD`, `DDD`, ... lines. Other sections,
+//! such as the edges of the `Graph` section, are skipped when reading.
+
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+use traits::*;
+use steinertree::{SteinerTree};
+
+const MAGIC: &'static str = "33D32945";
+
+#[derive(Debug)]
+pub enum StpError {
+    Io(io::Error),
+    /// A line that could not be parsed, with its 1-based line number
+    Syntax { line: usize, message: String },
+    /// A terminal without a line in the `Coordinates` section
+    MissingCoordinates(usize),
+    /// Coordinates whose dimension differs from earlier coordinates
+    Dimension { node: usize, expected: usize, found: usize },
+    /// The coordinates do not fit the point type asked for
+    PointType(usize)
+}
+
+impl fmt::Display for StpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            StpError::Io(ref e) => write!(f, "I/O error: {}", e),
+            StpError::Syntax { line, ref message } =>
+                write!(f, "Syntax error on line {}: {}", line, message),
+            StpError::MissingCoordinates(node) =>
+                write!(f, "Terminal {} has no coordinates", node),
+            StpError::Dimension { node, expected, found } =>
+                write!(f, "Node {} has {} coordinates, expected {}", node, found, expected),
+            StpError::PointType(dim) =>
+                write!(f, "Points with {} coordinates do not fit the requested point type", dim)
+        }
+    }
+}
+
+impl error::Error for StpError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            StpError::Io(ref e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+impl From<io::Error> for StpError {
+    fn from(e: io::Error) -> Self {
+        StpError::Io(e)
+    }
+}
+
+/// A geometric STP instance.
+#[derive(Debug, Clone)]
+pub struct StpInstance {
+    name: Option<String>,
+    dim: usize,
+    coordinates: HashMap<usize, Vec<f64>>,
+    terminals: Vec<usize>
+}
+
+impl StpInstance {
+    /// Instance named `name` with the given points as terminals, numbered
+    /// from 1 in order.
+    pub fn new<P: Point>(name: Option<String>, points: &[P]) -> Self {
+        StpInstance {
+            name: name,
+            dim: points.first().map(|p| p.dim()).unwrap_or(0),
+            coordinates: points.iter().enumerate()
+                .map(|(i, p)| (i + 1, p.iter().map(|&c| c.into()).collect()))
+                .collect(),
+            terminals: (1..points.len() + 1).collect()
+        }
+    }
+
+    pub fn open<Q: AsRef<Path>>(path: Q) -> Result<Self, StpError> {
+        let file = try!(File::open(path));
+        Self::read(BufReader::new(file))
+    }
+
+    pub fn read<B: BufRead>(r: B) -> Result<Self, StpError> {
+        let mut name = None;
+        let mut dim = 0;
+        let mut coordinates = HashMap::new();
+        let mut terminals = vec![];
+        let mut terminal_count = None;
+        let mut has_terminals = false;
+        let mut section: Option<String> = None;
+        let mut seen_magic = false;
+
+        for (i, line) in r.lines().enumerate() {
+            let line = try!(line);
+            let lineno = i + 1;
+            let syntax = |message: String| StpError::Syntax { line: lineno, message: message };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if !seen_magic {
+                if !line.to_uppercase().starts_with(MAGIC) {
+                    return Err(syntax(format!("expected the STP header {}", MAGIC)));
+                }
+                seen_magic = true;
+                continue;
+            }
+
+            let mut words = line.split_whitespace();
+            let keyword = words.next().unwrap().to_lowercase();
+
+            let current = match section {
+                None => {
+                    match keyword.as_ref() {
+                        "section" => match words.next() {
+                            Some(s) => {
+                                let s = s.to_lowercase();
+                                if s == "terminals" {
+                                    has_terminals = true;
+                                }
+                                section = Some(s);
+                            }
+                            None => return Err(syntax("missing section name".to_string()))
+                        },
+                        "eof" => break,
+                        _ => return Err(syntax(format!("unexpected '{}' outside of a section", line)))
+                    }
+                    continue;
+                }
+                Some(ref s) => s.clone()
+            };
+
+            if keyword == "end" {
+                section = None;
+                continue;
+            }
+
+            let parse_id = |s: Option<&str>| -> Result<usize, StpError> {
+                match s.map(|s| s.parse::<usize>()) {
+                    Some(Ok(id)) if id > 0 => Ok(id),
+                    _ => Err(syntax("expected a positive node number".to_string()))
+                }
+            };
+
+            match (current.as_ref(), keyword.as_ref()) {
+                ("comment", "name") => {
+                    let rest = line[4..].trim();
+                    name = Some(rest.trim_matches('"').to_string());
+                }
+                ("terminals", "terminals") => {
+                    terminal_count = Some((try!(words.next()
+                        .and_then(|s| s.parse::<usize>().ok())
+                        .ok_or_else(|| syntax("expected the number of terminals".to_string()))), lineno));
+                }
+                ("terminals", "t") => terminals.push(try!(parse_id(words.next()))),
+                ("coordinates", k) if k.chars().all(|c| c == 'd') => {
+                    let id = try!(parse_id(words.next()));
+                    let cs: Vec<f64> = try!(words
+                        .map(|w| w.parse::<f64>()
+                            .map_err(|_| syntax(format!("'{}' is not a number", w))))
+                        .collect());
+
+                    if cs.len() != k.len() {
+                        return Err(syntax(format!("'{}' needs {} coordinates, found {}",
+                            k.to_uppercase(), k.len(), cs.len())));
+                    }
+                    if dim == 0 {
+                        dim = k.len();
+                    } else if dim != k.len() {
+                        return Err(StpError::Dimension { node: id, expected: dim, found: k.len() });
+                    }
+                    if coordinates.insert(id, cs).is_some() {
+                        return Err(syntax(format!("node {} has coordinates twice", id)));
+                    }
+                }
+                ("coordinates", _) => {
+                    return Err(syntax(format!("unexpected '{}' in the Coordinates section", line)));
+                }
+                _ => { }
+            }
+        }
+
+        if !seen_magic {
+            return Err(StpError::Syntax { line: 1, message: format!("expected the STP header {}", MAGIC) });
+        }
+
+        if let Some((count, line)) = terminal_count {
+            if count != terminals.len() {
+                return Err(StpError::Syntax { line: line, message: format!(
+                    "{} terminals announced but {} listed", count, terminals.len()) });
+            }
+        }
+
+        // Instances without a Terminals section are all terminals
+        if !has_terminals {
+            terminals = coordinates.keys().cloned().collect();
+            terminals.sort();
+        }
+
+        for &t in terminals.iter() {
+            if !coordinates.contains_key(&t) {
+                return Err(StpError::MissingCoordinates(t));
+            }
+        }
+
+        Ok(StpInstance {
+            name: name,
+            dim: dim,
+            coordinates: coordinates,
+            terminals: terminals
+        })
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_ref().map(|s| &s[..])
+    }
+
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    /// Node numbers of the terminals in the order they were listed.
+    pub fn terminal_ids(&self) -> &[usize] {
+        &self.terminals[..]
+    }
+
+    /// The terminals as points, e.g. `Vec<Point2d<f64>>` or
+    /// `Vec<PointNd<f64>>`.
+    pub fn terminals<P: FromCoords>(&self) -> Result<Vec<P>, StpError> {
+        self.terminals.iter().map(|t| {
+            let cs: Vec<P::R> = self.coordinates[t].iter().map(|&c| P::R::from(c)).collect();
+            P::from_coords(&cs).ok_or(StpError::PointType(cs.len()))
+        }).collect()
+    }
+
+    /// Writes the instance as an STP file with a `Terminals` and a
+    /// `Coordinates` section.
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        try!(write_header(w, self.name(), None));
+
+        let mut nodes: Vec<&usize> = self.coordinates.keys().collect();
+        nodes.sort();
+
+        try!(writeln!(w, "SECTION Graph"));
+        try!(writeln!(w, "Nodes {}", nodes.len()));
+        try!(writeln!(w, "Edges 0"));
+        try!(writeln!(w, "END\n"));
+
+        try!(write_terminals(w, self.terminals.iter().cloned()));
+
+        try!(writeln!(w, "SECTION Coordinates"));
+        for &id in nodes {
+            try!(write_coords(w, id, self.coordinates[&id].iter()));
+        }
+        try!(writeln!(w, "END\n"));
+        writeln!(w, "EOF")
+    }
+}
+
+fn write_header<W: Write>(w: &mut W, name: Option<&str>, remark: Option<&str>) -> io::Result<()> {
+    try!(writeln!(w, "{} STP File, STP Format Version 1.0\n", MAGIC));
+    try!(writeln!(w, "SECTION Comment"));
+    if let Some(name) = name {
+        try!(writeln!(w, "Name \"{}\"", name));
+    }
+    if let Some(remark) = remark {
+        try!(writeln!(w, "Remark \"{}\"", remark));
+    }
+    writeln!(w, "END\n")
+}
+
+fn write_terminals<W: Write, I: ExactSizeIterator<Item=usize>>(w: &mut W, ts: I) -> io::Result<()> {
+    try!(writeln!(w, "SECTION Terminals"));
+    try!(writeln!(w, "Terminals {}", ts.len()));
+    for t in ts {
+        try!(writeln!(w, "T {}", t));
+    }
+    writeln!(w, "END\n")
+}
+
+fn write_coords<'a, W: Write, R: 'a + fmt::Display, I: Iterator<Item=&'a R>>(w: &mut W, id: usize, cs: I)
+    -> io::Result<()> {
+
+    let cs: Vec<&R> = cs.collect();
+    try!(write!(w, "{} {}", "D".repeat(cs.len()), id));
+    for c in cs {
+        try!(write!(w, " {}", c));
+    }
+    writeln!(w)
+}
+
+/// Writes a Steiner tree as an STP solution. Node `i` of the tree becomes
+/// node `i+1` of the file; the terminals are listed in the `Terminals`
+/// section, the Steiner points are the remaining nodes of the
+/// `Coordinates` section and the edges are weighted by their length in `geo`.
+pub fn write_tree<W, P, M>(w: &mut W, tree: &SteinerTree<P>, geo: &M, name: Option<&str>) -> io::Result<()>
+    where W: Write, P: Point, M: MinkowskiSpace<P> {
+
+    try!(write_header(w, name, Some(&format!("Steiner tree of length {}", tree.len(geo)))));
+
+    try!(writeln!(w, "SECTION Graph"));
+    try!(writeln!(w, "Nodes {}", tree.node_count()));
+    try!(writeln!(w, "Edges {}", tree.edges().count()));
+    for e in tree.edges() {
+        let (a, b) = e.nodes();
+        try!(writeln!(w, "E {} {} {}", a.id() + 1, b.id() + 1, e.len(geo)));
+    }
+    try!(writeln!(w, "END\n"));
+
+    let terminals: Vec<usize> = tree.nodes().filter(|n| n.is_terminal()).map(|n| n.id() + 1).collect();
+    try!(write_terminals(w, terminals.into_iter()));
+
+    try!(writeln!(w, "SECTION Coordinates"));
+    for n in tree.nodes() {
+        try!(write_coords(w, n.id() + 1, n.p().iter()));
+    }
+    try!(writeln!(w, "END\n"));
+    writeln!(w, "EOF")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::points::{Point2d, Point3d};
+    use geo::spaces::EuclideanSpace;
+
+    const SQUARE: &'static str = "33D32945 STP File, STP Format Version 1.0
+
+SECTION Comment
+Name    \"square\"
+END
+
+SECTION Graph
+Nodes 5
+Edges 1
+E 1 2 1.0
+END
+
+SECTION Terminals
+Terminals 4
+T 1
+T 2
+T 4
+T 5
+END
+
+SECTION Coordinates
+DD 1 0 0
+DD 2 1 0
+DD 3 0.5 0.5
+DD 4 1 1
+DD 5 0 1
+END
+
+EOF
+";
+
+    fn read(s: &str) -> Result<StpInstance, StpError> {
+        StpInstance::read(s.as_bytes())
+    }
+
+    fn syntax_line(r: Result<StpInstance, StpError>) -> usize {
+        match r {
+            Err(StpError::Syntax { line, .. }) => line,
+            r => panic!("expected a syntax error, got {:?}", r)
+        }
+    }
+
+    #[test]
+    fn reads_a_geometric_instance() {
+        let inst = read(SQUARE).unwrap();
+        assert_eq!(inst.name(), Some("square"));
+        assert_eq!(inst.dim(), 2);
+        assert_eq!(inst.terminal_ids(), &[1, 2, 4, 5]);
+        let ts: Vec<Point2d<f64>> = inst.terminals().unwrap();
+        assert_eq!(ts, vec![Point2d::new([0.0, 0.0]), Point2d::new([1.0, 0.0]),
+                            Point2d::new([1.0, 1.0]), Point2d::new([0.0, 1.0])]);
+    }
+
+    #[test]
+    fn all_nodes_are_terminals_without_a_terminals_section() {
+        let inst = read("33d32945\nSection Coordinates\nDDD 2 1 2 3\nddd 1 4 5 6\nEnd\nEOF\n").unwrap();
+        assert_eq!(inst.terminal_ids(), &[1, 2]);
+        let ts: Vec<Point3d<f64>> = inst.terminals().unwrap();
+        assert_eq!(ts[0], Point3d::new([4.0, 5.0, 6.0]));
+        match inst.terminals::<Point2d<f64>>() {
+            Err(StpError::PointType(3)) => { }
+            r => panic!("expected a point type error, got {:?}", r)
+        }
+    }
+
+    #[test]
+    fn reports_malformed_instances() {
+        assert_eq!(syntax_line(read("SECTION Coordinates\nEND\n")), 1);
+        assert_eq!(syntax_line(read("")), 1);
+        assert_eq!(syntax_line(read(&SQUARE.replace("Terminals 4", "Terminals 3"))), 14);
+        assert_eq!(syntax_line(read(&SQUARE.replace("DD 3 0.5 0.5", "DD 3 0.5"))), 24);
+        assert_eq!(syntax_line(read(&SQUARE.replace("DD 3 0.5 0.5", "DD 3 0.5 x"))), 24);
+        assert_eq!(syntax_line(read(&SQUARE.replace("DD 3", "DD 2"))), 24);
+        assert_eq!(syntax_line(read(&SQUARE.replace("T 4", "T 0"))), 17);
+
+        match read(&SQUARE.replace("DD 4 1 1\n", "")) {
+            Err(StpError::MissingCoordinates(4)) => { }
+            r => panic!("expected missing coordinates, got {:?}", r)
+        }
+        match read(&SQUARE.replace("DD 3 0.5 0.5", "DDD 3 0.5 0.5 0")) {
+            Err(StpError::Dimension { node: 3, expected: 2, found: 3 }) => { }
+            r => panic!("expected a dimension error, got {:?}", r)
+        }
+    }
+
+    #[test]
+    fn written_instances_read_back() {
+        let ts = vec![Point2d::new([0.25, -1.0]), Point2d::new([3.0, 1e-7]), Point2d::new([-2.5, 4.0])];
+        let mut buf = vec![];
+        StpInstance::new(Some("three".to_string()), &ts).write(&mut buf).unwrap();
+
+        let inst = StpInstance::read(&buf[..]).unwrap();
+        assert_eq!(inst.name(), Some("three"));
+        assert_eq!(inst.terminals::<Point2d<f64>>().unwrap(), ts);
+    }
+
+    #[test]
+    fn written_trees_read_back() {
+        let ts = [Point2d::new([0.0, 0.0]), Point2d::new([2.0, 0.0]), Point2d::new([1.0, 2.0])];
+        let mut tree = SteinerTree::new(&ts, &[Point2d::new([1.0, 0.5])], &[]);
+        for t in 0..3 {
+            tree.add_edge((t, 3));
+        }
+        let mut buf = vec![];
+        write_tree(&mut buf, &tree, &EuclideanSpace, Some("tree")).unwrap();
+
+        let text = String::from_utf8(buf).unwrap();
+        assert!(text.contains("Edges 3\nE 1 4 "));
+        let inst = read(&text).unwrap();
+        assert_eq!(inst.terminal_ids(), &[1, 2, 3]);
+        assert_eq!(inst.terminals::<Point2d<f64>>().unwrap(), ts.to_vec());
+    }
+}
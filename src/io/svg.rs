@@ -0,0 +1,237 @@
+//! Standalone SVG drawings of planar Steiner trees.
+
+use std::io::{self, Write};
+
+use traits::*;
+use geo::points::{Point2d};
+use steinertree::{SteinerTree};
+use algorithms::mst::{Kruskal};
+
+/// Draws a `SteinerTree<Point2d<R>>` as an SVG image. Terminals are drawn as
+/// filled black discs and Steiner points as smaller hollow red circles. The
+/// tree is scaled uniformly to fit the image.
+#[derive(Debug, Clone)]
+pub struct Svg {
+    width: f64,
+    height: f64,
+    margin: f64,
+    mst: bool,
+    edge_lengths: bool
+}
+
+impl Svg {
+    pub fn new() -> Self {
+        Svg {
+            width: 800.0,
+            height: 800.0,
+            margin: 40.0,
+            mst: false,
+            edge_lengths: false
+        }
+    }
+
+    /// Size of the image in pixels.
+    pub fn size(mut self, width: f64, height: f64) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Empty border around the drawing in pixels.
+    pub fn margin(mut self, margin: f64) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Overlays the minimum spanning tree of the terminals as dashed lines.
+    pub fn mst(mut self, mst: bool) -> Self {
+        self.mst = mst;
+        self
+    }
+
+    /// Writes the length of every edge next to its midpoint.
+    pub fn edge_lengths(mut self, edge_lengths: bool) -> Self {
+        self.edge_lengths = edge_lengths;
+        self
+    }
+
+    pub fn write<W, R, M>(&self, w: &mut W, tree: &SteinerTree<Point2d<R>>, geo: &M) -> io::Result<()>
+        where W: Write, R: Real, M: MinkowskiSpace<Point2d<R>> {
+
+        let coords = |p: &Point2d<R>| -> (f64, f64) { (p.arr()[0].into(), p.arr()[1].into()) };
+
+        let (mut minx, mut miny) = (::std::f64::INFINITY, ::std::f64::INFINITY);
+        let (mut maxx, mut maxy) = (::std::f64::NEG_INFINITY, ::std::f64::NEG_INFINITY);
+        for n in tree.nodes() {
+            let (x, y) = coords(n.p());
+            minx = minx.min(x);
+            miny = miny.min(y);
+            maxx = maxx.max(x);
+            maxy = maxy.max(y);
+        }
+        if tree.node_count() == 0 {
+            minx = 0.0;
+            miny = 0.0;
+            maxx = 0.0;
+            maxy = 0.0;
+        }
+
+        // Uniform scale fitting the larger extent; a single point or a
+        // degenerate extent is centered.
+        let (iw, ih) = (self.width - 2.0 * self.margin, self.height - 2.0 * self.margin);
+        let sx = if maxx > minx { iw / (maxx - minx) } else { ::std::f64::INFINITY };
+        let sy = if maxy > miny { ih / (maxy - miny) } else { ::std::f64::INFINITY };
+        let scale = if sx.min(sy).is_finite() { sx.min(sy) } else { 1.0 };
+        let (ox, oy) = (
+            self.margin + (iw - (maxx - minx) * scale) / 2.0,
+            self.margin + (ih - (maxy - miny) * scale) / 2.0
+        );
+        // SVG's y axis points down
+        let map = |p: &Point2d<R>| -> (f64, f64) {
+            let (x, y) = coords(p);
+            (ox + (x - minx) * scale, self.height - oy - (y - miny) * scale)
+        };
+
+        let font = (self.width.min(self.height) / 60.0).max(8.0);
+        let r = (self.width.min(self.height) / 150.0).max(2.0);
+
+        try!(writeln!(w, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
+        try!(writeln!(w, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+            viewBox=\"0 0 {} {}\">", self.width, self.height, self.width, self.height));
+        try!(writeln!(w, "<rect width=\"100%\" height=\"100%\" fill=\"white\"/>"));
+
+        let mut caption = format!("Steiner tree length {:.6}", tree.len(geo).into());
+
+        if self.mst {
+            let terminals: Vec<Point2d<R>> = tree.terminals().map(|t| t.p().clone()).collect();
+            let mst = Kruskal::new().find(&terminals, geo);
+            caption.push_str(&format!(", MST length {:.6}", mst.len(geo).into()));
+
+            try!(writeln!(w, "<g stroke=\"#888888\" stroke-width=\"1\" stroke-dasharray=\"6,4\">"));
+            for e in mst.edges() {
+                let (a, b) = e.nodes();
+                let ((x1, y1), (x2, y2)) = (map(a.p()), map(b.p()));
+                try!(writeln!(w, "<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\"/>",
+                    x1, y1, x2, y2));
+            }
+            try!(writeln!(w, "</g>"));
+        }
+
+        try!(writeln!(w, "<g stroke=\"black\" stroke-width=\"2\">"));
+        for e in tree.edges() {
+            let (a, b) = e.nodes();
+            let ((x1, y1), (x2, y2)) = (map(a.p()), map(b.p()));
+            try!(writeln!(w, "<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\"/>",
+                x1, y1, x2, y2));
+        }
+        try!(writeln!(w, "</g>"));
+
+        if self.edge_lengths {
+            try!(writeln!(w, "<g font-family=\"sans-serif\" font-size=\"{:.1}\" fill=\"#0050a0\">", font));
+            for e in tree.edges() {
+                let (a, b) = e.nodes();
+                let ((x1, y1), (x2, y2)) = (map(a.p()), map(b.p()));
+                try!(writeln!(w, "<text x=\"{:.2}\" y=\"{:.2}\">{:.3}</text>",
+                    (x1 + x2) / 2.0 + r, (y1 + y2) / 2.0 - r, e.len(geo).into()));
+            }
+            try!(writeln!(w, "</g>"));
+        }
+
+        try!(writeln!(w, "<g fill=\"black\">"));
+        for t in tree.terminals() {
+            let (x, y) = map(t.p());
+            try!(writeln!(w, "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"{:.1}\"/>", x, y, 1.5 * r));
+        }
+        try!(writeln!(w, "</g>"));
+
+        try!(writeln!(w, "<g fill=\"white\" stroke=\"red\" stroke-width=\"1.5\">"));
+        for s in tree.steiner_points() {
+            let (x, y) = map(s.p());
+            try!(writeln!(w, "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"{:.1}\"/>", x, y, r));
+        }
+        try!(writeln!(w, "</g>"));
+
+        try!(writeln!(w, "<text x=\"{:.1}\" y=\"{:.1}\" font-family=\"sans-serif\" font-size=\"{:.1}\">{}</text>",
+            self.margin / 2.0, self.margin / 2.0 + font / 2.0, font, caption));
+        writeln!(w, "</svg>")
+    }
+}
+
+impl Default for Svg {
+    fn default() -> Self {
+        Svg::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::spaces::{EuclideanSpace, LpSpace, L1};
+
+    /// Three terminals joined by a Steiner point.
+    fn tree(scale: f64, offset: f64) -> SteinerTree<Point2d<f64>> {
+        let ts = [Point2d::new([offset, offset]), Point2d::new([offset + 2.0 * scale, offset]),
+                  Point2d::new([offset + scale, offset + 2.0 * scale])];
+        let mut tree = SteinerTree::new(&ts, &[Point2d::new([offset + scale, offset + 0.5 * scale])], &[]);
+        for t in 0..3 {
+            tree.add_edge((t, 3));
+        }
+        tree
+    }
+
+    fn render<M: MinkowskiSpace<Point2d<f64>>>(svg: &Svg, tree: &SteinerTree<Point2d<f64>>, geo: &M) -> String {
+        let mut buf = vec![];
+        svg.write(&mut buf, tree, geo).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    /// The values of attribute `name` in `svg`, in order.
+    fn attrs(svg: &str, name: &str) -> Vec<f64> {
+        let key = format!(" {}=\"", name);
+        svg.match_indices(&key[..]).map(|(i, _)| {
+            let rest = &svg[i + key.len()..];
+            rest[..rest.find('"').unwrap()].parse().unwrap()
+        }).collect()
+    }
+
+    #[test]
+    fn draws_every_node_and_edge() {
+        let svg = render(&Svg::new(), &tree(1.0, 0.0), &EuclideanSpace);
+        assert!(svg.starts_with("<?xml"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<line").count(), 3);
+        assert_eq!(svg.matches("<circle").count(), 4);
+        assert_eq!(svg.matches("stroke=\"red\"").count(), 1);
+        assert!(!svg.contains("stroke-dasharray"));
+    }
+
+    #[test]
+    fn fits_any_coordinate_range() {
+        for &(scale, offset) in [(1.0, 0.0), (1e-6, 1e6), (1e9, -3e9)].iter() {
+            let svg = render(&Svg::new().size(400.0, 300.0).margin(20.0), &tree(scale, offset), &EuclideanSpace);
+            let xs: Vec<f64> = attrs(&svg, "cx").into_iter().chain(attrs(&svg, "x1")).collect();
+            let ys: Vec<f64> = attrs(&svg, "cy").into_iter().chain(attrs(&svg, "y1")).collect();
+            assert!(xs.iter().all(|&x| x >= 20.0 - 1e-9 && x <= 380.0 + 1e-9));
+            assert!(ys.iter().all(|&y| y >= 20.0 - 1e-9 && y <= 280.0 + 1e-9));
+            // The taller extent fills the height, and the top terminal is at
+            // the top
+            assert!((ys.iter().cloned().fold(0.0, f64::max) - 280.0).abs() < 0.01);
+            assert!((ys.iter().cloned().fold(300.0, f64::min) - 20.0).abs() < 0.01);
+        }
+
+        let single = SteinerTree::new(&[Point2d::new([5.0, 5.0])], &[], &[]);
+        let svg = render(&Svg::new(), &single, &EuclideanSpace);
+        assert_eq!(attrs(&svg, "cx"), vec![400.0]);
+        assert_eq!(attrs(&svg, "cy"), vec![400.0]);
+    }
+
+    #[test]
+    fn overlays_the_mst_and_edge_lengths() {
+        let svg = render(&Svg::new().mst(true).edge_lengths(true), &tree(1.0, 0.0), &LpSpace::new(L1));
+        assert_eq!(svg.matches("<line").count(), 5);
+        assert!(svg.contains("stroke-dasharray"));
+        // L1 lengths of the edges from the Steiner point
+        assert_eq!(svg.matches(">1.500</text>").count(), 3);
+        assert!(svg.contains("Steiner tree length 4.500000, MST length 5.000000"));
+    }
+}
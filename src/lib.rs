@@ -1,10 +1,16 @@
 pub mod traits;
+pub mod error;
 pub mod geo;
 pub mod algorithms;
 pub mod upperbounds;
 pub mod enumerator;
 pub mod prunetests;
+pub mod observer;
+pub mod stats;
 pub mod steinertree;
+pub mod preprocess;
+pub mod io;
+pub mod generators;
 
 use traits::*;
 use geo::points::*;
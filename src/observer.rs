@@ -0,0 +1,207 @@
+//! Callbacks on the events of a branch and bound search.
+//!
+//! `SteinerBnB`, `GPEnumeration` and `GeoMedianIter` take an observer with
+//! their `observer` builder methods and call it while they run. Observers
+//! get `&self`, so one observer can be shared by all three through a
+//! reference or an `Arc`, and keep their state in cells or atomics. The
+//! default `NoObserver` does nothing and is optimized away.
+//!
+//! An observer can't stop a search itself, but it can set the cancel flag
+//! given to `SteinerBnB::cancel_flag`.
+//!
+//! ```text
+//! struct Progress { nodes: AtomicU64 }
+//! impl<P: Point> Observer<P> for Progress {
+//!     fn enumerated(&self, _: usize, _: &SteinerTree<P>) {
+//!         self.nodes.fetch_add(1, Ordering::Relaxed);
+//!     }
+//! }
+//!
+//! let progress = Progress { nodes: AtomicU64::new(0) };
+//! let mut smt = SteinerBnB::new(GeoMedianIter::default(),
+//!     GPEnumeration::default().observer(&progress), LineTree);
+//! ```
+
+use std::fmt;
+use std::sync::Arc;
+
+use traits::*;
+use steinertree::{SteinerTree};
+
+/// Why a topology was pruned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PruneReason {
+    /// Bottleneck Steiner distance test of the enumerator
+    Bsd,
+    /// Smallest spheres test of the enumerator
+    Ss,
+    /// The prune test of `SteinerBnB`, i.e. the relatively minimal tree
+    /// was not shorter than the best tree
+    Bound
+}
+
+impl fmt::Display for PruneReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PruneReason::Bsd => write!(f, "bottleneck Steiner distance"),
+            PruneReason::Ss => write!(f, "smallest spheres"),
+            PruneReason::Bound => write!(f, "upper bound")
+        }
+    }
+}
+
+/// Receives the events of a search. All methods do nothing by default.
+///
+/// The depth of a topology is the number of terminals inserted after the
+/// first three, so the topology of the first three terminals has depth 0.
+pub trait Observer<P: Point> {
+    /// The enumerator returned a topology.
+    fn enumerated(&self, _depth: usize, _tree: &SteinerTree<P>) { }
+
+    /// A topology and all topologies below it were skipped.
+    fn pruned(&self, _depth: usize, _reason: PruneReason) { }
+
+    /// The relatively minimal tree of a topology was found in `iterations`
+    /// iterations.
+    fn rmt_finished(&self, _tree: &SteinerTree<P>, _len: P::R, _iterations: u64) { }
+
+    /// A full topology shorter than the best tree so far was found.
+    fn new_incumbent(&self, _tree: &SteinerTree<P>, _len: P::R) { }
+}
+
+/// The observer that ignores all events.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoObserver;
+
+impl<P: Point> Observer<P> for NoObserver { }
+
+impl<'a, P: Point, O: Observer<P>> Observer<P> for &'a O {
+    fn enumerated(&self, depth: usize, tree: &SteinerTree<P>) {
+        (**self).enumerated(depth, tree)
+    }
+
+    fn pruned(&self, depth: usize, reason: PruneReason) {
+        (**self).pruned(depth, reason)
+    }
+
+    fn rmt_finished(&self, tree: &SteinerTree<P>, len: P::R, iterations: u64) {
+        (**self).rmt_finished(tree, len, iterations)
+    }
+
+    fn new_incumbent(&self, tree: &SteinerTree<P>, len: P::R) {
+        (**self).new_incumbent(tree, len)
+    }
+}
+
+impl<P: Point, O: Observer<P>> Observer<P> for Arc<O> {
+    fn enumerated(&self, depth: usize, tree: &SteinerTree<P>) {
+        (**self).enumerated(depth, tree)
+    }
+
+    fn pruned(&self, depth: usize, reason: PruneReason) {
+        (**self).pruned(depth, reason)
+    }
+
+    fn rmt_finished(&self, tree: &SteinerTree<P>, len: P::R, iterations: u64) {
+        (**self).rmt_finished(tree, len, iterations)
+    }
+
+    fn new_incumbent(&self, tree: &SteinerTree<P>, len: P::R) {
+        (**self).new_incumbent(tree, len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+    use geo::points::Point2d;
+    use geo::spaces::EuclideanSpace;
+    use generators::Generator;
+    use enumerator::GPEnumeration;
+    use algorithms::rmt::GeoMedianIter;
+    use algorithms::steinerbnb::SteinerBnB;
+    use upperbounds::LineTree;
+
+    #[derive(Default)]
+    struct Recorder {
+        enumerated: Cell<usize>,
+        depths_ok: Cell<bool>,
+        pruned: RefCell<Vec<(usize, PruneReason)>>,
+        rmts: Cell<usize>,
+        incumbents: RefCell<Vec<f64>>
+    }
+
+    impl Observer<Point2d<f64>> for Recorder {
+        fn enumerated(&self, depth: usize, tree: &SteinerTree<Point2d<f64>>) {
+            self.enumerated.set(self.enumerated.get() + 1);
+            if depth + 3 != tree.terminals().len() {
+                self.depths_ok.set(false);
+            }
+        }
+
+        fn pruned(&self, depth: usize, reason: PruneReason) {
+            self.pruned.borrow_mut().push((depth, reason));
+        }
+
+        fn rmt_finished(&self, tree: &SteinerTree<Point2d<f64>>, len: f64, iterations: u64) {
+            assert_eq!(tree.len(&EuclideanSpace), len);
+            assert!(iterations > 0);
+            self.rmts.set(self.rmts.get() + 1);
+        }
+
+        fn new_incumbent(&self, tree: &SteinerTree<Point2d<f64>>, len: f64) {
+            assert_eq!(tree.len(&EuclideanSpace), len);
+            self.incumbents.borrow_mut().push(len);
+        }
+    }
+
+    fn count(rec: &Recorder, reason: PruneReason) -> usize {
+        rec.pruned.borrow().iter().filter(|&&(_, r)| r == reason).count()
+    }
+
+    #[test]
+    fn reports_every_event() {
+        let geo = EuclideanSpace::new();
+        let t: Vec<Point2d<f64>> = Generator::new(4, 2).uniform(9);
+        let rec = Recorder { depths_ok: Cell::new(true), ..Recorder::default() };
+        let mut smt = SteinerBnB::new(GeoMedianIter::default().observer(&rec),
+                                      GPEnumeration::default().observer(&rec),
+                                      LineTree).observer(&rec);
+        smt.find(t, &geo);
+
+        assert!(rec.depths_ok.get());
+        assert_eq!(rec.enumerated.get(), smt.rmt_alg().data().nodes());
+        assert_eq!(rec.rmts.get(), smt.rmt_alg().data().nodes());
+
+        let data = smt.enumerator().data().clone();
+        assert_eq!(count(&rec, PruneReason::Bsd), data.bsd_pruned().iter().sum::<usize>());
+        assert_eq!(count(&rec, PruneReason::Ss), data.ss_pruned().iter().sum::<usize>());
+        assert_eq!(count(&rec, PruneReason::Bound) as u64, smt.data().pruned());
+        assert!(rec.pruned.borrow().iter().all(|&(d, _)| d <= 6));
+
+        let incumbents = rec.incumbents.borrow();
+        assert_eq!(incumbents.len() as u64, smt.data().best_updates());
+        assert!(incumbents.windows(2).all(|w| w[1] < w[0]));
+        assert_eq!(*incumbents.last().unwrap(), smt.data().length());
+    }
+
+    #[test]
+    fn shared_through_an_arc() {
+        let geo = EuclideanSpace::new();
+        let t: Vec<Point2d<f64>> = Generator::new(5, 2).uniform(6);
+        let rec = Arc::new(Recorder::default());
+        let mut smt = SteinerBnB::new(GeoMedianIter::default(), GPEnumeration::default(),
+                                      LineTree).observer(rec.clone());
+        smt.find(t, &geo);
+        assert_eq!(rec.enumerated.get(), 0);
+        assert_eq!(rec.incumbents.borrow().len() as u64, smt.data().best_updates());
+    }
+
+    #[test]
+    fn reasons_display() {
+        assert_eq!(PruneReason::Bsd.to_string(), "bottleneck Steiner distance");
+        assert_eq!(PruneReason::Ss.to_string(), "smallest spheres");
+        assert_eq!(PruneReason::Bound.to_string(), "upper bound");
+    }
+}
@@ -0,0 +1,340 @@
+//! Preprocessing of terminal sets ahead of an SMT algorithm.
+//!
+//! `Preprocessing` checks the dimensions, merges coincident terminals and
+//! moves the terminals into the unit box, and maps trees of the processed
+//! terminals back to the input. The scaling is uniform in all coordinates,
+//! which every Minkowski space allows: a tree is minimal for the scaled
+//! terminals exactly if it is minimal for the original ones.
+//!
+//! `Preprocess` wraps an `SMT` algorithm with this preprocessing:
+//!
+//! ```text
+//! let mut smt = Preprocess::new(SteinerBnB::default());
+//! let tree = smt.try_find(terminals, &EuclideanSpace)?;
+//! ```
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::{self, Write};
+
+use traits::*;
+use error::{self, SteinerError};
+use steinertree::{SteinerTree};
+use stats::{Stats};
+
+/// The terminals after preprocessing, with what is needed to map trees back.
+#[derive(Debug, Clone)]
+pub struct Preprocessing<P: Point> {
+    originals: Vec<P>,
+    terminals: Vec<P>,
+    mapping: Vec<usize>,
+    offset: P,
+    scale: P::R
+}
+
+fn lex_cmp<P: Point>(a: &P, b: &P) -> Ordering {
+    a.iter().zip(b.iter())
+        .map(|(x, y)| x.cmp_nan_last(y))
+        .find(|&o| o != Ordering::Equal)
+        .unwrap_or(Ordering::Equal)
+}
+
+impl<P: Point> Preprocessing<P> {
+    /// Preprocesses `terminals`, failing if they are empty, of different
+    /// dimensions or not finite.
+    pub fn new(terminals: Vec<P>, merge: bool, normalize: bool) -> Result<Self, SteinerError> {
+        try!(error::check_points(&terminals));
+
+        // Representatives are the first of their coincident terminals, and
+        // the merged terminals keep the order of the input.
+        let mut mapping: Vec<usize> = (0..terminals.len()).collect();
+        if merge {
+            let mut order: Vec<usize> = (0..terminals.len()).collect();
+            order.sort_by(|&i, &j| lex_cmp(&terminals[i], &terminals[j]).then(i.cmp(&j)));
+            for w in order.windows(2) {
+                if lex_cmp(&terminals[w[0]], &terminals[w[1]]) == Ordering::Equal {
+                    mapping[w[1]] = mapping[w[0]];
+                }
+            }
+        }
+        let mut merged = Vec::new();
+        for i in 0..terminals.len() {
+            if mapping[i] == i {
+                mapping[i] = merged.len();
+                let mut t = terminals[i].clone();
+                t.set_id(merged.len());
+                merged.push(t);
+            } else {
+                mapping[i] = mapping[mapping[i]];
+            }
+        }
+
+        let mut offset = terminals[0].clone();
+        let mut scale = P::R::one();
+        if normalize {
+            for t in terminals.iter() {
+                offset.modify(t, &|c, o| c.min(o));
+            }
+            let mut extent = P::R::zero();
+            for t in terminals.iter() {
+                for (c, o) in t.iter().zip(offset.iter()) {
+                    extent = extent.max(*c - *o);
+                }
+            }
+            if extent > P::R::zero() {
+                scale = extent;
+            }
+            for t in merged.iter_mut() {
+                t.sub(&offset).div(scale);
+            }
+        } else {
+            for c in offset.iter_mut() {
+                *c = P::R::zero();
+            }
+        }
+
+        Ok(Preprocessing {
+            originals: terminals,
+            terminals: merged,
+            mapping: mapping,
+            offset: offset,
+            scale: scale
+        })
+    }
+
+    /// The processed terminals, with ids 0, 1, ...
+    pub fn terminals(&self) -> &[P] {
+        &self.terminals
+    }
+
+    /// The index in `terminals()` of every input terminal.
+    pub fn mapping(&self) -> &[usize] {
+        &self.mapping
+    }
+
+    /// Number of input terminals that were merged into an earlier one.
+    pub fn merged(&self) -> usize {
+        self.originals.len() - self.terminals.len()
+    }
+
+    /// The lower corner of the bounding box, which is moved to the origin.
+    pub fn offset(&self) -> &P {
+        &self.offset
+    }
+
+    /// The factor all coordinates were divided by after the translation.
+    pub fn scale(&self) -> P::R {
+        self.scale
+    }
+
+    /// Maps a tree of the processed terminals back to the input. The
+    /// terminals of the result are the input terminals in input order, with
+    /// their original coordinates and ids, followed by the Steiner points
+    /// of `tree`. Merged terminals are joined to their representative by an
+    /// edge of length zero.
+    ///
+    /// Terminals of `tree` are recognized by their coordinates, so `tree`
+    /// may order and number them freely. Without merging, coincident
+    /// terminals are told apart by their ids where the algorithm kept them,
+    /// and each of them is used by one terminal of `tree` in any case.
+    pub fn restore(&self, tree: &SteinerTree<P>) -> SteinerTree<P> {
+        let mut sorted: Vec<usize> = (0..self.terminals.len()).collect();
+        sorted.sort_by(|&i, &j| lex_cmp(&self.terminals[i], &self.terminals[j]).then(i.cmp(&j)));
+        let mut used = vec![false; self.terminals.len()];
+
+        let mut representative = vec![0; self.terminals.len()];
+        for (i, &m) in self.mapping.iter().enumerate().rev() {
+            representative[m] = i;
+        }
+
+        // Node of `tree` to node of the result
+        let n = self.originals.len();
+        let mut steiner_points = Vec::new();
+        let nodes: Vec<usize> = tree.nodes().map(|node| {
+            if node.is_terminal() {
+                let p = node.p();
+                // The first of the processed terminals at the position of p
+                let first = match sorted.binary_search_by(|&i|
+                    lex_cmp(&self.terminals[i], p).then(Ordering::Greater)) { Ok(i) | Err(i) => i };
+                let at: Vec<usize> = sorted[first..].iter().cloned()
+                    .take_while(|&i| lex_cmp(&self.terminals[i], p) == Ordering::Equal)
+                    .filter(|&i| !used[i])
+                    .collect();
+                let t = *at.iter().find(|&&i| i == p.id()).or(at.first())
+                    .expect("The tree has a terminal that is not a preprocessed terminal");
+                used[t] = true;
+                representative[t]
+            } else {
+                let mut s = node.p().clone();
+                s.mul(self.scale).add(&self.offset);
+                s.set_id(n + steiner_points.len());
+                steiner_points.push(s);
+                n + steiner_points.len() - 1
+            }
+        }).collect();
+
+        let mut edges: Vec<(usize, usize)> = tree.edges().map(|e| {
+            let (a, b) = e.nodes();
+            (nodes[a.id()], nodes[b.id()])
+        }).collect();
+        for (i, &m) in self.mapping.iter().enumerate() {
+            if representative[m] != i {
+                edges.push((representative[m], i));
+            }
+        }
+
+        SteinerTree::new(&self.originals, &steiner_points, &edges)
+    }
+}
+
+/// An `SMT` algorithm that preprocesses the terminals before solving and
+/// restores the tree afterwards. Both merging and normalization are on by
+/// default.
+#[derive(Debug, Clone)]
+pub struct Preprocess<S> {
+    smt: S,
+    merge: bool,
+    normalize: bool
+}
+
+impl<S> Preprocess<S> {
+    pub fn new(smt: S) -> Self {
+        Preprocess {
+            smt: smt,
+            merge: true,
+            normalize: true
+        }
+    }
+
+    pub fn merge_duplicates(mut self, merge: bool) -> Self {
+        self.merge = merge;
+        self
+    }
+
+    pub fn normalize(mut self, normalize: bool) -> Self {
+        self.normalize = normalize;
+        self
+    }
+
+    pub fn smt_alg(&mut self) -> &mut S {
+        &mut self.smt
+    }
+}
+
+impl<P, M, S> SMT<P, M> for Preprocess<S>
+    where P: Point, M: MinkowskiSpace<P>, S: SMT<P, M> {
+
+    type D = S::D;
+
+    fn find(&mut self, t: Vec<P>, geo: &M) -> SteinerTree<P> {
+        let pre = Preprocessing::new(t, self.merge, self.normalize)
+            .unwrap_or_else(|e| panic!("{}", e));
+        let tree = self.smt.find(pre.terminals().to_vec(), geo);
+        pre.restore(&tree)
+    }
+
+    fn try_find(&mut self, t: Vec<P>, geo: &M) -> Result<SteinerTree<P>, SteinerError> {
+        let pre = try!(Preprocessing::new(t, self.merge, self.normalize));
+        let tree = try!(self.smt.try_find(pre.terminals().to_vec(), geo));
+        Ok(pre.restore(&tree))
+    }
+
+    fn data(&self) -> &Self::D {
+        self.smt.data()
+    }
+
+    fn print(&self, f: &mut fmt::Formatter, inde: u32) -> fmt::Result {
+        if !self.merge && !self.normalize {
+            return self.smt.print(f, inde);
+        }
+        try!(write!(f, "Preprocessing ({}merging duplicates, {}normalizing to the unit box) for the ",
+            if self.merge { "" } else { "not " }, if self.normalize { "" } else { "not " }));
+        self.smt.print(f, inde)
+    }
+
+    fn print_data<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        self.smt.print_data(w)
+    }
+
+    fn report(&self) -> Stats {
+        self.smt.report()
+    }
+}
+
+impl<S: fmt::Display> fmt::Display for Preprocess<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.merge && !self.normalize {
+            return write!(f, "{}", self.smt);
+        }
+        write!(f, "Preprocessing for the {}", self.smt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::NAN;
+    use geo::points::Point2d;
+
+    /// A star over the processed terminals in reverse order, renumbered.
+    fn reversed_star(pre: &Preprocessing<Point2d<f64>>) -> SteinerTree<Point2d<f64>> {
+        let mut ts: Vec<Point2d<f64>> = pre.terminals().iter().rev().cloned().collect();
+        for (i, t) in ts.iter_mut().enumerate() {
+            t.set_id(i);
+        }
+        let edges: Vec<(usize, usize)> = (0..ts.len()).map(|i| (i, ts.len())).collect();
+        SteinerTree::new(&ts, &[Point2d::new([0.5, 0.5])], &edges)
+    }
+
+    fn assert_spanning(st: &SteinerTree<Point2d<f64>>) {
+        assert_eq!(st.edges().count(), st.node_count() - 1);
+        assert!(st.edges().all(|e| { let (a, b) = e.nodes(); a.id() != b.id() }));
+        assert!(st.nodes().all(|n| n.degree() > 0));
+    }
+
+    #[test]
+    fn merges_and_normalizes() {
+        let t = vec![Point2d::new([2.0, 2.0]), Point2d::new([4.0, 2.0]), Point2d::new([2.0, 2.0]),
+                     Point2d::new([2.0, 6.0])];
+        let pre = Preprocessing::new(t.clone(), true, true).unwrap();
+        assert_eq!(pre.merged(), 1);
+        assert_eq!(pre.mapping(), &[0, 1, 0, 2]);
+        assert_eq!(pre.scale(), 4.0);
+        assert_eq!(pre.terminals()[2].coords(), &[0.0, 1.0]);
+
+        let st = pre.restore(&reversed_star(&pre));
+        assert_spanning(&st);
+        assert_eq!(st.terminals().count(), 4);
+        assert_eq!(st.steiner_points().next().unwrap().p().coords(), &[4.0, 4.0]);
+        for (n, t) in st.terminals().zip(t.iter()) {
+            assert_eq!(n.p(), t);
+        }
+    }
+
+    #[test]
+    fn restores_duplicates_without_merging() {
+        let t = vec![Point2d::new([0.0, 0.0]), Point2d::new([1.0, 0.0]), Point2d::new([0.0, 0.0]),
+                     Point2d::new([0.0, 0.0])];
+        let pre = Preprocessing::new(t, false, false).unwrap();
+        assert_eq!(pre.merged(), 0);
+
+        let st = pre.restore(&reversed_star(&pre));
+        assert_spanning(&st);
+        assert_eq!(st.i(4).degree(), 4);
+    }
+
+    #[test]
+    fn rejects_invalid_terminals() {
+        assert_eq!(Preprocessing::<Point2d<f64>>::new(vec![], true, true).err(),
+            Some(SteinerError::NoTerminals));
+        assert_eq!(Preprocessing::new(vec![Point2d::new([0.0, 0.0]), Point2d::new([NAN, 0.0])],
+                                      true, true).err(),
+            Some(SteinerError::NonFinite { terminal: 1 }));
+    }
+
+    #[test]
+    fn nan_compares_after_numbers() {
+        assert_eq!(lex_cmp(&Point2d::new([0.0, NAN]), &Point2d::new([0.0, 1.0])), Ordering::Greater);
+        assert_eq!(lex_cmp(&Point2d::new([NAN, 0.0]), &Point2d::new([NAN, 1.0])), Ordering::Less);
+    }
+}
@@ -8,3 +8,17 @@ impl PruneTest for UpperBoundPruning {
         len >= ub
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::points::Point2d;
+
+    #[test]
+    fn prunes_when_the_bound_is_reached() {
+        let st = SteinerTree::new(&[Point2d::new([0.0, 0.0])], &[], &[]);
+        assert!(!UpperBoundPruning.prunetest(&st, 1.0, 2.0));
+        assert!(UpperBoundPruning.prunetest(&st, 2.0, 2.0));
+        assert!(UpperBoundPruning.prunetest(&st, 3.0, 2.0));
+    }
+}
@@ -0,0 +1,365 @@
+//! Machine-readable statistics of the algorithms.
+//!
+//! Every `*Data` struct implements `Report`, giving its statistics as a
+//! `Stats` tree of named entries. The algorithm traits have a `report`
+//! method that nests the reports of their components the way `print_data`
+//! chains their output, e.g. for `SteinerBnB`:
+//!
+//! ```text
+//! {"runs": 1, "time": 0.12, ..., "enumerator": {"nodes": 5140, ...},
+//!  "rmt": {"nodes": 5141, ..., "median": {"time": 0.03}}}
+//! ```
+//!
+//! Durations are given in seconds. The reports of several runs are combined
+//! with `merge`, and written with `write_json` or `write_csv`.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::time::{Duration};
+
+use io::json::{JsonValue};
+
+/// The value of an entry of `Stats`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stat {
+    /// A number of events; summed when merged
+    Count(u64),
+    /// A duration in seconds; summed when merged
+    Seconds(f64),
+    /// A measured value like a length; averaged over the runs when merged
+    Value(f64),
+    /// Counts per depth of the search; summed per depth when merged
+    Counts(Vec<u64>),
+    /// A description; replaced by "mixed" when merged with a different one
+    Text(String),
+    /// The statistics of a component
+    Group(Stats)
+}
+
+/// A report of statistics: named entries in a fixed order, for a number of
+/// runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    runs: u64,
+    entries: Vec<(String, Stat)>
+}
+
+impl Stats {
+    /// An empty report of one run.
+    pub fn new() -> Self {
+        Stats {
+            runs: 1,
+            entries: Vec::new()
+        }
+    }
+
+    pub fn count(self, key: &str, count: u64) -> Self {
+        self.with(key, Stat::Count(count))
+    }
+
+    pub fn seconds(self, key: &str, dur: &Duration) -> Self {
+        let secs = (dur.as_secs() as f64) + (dur.subsec_nanos() as f64) / 1000000000.0;
+        self.with(key, Stat::Seconds(secs))
+    }
+
+    pub fn value(self, key: &str, value: f64) -> Self {
+        self.with(key, Stat::Value(value))
+    }
+
+    pub fn counts(self, key: &str, counts: &[usize]) -> Self {
+        self.with(key, Stat::Counts(counts.iter().map(|&c| c as u64).collect()))
+    }
+
+    pub fn text(self, key: &str, text: &str) -> Self {
+        self.with(key, Stat::Text(text.to_string()))
+    }
+
+    pub fn group(self, key: &str, stats: Stats) -> Self {
+        self.with(key, Stat::Group(stats))
+    }
+
+    /// Adds the entry `key`, replacing an entry of the same name.
+    pub fn with(mut self, key: &str, stat: Stat) -> Self {
+        match self.entries.iter().position(|&(ref k, _)| k == key) {
+            Some(i) => self.entries[i].1 = stat,
+            None => self.entries.push((key.to_string(), stat))
+        }
+        self
+    }
+
+    /// Number of runs these statistics are of.
+    pub fn runs(&self) -> u64 {
+        self.runs
+    }
+
+    pub fn entries(&self) -> &[(String, Stat)] {
+        &self.entries
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Stat> {
+        self.entries.iter().find(|&&(ref k, _)| k == key).map(|&(_, ref s)| s)
+    }
+
+    /// Follows a path of group names, e.g. `&["rmt", "median"]`.
+    pub fn path(&self, keys: &[&str]) -> Option<&Stat> {
+        let (last, groups) = match keys.split_last() {
+            Some(s) => s,
+            None => return None
+        };
+        let mut stats = self;
+        for k in groups {
+            match stats.get(k) {
+                Some(&Stat::Group(ref g)) => stats = g,
+                _ => return None
+            }
+        }
+        stats.get(last)
+    }
+
+    /// Combines the statistics of the runs of `other` with these. Entries
+    /// only present in `other` are added at the end.
+    pub fn merge(&mut self, other: &Stats) {
+        let (r1, r2) = (self.runs as f64, other.runs as f64);
+        for &(ref key, ref stat) in other.entries.iter() {
+            let i = match self.entries.iter().position(|&(ref k, _)| k == key) {
+                Some(i) => i,
+                None => {
+                    self.entries.push((key.clone(), stat.clone()));
+                    continue;
+                }
+            };
+            let merged = match (&self.entries[i].1, stat) {
+                (&Stat::Count(a), &Stat::Count(b)) => Stat::Count(a + b),
+                (&Stat::Seconds(a), &Stat::Seconds(b)) => Stat::Seconds(a + b),
+                (&Stat::Value(a), &Stat::Value(b)) => Stat::Value((a * r1 + b * r2) / (r1 + r2)),
+                (&Stat::Counts(ref a), &Stat::Counts(ref b)) => {
+                    let mut c = a.clone();
+                    if c.len() < b.len() {
+                        c.resize(b.len(), 0);
+                    }
+                    for (x, y) in c.iter_mut().zip(b.iter()) {
+                        *x += *y;
+                    }
+                    Stat::Counts(c)
+                }
+                (&Stat::Text(ref a), &Stat::Text(ref b)) if a == b => Stat::Text(a.clone()),
+                (&Stat::Group(ref a), &Stat::Group(ref b)) => {
+                    let mut g = a.clone();
+                    g.merge(b);
+                    Stat::Group(g)
+                }
+                _ => Stat::Text("mixed".to_string())
+            };
+            self.entries[i].1 = merged;
+        }
+        self.runs += other.runs;
+    }
+
+    fn json_entries(&self) -> Vec<(String, JsonValue)> {
+        self.entries.iter().map(|&(ref k, ref s)| {
+            let v = match *s {
+                Stat::Count(c) => JsonValue::Number(c as f64),
+                Stat::Seconds(v) | Stat::Value(v) => JsonValue::Number(v),
+                Stat::Counts(ref cs) => JsonValue::Array(
+                    cs.iter().map(|&c| JsonValue::Number(c as f64)).collect()),
+                Stat::Text(ref t) => JsonValue::String(t.clone()),
+                Stat::Group(ref g) => JsonValue::Object(g.json_entries())
+            };
+            (k.clone(), v)
+        }).collect()
+    }
+
+    /// The report as a JSON object, with the number of runs first.
+    pub fn to_json(&self) -> JsonValue {
+        let mut entries = vec![("runs".to_string(), JsonValue::Number(self.runs as f64))];
+        entries.extend(self.json_entries());
+        JsonValue::Object(entries)
+    }
+
+    pub fn write_json<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "{}", self.to_json())
+    }
+
+    /// The entries as flat columns: nested names are joined by dots and
+    /// per-depth counts become one column per depth, e.g. `bsd_pruned.1`.
+    pub fn columns(&self) -> Vec<(String, String)> {
+        let mut cols = vec![("runs".to_string(), self.runs.to_string())];
+        self.flatten("", &mut cols);
+        cols
+    }
+
+    fn flatten(&self, prefix: &str, cols: &mut Vec<(String, String)>) {
+        for &(ref k, ref s) in self.entries.iter() {
+            let key = format!("{}{}", prefix, k);
+            match *s {
+                Stat::Count(c) => cols.push((key, c.to_string())),
+                Stat::Seconds(v) | Stat::Value(v) => cols.push((key, v.to_string())),
+                Stat::Counts(ref cs) => for (i, c) in cs.iter().enumerate() {
+                    cols.push((format!("{}.{}", key, i + 1), c.to_string()));
+                },
+                Stat::Text(ref t) => cols.push((key, t.clone())),
+                Stat::Group(ref g) => g.flatten(&format!("{}.", key), cols)
+            }
+        }
+    }
+
+    /// Writes `reports` as CSV with one row per report. The columns are the
+    /// union of the columns of the reports, missing values are left empty.
+    pub fn write_csv<W: Write>(w: &mut W, reports: &[Stats]) -> io::Result<()> {
+        let mut header: Vec<String> = Vec::new();
+        for r in reports.iter() {
+            for (k, _) in r.columns() {
+                if !header.contains(&k) {
+                    header.push(k);
+                }
+            }
+        }
+
+        try!(Stats::write_csv_header(w, &header));
+        for r in reports.iter() {
+            try!(r.write_csv_row(w, &header));
+        }
+        Ok(())
+    }
+
+    /// Writes the CSV header of the columns `header`. With `write_csv_row`
+    /// this writes the table of `write_csv` one row at a time.
+    pub fn write_csv_header<W: Write>(w: &mut W, header: &[String]) -> io::Result<()> {
+        let names: Vec<String> = header.iter().map(|h| csv_field(h)).collect();
+        writeln!(w, "{}", names.join(","))
+    }
+
+    /// Writes the values of the columns `header` as a CSV row, leaving the
+    /// missing ones empty.
+    pub fn write_csv_row<W: Write>(&self, w: &mut W, header: &[String]) -> io::Result<()> {
+        let row = self.columns();
+        let values: Vec<String> = header.iter().map(|h| {
+            row.iter().find(|&&(ref k, _)| k == h).map(|&(_, ref v)| csv_field(v))
+                .unwrap_or_else(String::new)
+        }).collect();
+        writeln!(w, "{}", values.join(","))
+    }
+}
+
+/// Quotes `s` as a CSV field if needed.
+fn csv_field(s: &str) -> String {
+    if s.contains(',') || s.contains('"') || s.contains('\n') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Stats::new()
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_json())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::points::Point2d;
+    use geo::spaces::EuclideanSpace;
+    use generators::Generator;
+    use algorithms::steinerbnb::SteinerBnB;
+    use traits::{SMT, Enumerator, EnumeratorData};
+
+    fn run(time: u64, nodes: u64, length: f64, order: &str) -> Stats {
+        Stats::new()
+            .seconds("time", &Duration::from_millis(time))
+            .count("nodes", nodes)
+            .value("length", length)
+            .counts("pruned", &[1, 2])
+            .text("order", order)
+            .group("rmt", Stats::new().count("iterations", nodes * 2))
+    }
+
+    #[test]
+    fn builds_and_finds_entries() {
+        let stats = run(1500, 3, 1.0, "depth-first").count("nodes", 4);
+        assert_eq!(stats.entries().len(), 6);
+        assert_eq!(stats.get("nodes"), Some(&Stat::Count(4)));
+        assert_eq!(stats.get("time"), Some(&Stat::Seconds(1.5)));
+        assert_eq!(stats.path(&["rmt", "iterations"]), Some(&Stat::Count(6)));
+        assert_eq!(stats.path(&["nodes", "iterations"]), None);
+        assert_eq!(stats.path(&[]), None);
+        assert_eq!(stats.get("missing"), None);
+    }
+
+    #[test]
+    fn merges_runs() {
+        let mut stats = run(1000, 3, 1.0, "depth-first");
+        stats.merge(&run(500, 5, 4.0, "depth-first"));
+        stats.merge(&run(500, 1, 4.0, "best-first").counts("pruned", &[1, 1, 1]).count("extra", 7));
+
+        assert_eq!(stats.runs(), 3);
+        assert_eq!(stats.get("time"), Some(&Stat::Seconds(2.0)));
+        assert_eq!(stats.get("nodes"), Some(&Stat::Count(9)));
+        assert_eq!(stats.get("length"), Some(&Stat::Value(3.0)));
+        assert_eq!(stats.get("pruned"), Some(&Stat::Counts(vec![3, 5, 1])));
+        assert_eq!(stats.get("order"), Some(&Stat::Text("mixed".to_string())));
+        assert_eq!(stats.path(&["rmt", "iterations"]), Some(&Stat::Count(18)));
+        assert_eq!(stats.entries().last(), Some(&("extra".to_string(), Stat::Count(7))));
+
+        // Values are weighted by the runs on both sides
+        let mut other = Stats::new().value("length", 0.0);
+        other.merge(&Stats::new().value("length", 0.0));
+        stats.merge(&other);
+        assert_eq!(stats.get("length"), Some(&Stat::Value(9.0 / 5.0)));
+    }
+
+    #[test]
+    fn writes_json() {
+        let stats = run(250, 3, 0.5, "a \"b\"");
+        assert_eq!(stats.to_string(), "{\"runs\":1,\"time\":0.25,\"nodes\":3,\"length\":0.5,\
+            \"pruned\":[1,2],\"order\":\"a \\\"b\\\"\",\"rmt\":{\"iterations\":6}}");
+
+        let mut out = Vec::new();
+        stats.write_json(&mut out).unwrap();
+        let json = JsonValue::parse(String::from_utf8(out).unwrap().trim()).unwrap();
+        assert_eq!(json.get("rmt").and_then(|r| r.get("iterations")).and_then(|i| i.as_f64()),
+            Some(6.0));
+    }
+
+    #[test]
+    fn writes_csv() {
+        let a = run(250, 3, 0.5, "a, b");
+        let b = Stats::new().count("nodes", 4).counts("pruned", &[0, 0, 7]);
+        let mut out = Vec::new();
+        Stats::write_csv(&mut out, &[a, b]).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(),
+            "runs,time,nodes,length,pruned.1,pruned.2,order,rmt.iterations,pruned.3\n\
+             1,0.25,3,0.5,1,2,\"a, b\",6,\n\
+             1,,4,,0,0,,,7\n");
+    }
+
+    #[test]
+    fn nests_the_components() {
+        let geo = EuclideanSpace::new();
+        let mut smt = SteinerBnB::default();
+        let mut total = Stats::new();
+        for seed in 0..2 {
+            let t: Vec<Point2d<f64>> = Generator::new(seed, 2).uniform(6);
+            smt.find(t, &geo);
+            let stats = smt.report();
+            assert_eq!(stats.get("status"), Some(&Stat::Text("optimal".to_string())));
+            assert_eq!(stats.path(&["enumerator", "nodes"]),
+                Some(&Stat::Count(smt.enumerator().data().nodes() as u64)));
+            assert!(stats.path(&["rmt", "median", "time"]).is_some());
+            if seed == 0 {
+                total = stats;
+            } else {
+                total.merge(&stats);
+            }
+        }
+        assert_eq!(total.runs(), 2);
+        assert_eq!(total.get("status"), Some(&Stat::Text("optimal".to_string())));
+    }
+}
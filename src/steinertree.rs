@@ -2,67 +2,88 @@ use traits::*;
 use std::fmt;
 use std::slice::{Iter, IterMut};
 use std::iter::*;
-
-#[derive(Debug)]
+use std::mem;
+
+/// A tree over terminals and Steiner points.
+///
+/// Nodes are stored in a single vector and refer to their neighbours by
+/// index, so the tree may grow to any size. Access to a node goes through
+/// the `Node` and `NodeMut` handles which borrow the tree.
+#[derive(Debug, Clone)]
 pub struct SteinerTree<P> {
-    nodes: Vec<Node<P>>,
+    nodes: Vec<NodeData<P>>,
     terminal_count: usize
 }
 
-#[derive(Debug)]
-pub struct Node<P> {
+#[derive(Debug, Clone)]
+struct NodeData<P> {
     p: P,
-    ns: Vec<*mut Node<P>>,
-    is_terminal: bool,
-    id: usize
+    ns: Vec<usize>,
+    is_terminal: bool
 }
 
-impl<P> Node<P> {
+impl<P> NodeData<P> {
     fn new(p: P, is_terminal: bool) -> Self {
-        Node {
+        NodeData {
             p: p,
             ns: Vec::new(),
-            is_terminal: is_terminal,
-            id: 0
+            is_terminal: is_terminal
         }
     }
 
-    fn add_neighbour(&mut self, n: *mut Self) {
+    fn add_neighbour(&mut self, n: usize) {
         self.ns.push(n);
     }
 
     fn remove_neighbour(&mut self, id: usize) {
-        for i in 0..self.ns.len() {
-            let neighbour = unsafe { &*self.ns[i] };
-            if neighbour.id == id {
-                self.ns.swap_remove(i);
-                return;
-            }
+        if let Some(i) = self.ns.iter().position(|&n| n == id) {
+            self.ns.swap_remove(i);
         }
     }
+}
 
-
+/// Shared handle to a node of a `SteinerTree`.
+pub struct Node<'a, P: 'a> {
+    nodes: &'a [NodeData<P>],
+    id: usize
 }
 
-impl<P: Clone> Node<P> {
+impl<'a, P> Clone for Node<'a, P> {
     fn clone(&self) -> Self {
-        let mut ns = Vec::with_capacity(self.ns.len());
-        for &n in self.ns.iter() {
-            ns.push(n);
-        }
+        *self
+    }
+}
+impl<'a, P> Copy for Node<'a, P> {}
 
-        Node {
-            p: self.p.clone(),
-            ns: ns,
-            is_terminal: self.is_terminal,
-            id: self.id
-        }
+impl<'a, P: fmt::Debug> fmt::Debug for Node<'a, P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Node")
+            .field("id", &self.id)
+            .field("p", self.p())
+            .field("ns", &self.data().ns)
+            .field("is_terminal", &self.is_terminal())
+            .finish()
+    }
+}
+
+/// Mutable handle to a node of a `SteinerTree`.
+///
+/// Gives mutable access to the position of the node and its neighbours,
+/// the topology of the tree can not be changed through it.
+pub struct NodeMut<'a, P: 'a> {
+    nodes: &'a mut [NodeData<P>],
+    id: usize
+}
+
+impl<'a, P: fmt::Debug> fmt::Debug for NodeMut<'a, P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.as_node().fmt(f)
     }
 }
 
 
 pub struct Edges<'a, P: 'a> {
-    nodes: Iter<'a, Node<P>>,
+    nodes: Nodes<'a, P>,
     edges: Option<IncidentEdges<'a, P>>
 }
 impl<'a, P> Iterator for Edges<'a, P> {
@@ -86,12 +107,28 @@ impl<'a, P> Iterator for Edges<'a, P> {
 }
 impl<'a, P> ExactSizeIterator for Edges<'a, P> {}
 
+pub struct Nodes<'a, P: 'a> {
+    nodes: &'a [NodeData<P>],
+    iter: ::std::ops::Range<usize>
+}
+impl<'a, P> Iterator for Nodes<'a, P> {
+    type Item = Node<'a, P>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let nodes = self.nodes;
+        self.iter.next().map(|i| Node { nodes: nodes, id: i })
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+impl<'a, P> ExactSizeIterator for Nodes<'a, P> {}
+
 pub struct Terminals<'a, P: 'a> {
-    iter: Iter<'a, Node<P>>,
+    iter: Nodes<'a, P>,
     terms: usize
 }
 impl<'a, P> Iterator for Terminals<'a, P> {
-    type Item = &'a Node<P>;
+    type Item = Node<'a, P>;
     fn next(&mut self) -> Option<Self::Item> {
         while let Some(n) = self.iter.next() {
             if n.is_terminal() {
@@ -110,11 +147,11 @@ impl<'a, P> Iterator for Terminals<'a, P> {
 impl<'a, P> ExactSizeIterator for Terminals<'a, P> {}
 
 pub struct SteinerPoints<'a, P: 'a> {
-    iter: IterMut<'a, Node<P>>,
+    iter: Nodes<'a, P>,
     steiner_points: usize
 }
 impl<'a, P> Iterator for SteinerPoints<'a, P> {
-    type Item = &'a mut Node<P>;
+    type Item = Node<'a, P>;
     fn next(&mut self) -> Option<Self::Item> {
         while let Some(n) = self.iter.next() {
             if !n.is_terminal() {
@@ -134,12 +171,12 @@ impl<'a, P> ExactSizeIterator for SteinerPoints<'a, P> {}
 
 
 pub struct NodePairs<'a, P: 'a> {
-    nodes: &'a [Node<P>],
+    nodes: &'a [NodeData<P>],
     i1: usize,
     i2: usize
 }
 impl<'a, P> NodePairs<'a, P> {
-    fn new(n: &'a [Node<P>]) -> NodePairs<'a, P> {
+    fn new(n: &'a [NodeData<P>]) -> NodePairs<'a, P> {
         NodePairs {
             nodes: n,
             i1: 0,
@@ -148,16 +185,14 @@ impl<'a, P> NodePairs<'a, P> {
     }
 }
 impl<'a, P> Iterator for NodePairs<'a, P> {
-    type Item = (&'a Node<P>, &'a Node<P>);
+    type Item = (Node<'a, P>, Node<'a, P>);
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.i1 == self.nodes.len() - 1 {
+        if self.i1 + 1 >= self.nodes.len() {
             return None;
         }
-        let ret = unsafe {
-            (self.nodes.get_unchecked(self.i1),
-             self.nodes.get_unchecked(self.i2))
-        };
+        let ret = (Node { nodes: self.nodes, id: self.i1 },
+                   Node { nodes: self.nodes, id: self.i2 });
         self.i2 += 1;
         if self.i2 == self.nodes.len() {
             self.i1 += 1;
@@ -167,8 +202,9 @@ impl<'a, P> Iterator for NodePairs<'a, P> {
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        (self.nodes.len()*(self.nodes.len()-1)/2,
-         Some(self.nodes.len()*(self.nodes.len()-1)/2))
+        let n = self.nodes.len();
+        let pairs = if n < 2 { 0 } else { n*(n-1)/2 };
+        (pairs, Some(pairs))
     }
 }
 impl<'a, P> ExactSizeIterator for NodePairs<'a, P> {}
@@ -176,28 +212,19 @@ impl<'a, P> ExactSizeIterator for NodePairs<'a, P> {}
 impl<P: Point> SteinerTree<P> {
     pub fn new(t: &[P], s: &[P], edges: &[(usize, usize)]) -> Self {
         let mut st = SteinerTree {
-            nodes: t.iter().map(|x| Node::new(x.clone(), true)).chain(
-                       s.iter().map(|x| Node::new(x.clone(), false))
+            nodes: t.iter().map(|x| NodeData::new(x.clone(), true)).chain(
+                       s.iter().map(|x| NodeData::new(x.clone(), false))
                    ).collect(),
             terminal_count: t.len()
         };
 
-        // TODO: Hack!! <- neighbour pointers are invalidated on reallocation
-        st.nodes.reserve(100);
-
-        for i in 0..st.nodes().len() {
-            st.nodes[i].id = i;
-        }
-
         for &(a, b) in edges.iter() {
             debug_assert!(a < t.len() + s.len());
             debug_assert!(b < t.len() + s.len());
             debug_assert!(a != b);
 
-            let aptr = &mut st.nodes[a] as *mut Node<P>;
-            let bptr = &mut st.nodes[b] as *mut Node<P>;
-            st.nodes[a].add_neighbour(bptr);
-            st.nodes[b].add_neighbour(aptr);
+            st.nodes[a].add_neighbour(b);
+            st.nodes[b].add_neighbour(a);
         }
 
         st
@@ -205,7 +232,7 @@ impl<P: Point> SteinerTree<P> {
 
     pub fn edges(&self) -> Edges<P> {
         Edges {
-            nodes: self.nodes.iter(),
+            nodes: self.nodes(),
             edges: None
         }
     }
@@ -214,44 +241,172 @@ impl<P: Point> SteinerTree<P> {
         NodePairs::new(&self.nodes[..])
     }
 
-    pub fn nodes(&self) -> Iter<Node<P>> {
-        self.nodes.iter()
+    pub fn nodes(&self) -> Nodes<P> {
+        Nodes {
+            nodes: &self.nodes[..],
+            iter: 0..self.nodes.len()
+        }
     }
 
     pub fn terminals(&self) -> Terminals<P> {
         Terminals {
-            iter: self.nodes.iter(),
+            iter: self.nodes(),
             terms: self.terminal_count
         }
     }
 
-    pub fn nodes_mut(&mut self) -> IterMut<Node<P>> {
-        self.nodes.iter_mut()
+    /// Mutable access to the positions of all nodes.
+    pub fn points_mut(&mut self) -> PointsMut<P> {
+        PointsMut {
+            iter: self.nodes.iter_mut()
+        }
+    }
+
+    /// Mutable access to the positions of all nodes, together with their
+    /// ids, in the manner of the former `&mut Node` iterator.
+    #[deprecated(note = "use `points_mut` or `steiner_i` instead")]
+    pub fn nodes_mut(&mut self) -> NodesMut<P> {
+        NodesMut {
+            iter: self.nodes.iter_mut().enumerate()
+        }
     }
 
-    pub fn steiner_points(&mut self) -> SteinerPoints<P> {
+    pub fn steiner_points(&self) -> SteinerPoints<P> {
         SteinerPoints {
             steiner_points: self.nodes.len() - self.terminal_count,
-            iter: self.nodes.iter_mut()
+            iter: self.nodes()
         }
     }
 
-    pub fn i(&self, i: usize) -> &Node<P> {
-        &self.nodes[i]
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
     }
 
-    pub fn steiner_i(&mut self, i: usize) -> &mut Node<P> {
-        //println!("steiner_i: i={}", i);
-        &mut self.nodes[i]
+    /// Approximate number of bytes the tree occupies, not counting memory
+    /// the points own themselves.
+    pub fn memory(&self) -> usize {
+        mem::size_of::<Self>()
+            + self.nodes.capacity() * mem::size_of::<NodeData<P>>()
+            + self.nodes.iter().map(|n| n.ns.capacity() * mem::size_of::<usize>()).sum::<usize>()
     }
 
-    pub fn non_degenerate(&mut self) {
-        //TODO
+    pub fn i(&self, i: usize) -> Node<P> {
+        debug_assert!(i < self.nodes.len());
+        Node {
+            nodes: &self.nodes[..],
+            id: i
+        }
+    }
+
+    pub fn steiner_i(&mut self, i: usize) -> NodeMut<P> {
+        debug_assert!(i < self.nodes.len());
+        NodeMut {
+            nodes: &mut self.nodes[..],
+            id: i
+        }
+    }
+
+    /// Removes the degeneracies of a full topology tree.
+    ///
+    /// Edges no longer than `eps` times the length of the tree (measured in
+    /// `geo`) incident to a Steiner point are contracted, merging the
+    /// Steiner point into its neighbour, and Steiner points of degree at
+    /// most 2 are removed. Terminals are
+    /// never removed. The remaining nodes keep their relative order but are
+    /// renumbered.
+    pub fn non_degenerate<M: MinkowskiSpace<P>>(&mut self, geo: &M, eps: P::R) {
+        let before = self.len(geo);
+        let eps = eps * before;
+        // Moving the other edges of a contracted Steiner point onto its
+        // neighbour lengthens each of them by at most the contracted edge
+        let mut growth = P::R::zero();
+        let mut alive = vec![true; self.nodes.len()];
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for s in 0..self.nodes.len() {
+                if !alive[s] || self.nodes[s].is_terminal {
+                    continue;
+                }
+
+                match self.nodes[s].ns.len() {
+                    0 => {},
+                    1 => {
+                        let n = self.nodes[s].ns[0];
+                        self.remove_edge((s, n));
+                    },
+                    2 => {
+                        let (a, b) = (self.nodes[s].ns[0], self.nodes[s].ns[1]);
+                        self.remove_edge((s, a));
+                        self.remove_edge((s, b));
+                        self.add_edge((a, b));
+                    },
+                    _ => {
+                        // Prefer merging into a terminal, so the terminal
+                        // keeps its exact position.
+                        let w = {
+                            let short = self.nodes[s].ns.iter().cloned()
+                                .filter(|&n| geo.dist(&self.nodes[n].p, &self.nodes[s].p) <= eps);
+                            let mut w = None;
+                            for n in short {
+                                if w.is_none() || self.nodes[n].is_terminal {
+                                    w = Some(n);
+                                }
+                            }
+                            match w {
+                                Some(w) => w,
+                                None => continue
+                            }
+                        };
+
+                        let d = geo.dist(&self.nodes[w].p, &self.nodes[s].p);
+                        let moved = P::R::from((self.nodes[s].ns.len() - 2) as f64);
+                        growth += moved * d;
+
+                        self.remove_edge((s, w));
+                        while let Some(&n) = self.nodes[s].ns.last() {
+                            self.remove_edge((s, n));
+                            self.add_edge((n, w));
+                        }
+                    }
+                }
+
+                alive[s] = false;
+                changed = true;
+            }
+        }
+
+        self.retain(&alive);
+        debug_assert!(self.len(geo) <= before + growth + before * P::R::from(1e-12));
+    }
+
+    /// Keeps the nodes `i` where `keep[i]` is true. Removed nodes must not
+    /// have any neighbours.
+    fn retain(&mut self, keep: &[bool]) {
+        debug_assert_eq!(keep.len(), self.nodes.len());
+
+        let mut map = vec![0; self.nodes.len()];
+        let mut next = 0;
+        for (i, &k) in keep.iter().enumerate() {
+            debug_assert!(k || self.nodes[i].ns.is_empty());
+            map[i] = next;
+            if k {
+                next += 1;
+            }
+        }
+
+        let mut i = 0;
+        self.nodes.retain(|_| { i += 1; keep[i - 1] });
+        for node in self.nodes.iter_mut() {
+            for n in node.ns.iter_mut() {
+                *n = map[*n];
+            }
+        }
+        self.terminal_count = self.nodes.iter().filter(|n| n.is_terminal).count();
     }
 
     pub fn remove_edge(&mut self, (a, b): (usize, usize)) {
-        //println!("remove_edge: ({}, {})", a, b);
-        //stdout().flush();
         debug_assert!(a < self.nodes.len());
         debug_assert!(b < self.nodes.len());
 
@@ -260,63 +415,56 @@ impl<P: Point> SteinerTree<P> {
     }
 
     pub fn add_edge(&mut self, (a, b): (usize, usize)) {
-        //println!("add_edge: ({}, {})", a, b);
-        //stdout().flush();
         debug_assert!(a < self.nodes.len());
         debug_assert!(b < self.nodes.len());
+        debug_assert!(a != b);
 
-        let aptr = &mut self.nodes[a] as *mut Node<P>;
-        let bptr = &mut self.nodes[b] as *mut Node<P>;
-        self.nodes[a].add_neighbour(bptr);
-        self.nodes[b].add_neighbour(aptr);
+        self.nodes[a].add_neighbour(b);
+        self.nodes[b].add_neighbour(a);
     }
 
-    pub fn push_node(&mut self, p: P, ns: &[usize], is_terminal: bool) -> &Node<P> {
-        //println!("add_node: [ {:?} ]", ns);
-        //stdout().flush();
-        let mut node = Node::new(p, is_terminal);
-        node.id = self.nodes.len();
+    pub fn push_node(&mut self, p: P, ns: &[usize], is_terminal: bool) -> Node<P> {
+        let id = self.nodes.len();
+        let mut node = NodeData::new(p, is_terminal);
 
         for &n in ns {
             debug_assert!(n < self.nodes.len());
-            node.add_neighbour(&mut self.nodes[n]);
+            node.add_neighbour(n);
+            self.nodes[n].add_neighbour(id);
         }
         self.nodes.push(node);
-        for &n in ns {
-            let ptr = self.nodes.last_mut().unwrap() as *mut Node<P>;
-            self.nodes[n].add_neighbour(ptr);
-        }
 
         if is_terminal {
             self.terminal_count += 1;
         } else {
-            self.nodes.last_mut().unwrap().init();
+            self.steiner_i(id).init();
         }
 
-        self.nodes.last().unwrap()
+        self.i(id)
     }
 
     pub fn pop_node(&mut self) -> Option<P> {
-        //println!("remove_node: {}", id);
-        //stdout().flush();
-
-        if let Some(node) = self.nodes.last() {
-            if node.is_terminal() {
+        self.nodes.pop().map(|node| {
+            if node.is_terminal {
                 self.terminal_count -= 1;
             }
 
-            // Remove pointers to the node
-            for &ptr in node.ns.iter() {
-                let n = unsafe { &mut *ptr };
-                n.remove_neighbour(node.id());
+            // Remove references to the node
+            let id = self.nodes.len();
+            for &n in node.ns.iter() {
+                self.nodes[n].remove_neighbour(id);
             }
-        }
 
-        self.nodes.pop().map(|node| node.p)
+            node.p
+        })
     }
 
-    pub fn last_node(&self) -> Option<&Node<P>> {
-        self.nodes.last()
+    pub fn last_node(&self) -> Option<Node<P>> {
+        if self.nodes.is_empty() {
+            None
+        } else {
+            Some(self.i(self.nodes.len() - 1))
+        }
     }
 }
 
@@ -324,27 +472,44 @@ impl<P: Point> SteinerTree<P> {
     pub fn len<M: MinkowskiSpace<P>>(&self, geo: &M) -> P::R {
         self.edges().fold(P::R::zero(), |acc, e| acc + e.len(geo))
     }
-}
-
-impl<P: Point> Clone for SteinerTree<P> {
-    fn clone(&self) -> Self {
-        let mut nodes = Vec::with_capacity(self.nodes.len());
-        for node in self.nodes() {
-            nodes.push(node.clone());
-        }
 
-        for i in 0..nodes.len() {
-            for n in 0..nodes[i].ns.len() {
-                let id = unsafe { (*nodes[i].ns[n]).id() };
-                let ptr = &mut nodes[id] as *mut Node<P>;
-                nodes[i].ns[n] = ptr;
+    /// The bottleneck distances of all pairs of nodes, i.e. the length of
+    /// the longest edge on the path between them, indexed by node ids. For
+    /// a minimum spanning tree these are the bottleneck Steiner distances.
+    /// Nodes in different components get `P::R::zero()`. If the edges form
+    /// a cycle, the first path found is used.
+    ///
+    /// Takes O(n²) time with one traversal of the tree per node. The
+    /// spanning tree is not included: with `Kruskal` it takes O(n² log n)
+    /// time, which dominates the bottleneck distances of
+    /// `GPEnumeration`.
+    pub fn bottleneck_distances<M: MinkowskiSpace<P>>(&self, geo: &M) -> Vec<Vec<P::R>> {
+        let n = self.nodes.len();
+        let adjacent: Vec<Vec<(usize, P::R)>> = self.nodes().map(|node|
+            node.edges().map(|e| (e.nodes().1.id(), e.len(geo))).collect()
+        ).collect();
+
+        let mut bsd = vec![vec![P::R::zero(); n]; n];
+        let mut stack = Vec::with_capacity(n);
+        let mut visited = vec![false; n];
+        for root in 0..n {
+            let row = &mut bsd[root];
+            for v in visited.iter_mut() {
+                *v = false;
+            }
+            visited[root] = true;
+            stack.push((root, P::R::zero()));
+            while let Some((node, b)) = stack.pop() {
+                row[node] = b;
+                for &(next, len) in adjacent[node].iter() {
+                    if !visited[next] {
+                        visited[next] = true;
+                        stack.push((next, b.max(len)));
+                    }
+                }
             }
         }
-
-        SteinerTree {
-            nodes: nodes,
-            terminal_count: self.terminal_count
-        }
+        bsd
     }
 }
 
@@ -369,12 +534,12 @@ impl<E: Point + fmt::Display> fmt::Display for SteinerTree<E> {
 
 #[derive(Debug)]
 pub struct Edge<'a, P: 'a> {
-    n1: &'a Node<P>,
-    n2: &'a Node<P>
+    n1: Node<'a, P>,
+    n2: Node<'a, P>
 }
 
 impl<'a, P: 'a> Edge<'a, P> {
-    pub fn nodes(&self) -> (&Node<P>, &Node<P>) {
+    pub fn nodes(&self) -> (Node<'a, P>, Node<'a, P>) {
         (self.n1, self.n2)
     }
 }
@@ -385,13 +550,85 @@ impl<'a, P: 'a + Point> Edge<'a, P> {
     }
 }
 
+pub struct PointsMut<'a, P: 'a> {
+    iter: IterMut<'a, NodeData<P>>
+}
+impl<'a, P> Iterator for PointsMut<'a, P> {
+    type Item = &'a mut P;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|n| &mut n.p)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+impl<'a, P> ExactSizeIterator for PointsMut<'a, P> {}
+
+pub struct NodesMut<'a, P: 'a> {
+    iter: Enumerate<IterMut<'a, NodeData<P>>>
+}
+impl<'a, P> Iterator for NodesMut<'a, P> {
+    type Item = PointMut<'a, P>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(i, n)| PointMut {
+            p: &mut n.p,
+            id: i,
+            is_terminal: n.is_terminal
+        })
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+impl<'a, P> ExactSizeIterator for NodesMut<'a, P> {}
+
+/// The position of a node together with its id, as handed out by the
+/// deprecated `SteinerTree::nodes_mut`.
+pub struct PointMut<'a, P: 'a> {
+    p: &'a mut P,
+    id: usize,
+    is_terminal: bool
+}
+
+impl<'a, P> PointMut<'a, P> {
+    pub fn p(&self) -> &P {
+        &*self.p
+    }
+
+    #[deprecated(note = "use `SteinerTree::steiner_i(i).p_mut()` instead")]
+    pub fn p_mut(&mut self) -> &mut P {
+        &mut *self.p
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        self.is_terminal
+    }
+}
+
 pub struct NeighboursMut<'a, P: 'a> {
-    iter: Iter<'a, *mut Node<P>>,
+    before: &'a mut [NodeData<P>],
+    after: &'a mut [NodeData<P>],
+    iter: Iter<'a, usize>
 }
 impl<'a, P> Iterator for NeighboursMut<'a, P> {
-    type Item = &'a mut Node<P>;
+    type Item = &'a mut P;
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next().map(|&n| unsafe { &mut *n })
+        let n = match self.iter.next() {
+            Some(&n) => n,
+            None => return None
+        };
+        let node = if n < self.before.len() {
+            &mut self.before[n]
+        } else {
+            &mut self.after[n - self.before.len() - 1]
+        };
+        // The neighbours are distinct, see `NodeMut::neighbours_mut`, so
+        // every node is handed out at most once.
+        Some(unsafe { &mut *(&mut node.p as *mut P) })
     }
     fn size_hint(&self) -> (usize, Option<usize>) {
         self.iter.size_hint()
@@ -400,12 +637,14 @@ impl<'a, P> Iterator for NeighboursMut<'a, P> {
 impl<'a, P> ExactSizeIterator for NeighboursMut<'a, P> {}
 
 pub struct Neighbours<'a, P: 'a> {
-    iter: Iter<'a, *mut Node<P>>,
+    nodes: &'a [NodeData<P>],
+    iter: Iter<'a, usize>,
 }
 impl<'a, P> Iterator for Neighbours<'a, P> {
-    type Item = &'a Node<P>;
+    type Item = Node<'a, P>;
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next().map(|&n| unsafe { &*n })
+        let nodes = self.nodes;
+        self.iter.next().map(|&n| Node { nodes: nodes, id: n })
     }
     fn size_hint(&self) -> (usize, Option<usize>) {
         self.iter.size_hint()
@@ -414,12 +653,21 @@ impl<'a, P> Iterator for Neighbours<'a, P> {
 impl<'a, P> ExactSizeIterator for Neighbours<'a, P> {}
 
 pub struct NeighboursData<'a, P: 'a> {
-    iter: Iter<'a, *mut Node<P>>,
+    before: &'a [NodeData<P>],
+    after: &'a [NodeData<P>],
+    iter: Iter<'a, usize>,
 }
 impl<'a, P> Iterator for NeighboursData<'a, P> {
     type Item = &'a P;
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next().map(|&n| unsafe { &*n }.p())
+        let (before, after) = (self.before, self.after);
+        self.iter.next().map(|&n| {
+            if n < before.len() {
+                &before[n].p
+            } else {
+                &after[n - before.len() - 1].p
+            }
+        })
     }
     fn size_hint(&self) -> (usize, Option<usize>) {
         self.iter.size_hint()
@@ -429,12 +677,13 @@ impl<'a, P> ExactSizeIterator for NeighboursData<'a, P> {}
 
 pub struct IncidentEdges<'a, P: 'a> {
     iter: Neighbours<'a, P>,
-    node: &'a Node<P>
+    node: Node<'a, P>
 }
 impl<'a, P> Iterator for IncidentEdges<'a, P> {
     type Item = Edge<'a, P>;
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next().map(|n| Edge { n1: &self.node, n2: n})
+        let node = self.node;
+        self.iter.next().map(|n| Edge { n1: node, n2: n})
     }
     fn size_hint(&self) -> (usize, Option<usize>) {
         self.iter.size_hint()
@@ -442,25 +691,25 @@ impl<'a, P> Iterator for IncidentEdges<'a, P> {
 }
 impl<'a, P> ExactSizeIterator for IncidentEdges<'a, P> {}
 
-impl<P: Point> Node<P> {
+impl<'a, P: Point> NodeMut<'a, P> {
     pub fn init(&mut self) {
-        let mut p = self.p.clone();
+        let mut p = self.p().clone();
         p.mul(P::R::zero());
         let num = P::R::from(self.neighbours().len() as f64);
         for n in self.neighbours() {
             p.add(n.p().clone().div(num));
         }
-        self.p = p;
+        *self.p_mut() = p;
     }
 }
 
-impl<P> Node<P> {
-    pub fn p(&self) -> &P {
-        &self.p
+impl<'a, P> Node<'a, P> {
+    fn data(&self) -> &'a NodeData<P> {
+        &self.nodes[self.id]
     }
 
-    pub fn p_mut(&mut self) -> &mut P {
-        &mut self.p
+    pub fn p(&self) -> &'a P {
+        &self.data().p
     }
 
     pub fn id(&self) -> usize {
@@ -468,40 +717,304 @@ impl<P> Node<P> {
     }
 
     pub fn is_terminal(&self) -> bool {
-        self.is_terminal
+        self.data().is_terminal
     }
 
-    pub fn edges(&self) -> IncidentEdges<P> {
+    pub fn degree(&self) -> usize {
+        self.data().ns.len()
+    }
+
+    pub fn edges(&self) -> IncidentEdges<'a, P> {
         IncidentEdges {
             iter: self.neighbours(),
-            node: &self
+            node: *self
         }
     }
 
-    pub fn neighbours(&self) -> Neighbours<P> {
+    pub fn neighbours(&self) -> Neighbours<'a, P> {
         Neighbours {
-            iter: self.ns.iter()
+            nodes: self.nodes,
+            iter: self.data().ns.iter()
         }
     }
+}
+
+impl<'a, P> NodeMut<'a, P> {
+    pub fn as_node(&self) -> Node<P> {
+        Node {
+            nodes: &*self.nodes,
+            id: self.id
+        }
+    }
+
+    pub fn p(&self) -> &P {
+        &self.nodes[self.id].p
+    }
+
+    pub fn p_mut(&mut self) -> &mut P {
+        &mut self.nodes[self.id].p
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        self.nodes[self.id].is_terminal
+    }
+
+    pub fn degree(&self) -> usize {
+        self.nodes[self.id].ns.len()
+    }
+
+    pub fn edges(&self) -> IncidentEdges<P> {
+        self.as_node().edges()
+    }
+
+    pub fn neighbours(&self) -> Neighbours<P> {
+        self.as_node().neighbours()
+    }
 
     pub fn neighbours_data_mut(&mut self) -> (&mut P, NeighboursData<P>) {
-        (&mut self.p, NeighboursData {
-            iter: self.ns.iter()
+        let (before, rest) = self.nodes.split_at_mut(self.id);
+        let (node, after) = rest.split_first_mut().unwrap();
+        let NodeData { ref mut p, ref ns, .. } = *node;
+        (p, NeighboursData {
+            before: before,
+            after: after,
+            iter: ns.iter()
         })
     }
 
+    /// The points of the neighbours in the order of `neighbours`. Panics
+    /// if a neighbour is repeated.
     pub fn neighbours_mut(&mut self) -> NeighboursMut<P> {
+        let id = self.id;
+        let (before, rest) = self.nodes.split_at_mut(id);
+        let (node, after) = rest.split_first_mut().unwrap();
+        let ns = &node.ns;
+        assert!((0..ns.len()).all(|i| ns[i] != id && !ns[..i].contains(&ns[i])),
+            "node {} has repeated neighbours {:?}", id, ns);
         NeighboursMut {
-            iter: self.ns.iter()
+            before: before,
+            after: after,
+            iter: ns.iter()
         }
     }
 }
 
-impl<P: fmt::Display> fmt::Display for Node<P> {
+impl<'a, P: fmt::Display> fmt::Display for Node<'a, P> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        try!(write!(f, "{} [ ", self.p));
+        try!(write!(f, "{} [ ", self.p()));
         try!(self.neighbours().fold(Ok(()), |_: fmt::Result, n|
             write!(f, "#{} ", n.id())));
         write!(f, "]")
     }
 }
+
+impl<'a, P: fmt::Display> fmt::Display for NodeMut<'a, P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.as_node().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::points::Point2d;
+
+    /// A path through `n` terminals on the x-axis.
+    fn path(n: usize) -> SteinerTree<Point2d<f64>> {
+        let ts: Vec<_> = (0..n).map(|i| Point2d::new([i as f64, 0.0])).collect();
+        let es: Vec<_> = (1..n).map(|i| (i - 1, i)).collect();
+        SteinerTree::new(&ts, &[], &es)
+    }
+
+    #[test]
+    fn grows_beyond_reserved_size() {
+        let mut st = path(2);
+        for i in 0..500 {
+            let last = st.node_count() - 1;
+            st.push_node(Point2d::new([i as f64, 1.0]), &[last], false);
+        }
+        assert_eq!(st.node_count(), 502);
+        assert_eq!(st.edges().count(), 501);
+        assert_eq!(st.i(501).neighbours().next().unwrap().id(), 500);
+        assert_eq!(st.i(300).degree(), 2);
+
+        for _ in 0..500 {
+            st.pop_node();
+        }
+        assert_eq!(st.node_count(), 2);
+        assert_eq!(st.i(1).degree(), 1);
+        assert_eq!(st.terminals().len(), 2);
+    }
+
+    #[test]
+    fn push_node_starts_at_centroid() {
+        let ts = [Point2d::new([0.0, 0.0]), Point2d::new([3.0, 0.0]), Point2d::new([0.0, 3.0])];
+        let mut st = SteinerTree::new(&ts, &[], &[]);
+        let s = st.push_node(Point2d::new([7.0, 7.0]), &[0, 1, 2], false).id();
+        assert_eq!(*st.i(s).p(), Point2d::new([1.0, 1.0]));
+        assert_eq!(st.steiner_points().len(), 1);
+    }
+
+    #[test]
+    fn clone_is_independent() {
+        let st = path(4);
+        let mut copy = st.clone();
+        copy.remove_edge((1, 2));
+        *copy.steiner_i(0).p_mut() = Point2d::new([9.0, 9.0]);
+        assert_eq!(st.edges().count(), 3);
+        assert_eq!(copy.edges().count(), 2);
+        assert_eq!(*st.i(0).p(), Point2d::new([0.0, 0.0]));
+    }
+
+    #[test]
+    fn neighbours_mut_touches_only_neighbours() {
+        let ts: Vec<_> = (0..5).map(|i| Point2d::new([i as f64, 0.0])).collect();
+        let mut st = SteinerTree::new(&ts, &[], &[(2, 4), (2, 0), (2, 3)]);
+        {
+            let mut node = st.steiner_i(2);
+            assert_eq!(node.neighbours_mut().len(), 3);
+            let order: Vec<f64> = node.neighbours_mut().map(|q| q.coords()[0]).collect();
+            assert_eq!(order, node.neighbours().map(|n| n.p().coords()[0]).collect::<Vec<_>>());
+            assert_eq!(order, vec![4.0, 0.0, 3.0]);
+            for q in node.neighbours_mut() {
+                q.add(&Point2d::new([0.0, 1.0]));
+            }
+        }
+        let ys: Vec<f64> = st.nodes().map(|n| n.p().coords()[1]).collect();
+        assert_eq!(ys, vec![1.0, 0.0, 0.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn neighbours_mut_rejects_double_edges() {
+        let mut st = path(3);
+        st.add_edge((0, 1));
+        st.steiner_i(1).neighbours_mut().count();
+    }
+
+    #[test]
+    fn neighbours_data_mut_skips_the_node() {
+        let mut st = path(3);
+        let mut node = st.steiner_i(1);
+        let (q, ns) = node.neighbours_data_mut();
+        let xs: Vec<f64> = ns.map(|n| n.coords()[0]).collect();
+        assert_eq!(xs, vec![0.0, 2.0]);
+        assert_eq!(q.coords()[0], 1.0);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn nodes_mut_shim() {
+        let mut st = path(3);
+        for mut n in st.nodes_mut() {
+            let x = n.id() as f64 * 2.0;
+            *n.p_mut() = Point2d::new([x, 0.0]);
+        }
+        assert_eq!(st.len(&::geo::spaces::EuclideanSpace::new()), 4.0);
+    }
+
+    /// Full topology over four terminals whose two Steiner points have
+    /// collapsed onto terminal 0 and the centre, scaled by `f`.
+    fn collapsed(f: f64) -> SteinerTree<Point2d<f64>> {
+        let ts = [Point2d::new([0.0, 0.0]), Point2d::new([2.0 * f, 0.0]),
+                  Point2d::new([0.0, 2.0 * f]), Point2d::new([-2.0 * f, 0.0])];
+        let ss = [Point2d::new([1e-9 * f, 0.0]), Point2d::new([1e-9 * f, 1e-9 * f])];
+        SteinerTree::new(&ts, &ss, &[(0, 4), (1, 4), (4, 5), (2, 5), (3, 5)])
+    }
+
+    #[test]
+    fn non_degenerate_contracts_short_edges() {
+        let geo = ::geo::spaces::EuclideanSpace::new();
+        let mut st = collapsed(1.0);
+        let len = st.len(&geo);
+        st.non_degenerate(&geo, 1e-5);
+
+        assert_eq!(st.node_count(), 4);
+        assert_eq!(st.steiner_points().len(), 0);
+        assert_eq!(st.i(0).degree(), 3);
+        assert!((st.len(&geo) - len).abs() < 1e-6);
+    }
+
+    #[test]
+    fn non_degenerate_removes_degree_two() {
+        let geo = ::geo::spaces::EuclideanSpace::new();
+        let ts = [Point2d::new([0.0, 0.0]), Point2d::new([2.0, 0.0])];
+        let mut st = SteinerTree::new(&ts, &[Point2d::new([1.0, 0.0])], &[(0, 2), (2, 1)]);
+        st.non_degenerate(&geo, 1e-5);
+        assert_eq!(st.node_count(), 2);
+        assert_eq!(st.edges().count(), 1);
+        assert_eq!(st.len(&geo), 2.0);
+    }
+
+    #[test]
+    fn non_degenerate_is_scale_invariant() {
+        let geo = ::geo::spaces::EuclideanSpace::new();
+        for &f in [1e-8, 1.0, 1e8].iter() {
+            let mut st = collapsed(f);
+            st.non_degenerate(&geo, 1e-5);
+            assert_eq!(st.node_count(), 4);
+        }
+
+        // A proper Steiner point survives at any scale
+        for &f in [1e-8, 1.0, 1e8].iter() {
+            let ts = [Point2d::new([0.0, 0.0]), Point2d::new([f, 0.0]), Point2d::new([0.5 * f, f])];
+            let mut st = SteinerTree::new(&ts, &[Point2d::new([0.5 * f, 0.3 * f])], &[(0, 3), (1, 3), (2, 3)]);
+            st.non_degenerate(&geo, 1e-5);
+            assert_eq!(st.node_count(), 4);
+        }
+    }
+
+    /// The bottleneck distance of `goal` from `start` by the recursive
+    /// search `GPEnumeration` used before `bottleneck_distances`.
+    fn dfs_bottleneck<M, P>(start: &Node<P>, goal: &Node<P>, prev: &Node<P>, b: P::R, geo: &M) -> (bool, P::R)
+        where P: Point, M: MinkowskiSpace<P> {
+
+        for e in start.edges().filter(|e| e.nodes().1.id() != prev.id()) {
+            if e.nodes().1.id() == goal.id() {
+                return (true, b.max(e.len(geo)));
+            }
+            if let res @ (true, _) = dfs_bottleneck(&e.nodes().1, goal, start, b.max(e.len(geo)), geo) {
+                return res;
+            }
+        }
+        (false, P::R::zero())
+    }
+
+    #[test]
+    fn bottleneck_distances_match_the_recursive_search() {
+        use algorithms::mst::Kruskal;
+        use generators::Generator;
+
+        let geo = ::geo::spaces::EuclideanSpace::new();
+        for seed in 0..5 {
+            let ts: Vec<Point2d<f64>> = Generator::new(seed, 2).uniform(30);
+            let mut mst = Kruskal::new().find(&ts, &geo);
+            mst.push_node(Point2d::new([0.5, 0.5]), &[0], false);
+            let bsd = mst.bottleneck_distances(&geo);
+            for (a, b) in mst.node_pairs() {
+                let (found, d) = dfs_bottleneck(&a, &b, &a, 0.0, &geo);
+                assert!(found);
+                assert_eq!(bsd[a.id()][b.id()], d);
+                assert_eq!(bsd[b.id()][a.id()], d);
+            }
+            assert!((0..mst.node_count()).all(|i| bsd[i][i] == 0.0));
+        }
+    }
+
+    #[test]
+    fn bottleneck_distances_of_cycles_and_forests() {
+        let geo = ::geo::spaces::EuclideanSpace::new();
+        let ts = [Point2d::new([0.0, 0.0]), Point2d::new([1.0, 0.0]), Point2d::new([1.0, 2.0]),
+                  Point2d::new([5.0, 5.0]), Point2d::new([5.0, 6.0])];
+        let st = SteinerTree::new(&ts, &[], &[(0, 1), (1, 2), (2, 0), (3, 4)]);
+        let bsd = st.bottleneck_distances(&geo);
+        assert_eq!(bsd[0][1], 1.0);
+        assert_eq!(bsd[3][4], 1.0);
+        assert_eq!(bsd[0][3], 0.0);
+        assert!(bsd[0][2] == 2.0 || bsd[0][2] == 5f64.sqrt());
+    }
+}
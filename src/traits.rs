@@ -1,11 +1,14 @@
 use std::fmt;
+use std::cmp::Ordering;
 use std::slice::{Iter, IterMut};
 use std::ops::*;
-use steinertree::{SteinerTree, Node};
+use steinertree::{SteinerTree, NodeMut};
 use std::time::{Duration};
 use std::io::{self, Write};
 
 use geo::spaces::{EuclideanSpace};
+use error::{self, SteinerError};
+use stats::{Stats};
 
 pub trait Point : PartialEq + Clone + fmt::Display + fmt::Debug {
     type R : Real;
@@ -90,6 +93,14 @@ pub trait Point : PartialEq + Clone + fmt::Display + fmt::Debug {
     }
 }
 
+/// Points that can be built from a list of coordinates, such as points read
+/// from a file.
+pub trait FromCoords: Point + Sized {
+    /// Returns `None` if the number of coordinates does not match the
+    /// dimension of the point type.
+    fn from_coords(c: &[Self::R]) -> Option<Self>;
+}
+
 pub trait Real : Neg<Output=Self> + Add<Output=Self>
                 + Sub<Output=Self> + Mul<Output=Self>
                 + Div<Output=Self> + Rem<Output=Self>
@@ -107,6 +118,16 @@ pub trait Real : Neg<Output=Self> + Add<Output=Self>
     fn sqrt(self) -> Self;
     fn pow(self, Self) -> Self;
     fn is_number(&self) -> bool;
+
+    /// Total order that puts NaN after every number, so that sorting the
+    /// values computed from invalid input does not panic.
+    fn cmp_nan_last(&self, o: &Self) -> Ordering {
+        match self.partial_cmp(o) {
+            Some(ord) => ord,
+            // Only NaN is unequal to itself
+            None => (self == self).cmp(&(o == o)).reverse()
+        }
+    }
 }
 
 pub trait Hyperplane<P: Point> {
@@ -180,12 +201,30 @@ pub trait Enumerator<P: Point>: fmt::Display {
     type D: EnumeratorData;
 
     fn init<M: MinkowskiSpace<P>>(&mut self, Vec<P>, &M);
+    /// Like `init`, but checks the terminals first.
+    fn try_init<M: MinkowskiSpace<P>>(&mut self, terminals: Vec<P>, geo: &M) -> Result<(), SteinerError> {
+        try!(error::check_terminals(&terminals));
+        self.init(terminals, geo);
+        Ok(())
+    }
     fn next<M: MinkowskiSpace<P>>(&mut self, &M) -> bool;
+    /// Skips the topologies below the current one, i.e. the next call to
+    /// `next` will not extend the current topology.
+    fn prune(&mut self);
     fn tree(&self) -> &SteinerTree<P>;
     fn tree_mut(&mut self) -> &mut SteinerTree<P>;
+    /// Number of topologies the enumerator keeps, including the current
+    /// one, each about the size of `tree()`.
+    fn stored(&self) -> usize {
+        1
+    }
     fn data(&self) -> &Self::D;
     fn print(&self, &mut fmt::Formatter, u32) -> fmt::Result;
     fn print_data<W: Write>(&self, &mut W) -> io::Result<()>;
+    /// The statistics of `print_data` in machine-readable form.
+    fn report(&self) -> Stats {
+        self.data().report()
+    }
 }
 
 pub trait TerminalSorter: fmt::Display {
@@ -198,63 +237,115 @@ pub trait RMT<P: Point, M: MinkowskiSpace<P>>: fmt::Display {
     type D: RmtData;
 
     fn find(&mut self, tree: &mut SteinerTree<P>, geo: &M) -> P::R;
+    /// Like `find`, but checks the terminals of `tree` first and fails if
+    /// the resulting length is not finite.
+    fn try_find(&mut self, tree: &mut SteinerTree<P>, geo: &M) -> Result<P::R, SteinerError> {
+        let ts: Vec<P> = tree.terminals().map(|n| n.p().clone()).collect();
+        try!(error::check_terminals(&ts));
+        let len = self.find(tree, geo);
+        if len.is_number() { Ok(len) } else { Err(SteinerError::NonFiniteLength) }
+    }
     fn data(&self) -> &Self::D;
     fn print(&self, &mut fmt::Formatter, u32) -> fmt::Result;
     fn print_data<W: Write>(&self, &mut W) -> io::Result<()>;
+    /// The statistics of `print_data` in machine-readable form.
+    fn report(&self) -> Stats {
+        self.data().report()
+    }
 }
 
 pub trait SMT<P: Point, M: MinkowskiSpace<P>>: fmt::Display {
     type D: SmtData;
 
     fn find(&mut self, Vec<P>, geo: &M) -> SteinerTree<P>;
+    /// Like `find`, but checks the terminals first and fails if the
+    /// resulting tree has no finite length.
+    fn try_find(&mut self, terminals: Vec<P>, geo: &M) -> Result<SteinerTree<P>, SteinerError> {
+        try!(error::check_terminals(&terminals));
+        let tree = self.find(terminals, geo);
+        if tree.len(geo).is_number() { Ok(tree) } else { Err(SteinerError::NonFiniteLength) }
+    }
     fn data(&self) -> &Self::D;
     fn print(&self, &mut fmt::Formatter, u32) -> fmt::Result;
     fn print_data<W: Write>(&self, &mut W) -> io::Result<()>;
+    /// The statistics of `print_data` in machine-readable form.
+    fn report(&self) -> Stats {
+        self.data().report()
+    }
 }
 
 pub trait MST<P: Point, M: MinkowskiSpace<P>> {
     fn find(&mut self, &[P], geo: &M) -> SteinerTree<P>;
+    /// Like `find`, but checks the terminals first. Duplicates are allowed
+    /// here, they are joined by edges of length zero.
+    fn try_find(&mut self, terminals: &[P], geo: &M) -> Result<SteinerTree<P>, SteinerError> {
+        try!(error::check_points(terminals));
+        Ok(self.find(terminals, geo))
+    }
 }
 
 pub trait GeoMedian<P: Point, M: MinkowskiSpace<P>>: fmt::Display {
     type D: GeoMedianData;
 
-    fn find(&mut self, &mut Node<P>, &M);
-    fn init(&mut self, &mut Node<P>, &M);
+    fn find(&mut self, &mut NodeMut<P>, &M);
+    /// Like `find`, but fails instead of panicking on a Steiner point the
+    /// algorithm cannot handle, leaving it in place.
+    fn try_find(&mut self, node: &mut NodeMut<P>, geo: &M) -> Result<(), SteinerError> {
+        self.find(node, geo);
+        Ok(())
+    }
+    fn init(&mut self, &mut NodeMut<P>, &M);
     fn data(&self) -> &Self::D;
     fn print(&self, &mut fmt::Formatter, u32) -> fmt::Result;
     fn print_data<W: Write>(&self, &mut W) -> io::Result<()>;
+    /// The statistics of `print_data` in machine-readable form.
+    fn report(&self) -> Stats {
+        self.data().report()
+    }
 }
 
 pub trait GeoMedianStep<P: Point, M: MinkowskiSpace<P>>: fmt::Display {
     type D: GeoMedianStepData;
 
-    fn step(&mut self, &mut P, &mut Node<P>, usize, &M);
-    fn init(&mut self, &mut Node<P>, &M);
+    fn step(&mut self, &mut P, &mut NodeMut<P>, usize, &M);
+    fn init(&mut self, &mut NodeMut<P>, &M);
     fn data(&self) -> &Self::D;
     fn print(&self, &mut fmt::Formatter, u32) -> fmt::Result;
     fn print_data<W: Write>(&self, &mut W) -> io::Result<()>;
+    /// The statistics of `print_data` in machine-readable form.
+    fn report(&self) -> Stats {
+        self.data().report()
+    }
 }
 
-pub trait RmtData: fmt::Display + Clone  {
+pub trait RmtData: fmt::Display + Clone + Report {
     fn nodes(&self) -> usize;
     fn time(&self) -> &Duration;
 }
 
-pub trait SmtData: fmt::Display + Clone {
+pub trait SmtData: fmt::Display + Clone + Report {
     fn time(&self) -> &Duration;
     fn best_updates(&self) -> u64;
 }
 
-pub trait GeoMedianData: fmt::Display + Clone {
+pub trait GeoMedianData: fmt::Display + Clone + Report {
     fn time(&self) -> &Duration;
+    /// Number of medians whose iteration was stopped at its step limit.
+    fn unconverged(&self) -> u64 {
+        0
+    }
 }
 
-pub trait GeoMedianStepData: fmt::Display + Clone {
+pub trait GeoMedianStepData: fmt::Display + Clone + Report {
 }
 
-pub trait EnumeratorData: fmt::Display + Clone {
+pub trait EnumeratorData: fmt::Display + Clone + Report {
     fn nodes(&self) -> usize;
     fn pruned(&self) -> usize;
     fn time(&self) -> &Duration;
 }
+
+/// Statistics that can be reported as `Stats`.
+pub trait Report {
+    fn report(&self) -> Stats;
+}
@@ -1,6 +1,6 @@
 use traits::*;
 use steinertree::{SteinerTree};
-use algorithms::mst::Kruskal;
+use algorithms::mst::{Kruskal};
 
 #[derive(Debug)]
 pub struct LineTree;
@@ -10,7 +10,7 @@ impl<P, M> UpperBound<P, M> for LineTree
     fn bound(&self, t: Vec<P>, _: &M) -> SteinerTree<P> {
         let mut edges = Vec::new();
         let mut i = 0;
-        while i < t.len() - 1 {
+        while i + 1 < t.len() {
             edges.push((i, i+1));
             i += 1;
         }
@@ -32,3 +32,29 @@ impl<P: Point, M: MinkowskiSpace<P>> UpperBound<P, M> for MSTBound {
         Kruskal::new().find(&t[..], geo)
     }
 }
+
+impl Default for MSTBound {
+    fn default() -> Self {
+        MSTBound
+    }
+}
+
+/// The minimum spanning tree of the terminals like `MSTBound`, found with
+/// another MST algorithm, e.g. `Prim` for many terminals or `DelaunayMST`
+/// in the Euclidean plane.
+#[derive(Debug, Clone, Copy)]
+pub struct MSTBoundWith<T> {
+    mst: T
+}
+impl<T> MSTBoundWith<T> {
+    pub fn new(mst: T) -> Self {
+        MSTBoundWith { mst: mst }
+    }
+}
+impl<P, M, T> UpperBound<P, M> for MSTBoundWith<T>
+    where P: Point, M: MinkowskiSpace<P>, T: MST<P, M> + Clone {
+
+    fn bound(&self, t: Vec<P>, geo: &M) -> SteinerTree<P> {
+        self.mst.clone().find(&t[..], geo)
+    }
+}